@@ -0,0 +1,256 @@
+use axum::http::Method;
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+use utoipa::ToSchema;
+
+use crate::commands;
+use crate::history::unix_now;
+
+// ── Scopes ────────────────────────────────────────────────────────────────────
+
+/// What an API key may do. Each scope includes the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// GET routes and the WebSocket.
+    Read,
+    /// Plus sending bot commands (`/bots/{id}/cmd`, `/bots/cmd`, `/groups/{name}/cmd`).
+    BotControl,
+    /// Plus spawning, importing and removing bots, autostart, tags, groups, schedules and stored scripts.
+    BotManagement,
+}
+
+impl Scope {
+    /// Whether a key with this scope may call `method path`. User and session management,
+    /// the audit log, the vault and key management itself are never reachable with a key.
+    pub fn permits(self, method: &Method, path: &str) -> bool {
+        let never = path == "/audit"
+            || path == "/users"
+            || path.starts_with("/users/")
+            || path.starts_with("/vault/")
+            || path == "/api-keys"
+            || path.starts_with("/api-keys/")
+            || path.starts_with("/auth/");
+        if never {
+            return false;
+        }
+        if matches!(*method, Method::GET | Method::HEAD) {
+            return true;
+        }
+        if commands::is_cmd_path(path) {
+            return self >= Scope::BotControl;
+        }
+        let is_management = path == "/bots"
+            || path.starts_with("/bots/")
+            || path == "/groups"
+            || path.starts_with("/groups/")
+            || path == "/schedules"
+            || path.starts_with("/schedules/")
+            || path == "/scripts"
+            || path.starts_with("/scripts/");
+        is_management && self >= Scope::BotManagement
+    }
+}
+
+// ── Persistent key store ──────────────────────────────────────────────────────
+
+/// Prefix of every generated key, so leaked keys are easy to grep for.
+const KEY_PREFIX: &str = "mori_";
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiKey {
+    pub id:         String,
+    pub name:       String,
+    /// First characters of the key, shown in listings to tell keys apart.
+    pub prefix:     String,
+    /// Hex SHA-256 of the full key; the key itself is never stored.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    #[schema(ignore)]
+    hash:           String,
+    pub scope:      Scope,
+    /// Unix timestamp.
+    pub created_at: u64,
+    /// Unix timestamp after which the key is rejected; null means never.
+    pub expires_at: Option<u64>,
+}
+
+impl ApiKey {
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|t| now >= t)
+    }
+
+    /// Copy without the hash, for API responses.
+    fn public(&self) -> ApiKey {
+        ApiKey { hash: String::new(), ..self.clone() }
+    }
+}
+
+fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// An existing but unreadable file is an error rather than "no keys": that would
+/// quietly revoke every automation client and overwrite the file on the next change.
+fn load_keys(path: &Path) -> anyhow::Result<Vec<ApiKey>> {
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => anyhow::bail!("cannot read api_keys.json: {e}"),
+    };
+    serde_json::from_str(&data).map_err(|e| anyhow::anyhow!("api_keys.json does not parse: {e}"))
+}
+
+fn save_keys(path: &Path, keys: &[ApiKey]) -> anyhow::Result<()> {
+    let data = serde_json::to_string_pretty(keys)?;
+    crate::persist::write_atomic(path, data.as_bytes())?;
+    Ok(())
+}
+
+// ── Key store (shared across requests) ────────────────────────────────────────
+
+#[derive(Clone)]
+pub struct ApiKeyStore {
+    keys: Arc<RwLock<Vec<ApiKey>>>,
+    /// `api_keys.json` inside the data directory.
+    path: PathBuf,
+}
+
+impl ApiKeyStore {
+    pub fn new(data_dir: &Path) -> anyhow::Result<Self> {
+        let path = data_dir.join("api_keys.json");
+        Ok(Self { keys: Arc::new(RwLock::new(load_keys(&path)?)), path })
+    }
+
+    /// Create a key and return it with the plain-text key, which is not retrievable later.
+    pub fn create(&self, name: &str, scope: Scope, expires_at: Option<u64>) -> anyhow::Result<(ApiKey, String)> {
+        let name = name.trim();
+        if name.is_empty() || name.len() > 64 {
+            anyhow::bail!("name must be 1–64 characters");
+        }
+        let mut keys = self.keys.write().unwrap();
+        if keys.iter().any(|k| k.name == name) {
+            anyhow::bail!("a key named '{name}' already exists");
+        }
+
+        let mut secret = [0u8; 24];
+        OsRng.fill_bytes(&mut secret);
+        let plain = format!("{KEY_PREFIX}{}", hex::encode(secret));
+        let key = ApiKey {
+            id:         uuid::Uuid::new_v4().to_string(),
+            name:       name.to_string(),
+            prefix:     plain[..KEY_PREFIX.len() + 8].to_string(),
+            hash:       hash_key(&plain),
+            scope,
+            created_at: unix_now(),
+            expires_at,
+        };
+        let mut next = keys.clone();
+        next.push(key.clone());
+        save_keys(&self.path, &next)?;
+        *keys = next;
+        Ok((key.public(), plain))
+    }
+
+    pub fn list(&self) -> Vec<ApiKey> {
+        self.keys.read().unwrap().iter().map(ApiKey::public).collect()
+    }
+
+    /// Returns false when no key has this id.
+    pub fn revoke(&self, id: &str) -> anyhow::Result<bool> {
+        let mut keys = self.keys.write().unwrap();
+        let next: Vec<ApiKey> = keys.iter().filter(|k| k.id != id).cloned().collect();
+        if next.len() == keys.len() {
+            return Ok(false);
+        }
+        save_keys(&self.path, &next)?;
+        *keys = next;
+        Ok(true)
+    }
+
+    /// The unexpired key matching a bearer token, if any.
+    pub fn lookup(&self, token: &str) -> Option<ApiKey> {
+        if !token.starts_with(KEY_PREFIX) {
+            return None;
+        }
+        let hash = hash_key(token);
+        let now = unix_now();
+        self.keys.read().unwrap().iter()
+            .find(|k| k.hash == hash && !k.is_expired(now))
+            .map(ApiKey::public)
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn scope_permissions() {
+        let (get, post, delete) = (Method::GET, Method::POST, Method::DELETE);
+        assert!(Scope::Read.permits(&get, "/bots/1/state"));
+        assert!(!Scope::Read.permits(&post, "/bots/1/cmd"));
+        assert!(Scope::BotControl.permits(&post, "/bots/1/cmd"));
+        assert!(!Scope::BotControl.permits(&delete, "/bots/1"));
+        assert!(Scope::BotManagement.permits(&delete, "/bots/1"));
+        assert!(Scope::BotManagement.permits(&post, "/schedules"));
+        assert!(Scope::BotControl.permits(&post, "/groups/farmers/cmd"));
+        assert!(!Scope::BotControl.permits(&post, "/groups"));
+        assert!(Scope::BotManagement.permits(&delete, "/groups/farmers"));
+        assert!(!Scope::BotControl.permits(&post, "/scripts"));
+        assert!(Scope::BotManagement.permits(&post, "/scripts/farm.lua"));
+        assert!(!Scope::BotManagement.permits(&post, "/proxies"));
+        assert!(!Scope::BotManagement.permits(&get, "/audit"));
+        assert!(!Scope::BotManagement.permits(&post, "/api-keys"));
+    }
+
+    #[test]
+    fn create_and_lookup() {
+        let dir = TestDir::new("keys");
+        let store = ApiKeyStore::new(&dir).unwrap();
+        let (key, plain) = store.create("grafana", Scope::Read, None).unwrap();
+        assert!(plain.starts_with(&key.prefix));
+        assert!(store.create("grafana", Scope::Read, None).is_err());
+        assert_eq!(store.lookup(&plain).unwrap().id, key.id);
+        assert_eq!(store.lookup(&plain).unwrap().name, "grafana");
+        assert!(store.lookup("mori_nope").is_none());
+    }
+
+    #[test]
+    fn expired_keys_are_rejected() {
+        let dir = TestDir::new("keys");
+        let store = ApiKeyStore::new(&dir).unwrap();
+        let (_, expired) = store.create("old", Scope::Read, Some(1)).unwrap();
+        assert!(store.lookup(&expired).is_none());
+    }
+
+    #[test]
+    fn keys_survive_a_reload_until_revoked() {
+        let dir = TestDir::new("keys");
+        let (key, plain) = ApiKeyStore::new(&dir).unwrap().create("grafana", Scope::Read, None).unwrap();
+
+        // Hashes survive a reload but are never listed.
+        let reloaded = ApiKeyStore::new(&dir).unwrap();
+        assert!(reloaded.lookup(&plain).is_some());
+        assert!(reloaded.list().iter().all(|k| k.hash.is_empty()));
+        assert!(reloaded.revoke(&key.id).unwrap());
+        assert!(!reloaded.revoke(&key.id).unwrap());
+        assert!(reloaded.lookup(&plain).is_none());
+    }
+
+    #[test]
+    fn unreadable_keys_file_fails_closed() {
+        let dir = TestDir::new("keys");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("api_keys.json"), "[{\"id\": \"a").unwrap();
+        assert!(ApiKeyStore::new(&dir).is_err());
+        assert_eq!(std::fs::read_to_string(dir.join("api_keys.json")).unwrap(), "[{\"id\": \"a");
+    }
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use utoipa::ToSchema;
+
+// ── Audit log ─────────────────────────────────────────────────────────────────
+//
+// Every mutating API request is appended to `<data_dir>/audit.jsonl`, one JSON
+// object per line. The file is never rewritten.
+
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+pub struct AuditEntry {
+    /// Unix timestamp.
+    pub ts:       u64,
+    /// Who sent the request, e.g. `user:alice`; null when unauthenticated.
+    pub identity: Option<String>,
+    pub ip:       String,
+    pub method:   String,
+    pub path:     String,
+    /// The `CmdRequest` body for `/bots/{id}/cmd`; other bodies are not recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command:  Option<serde_json::Value>,
+    /// HTTP status of the response.
+    pub status:   u16,
+    /// Set by the handler through an `AuditNote` response extension, e.g. for lockouts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note:     Option<String>,
+}
+
+/// Response extension whose text is copied into the entry's `note`.
+#[derive(Clone)]
+pub struct AuditNote(pub String);
+
+#[derive(Serialize, ToSchema)]
+pub struct AuditPage {
+    pub entries:   Vec<AuditEntry>,
+    pub total:     usize,
+    pub page:      usize,
+    pub page_size: usize,
+}
+
+#[derive(Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+    /// Serialises appends so concurrent requests don't interleave lines.
+    lock: Arc<Mutex<()>>,
+}
+
+impl AuditLog {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { path: data_dir.join("audit.jsonl"), lock: Arc::new(Mutex::new(())) }
+    }
+
+    pub fn append(&self, entry: &AuditEntry) -> anyhow::Result<()> {
+        let line = serde_json::to_string(entry)?;
+        let _guard = self.lock.lock().unwrap();
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{line}")?;
+        Ok(())
+    }
+
+    /// Page `page` (1-based) of the log, newest entries first.
+    ///
+    /// The file is streamed twice, once to count the entries and once to pick
+    /// out the page, so at most `page_size` entries are held in memory.
+    pub fn page(&self, page: usize, page_size: usize) -> AuditPage {
+        let page = page.max(1);
+        let total = self.read::<serde::de::IgnoredAny>().count();
+        let end = total.saturating_sub((page - 1).saturating_mul(page_size));
+        let start = end.saturating_sub(page_size);
+        let mut entries: Vec<AuditEntry> = self.read().skip(start).take(end - start).collect();
+        entries.reverse();
+        AuditPage { entries, total, page, page_size }
+    }
+
+    /// Parsable lines of the log, oldest first; unparsable ones are skipped.
+    fn read<T: serde::de::DeserializeOwned>(&self) -> impl Iterator<Item = T> {
+        std::fs::File::open(&self.path)
+            .ok()
+            .into_iter()
+            .flat_map(|file| BufReader::new(file).lines().map_while(Result::ok))
+            .filter_map(|line| serde_json::from_str(&line).ok())
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn pages_newest_first() {
+        let dir = TestDir::new("audit");
+        let log = AuditLog::new(&dir);
+        for ts in 1..=5 {
+            log.append(&AuditEntry {
+                ts,
+                identity: None,
+                ip:       "127.0.0.1".into(),
+                method:   "POST".into(),
+                path:     "/bots".into(),
+                command:  None,
+                status:   200,
+                note:     None,
+            }).unwrap();
+        }
+        let first = log.page(1, 2);
+        assert_eq!(first.total, 5);
+        assert_eq!(first.entries.iter().map(|e| e.ts).collect::<Vec<_>>(), vec![5, 4]);
+        let last = log.page(3, 2);
+        assert_eq!(last.entries.iter().map(|e| e.ts).collect::<Vec<_>>(), vec![1]);
+        let past_end = log.page(usize::MAX, 500);
+        assert_eq!(past_end.total, 5);
+        assert!(past_end.entries.is_empty());
+    }
+}
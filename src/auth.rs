@@ -2,119 +2,634 @@ use argon2::{
     Argon2,
     password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
 };
+use axum::http::Method;
 use rand_core::OsRng;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::{
-    path::PathBuf,
+    collections::HashMap,
+    path::{Path, PathBuf},
     sync::{Arc, RwLock},
 };
+use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::commands;
+use crate::config::SessionConfig;
+use crate::history::unix_now;
+
+// ── Roles ─────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Everything, including user and API key management, the audit log and vault export.
+    Admin,
+    /// Read access plus sending bot commands (`/bots/{id}/cmd`, `/bots/cmd`, `/groups/{name}/cmd`).
+    Operator,
+    /// GET routes and the WebSocket only.
+    Viewer,
+}
+
+impl Role {
+    /// Whether this role may call `method path`. Public routes are handled by the caller.
+    pub fn permits(self, method: &Method, path: &str) -> bool {
+        let admin_only = path == "/audit"
+            || path == "/users"
+            || path.starts_with("/users/")
+            || path.starts_with("/vault/")
+            || path == "/api-keys"
+            || path.starts_with("/api-keys/");
+        // Every user manages their own sessions and password.
+        if self == Role::Admin || path.starts_with("/auth/") {
+            return true;
+        }
+        if admin_only {
+            return false;
+        }
+        if matches!(*method, Method::GET | Method::HEAD) {
+            return true;
+        }
+        self == Role::Operator && commands::is_cmd_path(path)
+    }
+}
+
 // ── Persistent credential store ───────────────────────────────────────────────
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct UserRecord {
+    username:      String,
     /// Argon2id PHC string
     password_hash: String,
+    role:          Role,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct UsersFile {
+    users: Vec<UserRecord>,
 }
 
-fn user_path() -> PathBuf {
-    std::env::current_dir()
-        .unwrap_or_else(|_| PathBuf::from("."))
-        .join("data")
-        .join("user.json")
+/// Pre-multi-user `user.json`: a single password, imported as the `admin` account.
+#[derive(Deserialize)]
+struct LegacyUser {
+    password_hash: String,
 }
 
-fn load_user() -> Option<UserRecord> {
-    let path = user_path();
-    let data = std::fs::read_to_string(path).ok()?;
-    serde_json::from_str(&data).ok()
+/// Username of the account created by `/auth/setup` and of a migrated `user.json`.
+pub const DEFAULT_ADMIN: &str = "admin";
+
+/// `Ok(None)` when `path` does not exist.
+fn read_existing(path: &Path) -> anyhow::Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(data) => Ok(Some(data)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => anyhow::bail!("cannot read {}: {e}", path.display()),
+    }
 }
 
-fn save_user(record: &UserRecord) -> anyhow::Result<()> {
-    let path = user_path();
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
+/// An existing but unreadable file is an error rather than "no users": that would
+/// reopen `/auth/setup` to anyone.
+fn load_users(data_dir: &Path) -> anyhow::Result<Vec<UserRecord>> {
+    if let Some(data) = read_existing(&data_dir.join("users.json"))? {
+        return serde_json::from_str::<UsersFile>(&data)
+            .map(|f| f.users)
+            .map_err(|e| anyhow::anyhow!("users.json does not parse: {e}"));
+    }
+    let Some(data) = read_existing(&data_dir.join("user.json"))? else {
+        return Ok(Vec::new());
+    };
+    let user = serde_json::from_str::<LegacyUser>(&data)
+        .map_err(|e| anyhow::anyhow!("user.json does not parse: {e}"))?;
+    println!("[Auth] Migrated user.json to users.json as '{DEFAULT_ADMIN}'");
+    Ok(vec![UserRecord {
+        username:      DEFAULT_ADMIN.to_string(),
+        password_hash: user.password_hash,
+        role:          Role::Admin,
+    }])
+}
+
+fn save_users(path: &Path, users: &[UserRecord]) -> anyhow::Result<()> {
+    let data = serde_json::to_string_pretty(&UsersFile { users: users.to_vec() })?;
+    crate::persist::write_atomic(path, data.as_bytes())?;
+    Ok(())
+}
+
+fn hash_password(password: &str) -> anyhow::Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| anyhow::anyhow!("hash error: {}", e))?
+        .to_string())
+}
+
+/// Usernames: 1–32 ASCII letters, digits, `_`, `-` or `.`.
+pub fn check_username(name: &str) -> anyhow::Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 32
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if !valid {
+        anyhow::bail!("invalid username '{name}'");
     }
-    let data = serde_json::to_string_pretty(record)?;
-    std::fs::write(path, data)?;
     Ok(())
 }
 
+// ── Persistent sessions ───────────────────────────────────────────────────────
+
+/// Minimum gap between `last_seen` writes, so busy clients don't rewrite the file per request.
+const TOUCH_EVERY: u64 = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SessionRecord {
+    id:         String,
+    /// Hex SHA-256 of the token; the token itself is never stored.
+    token_hash: String,
+    username:   String,
+    /// Unix timestamp.
+    created_at: u64,
+    /// Unix timestamp of the last authenticated request (to within `TOUCH_EVERY`).
+    last_seen:  u64,
+}
+
+impl SessionRecord {
+    /// When the session runs out, or None when neither limit is set.
+    fn expires_at(&self, limits: &SessionConfig) -> Option<u64> {
+        let idle = (limits.idle_timeout_secs > 0).then(|| self.last_seen + limits.idle_timeout_secs);
+        let absolute = (limits.max_age_secs > 0).then(|| self.created_at + limits.max_age_secs);
+        idle.into_iter().chain(absolute).min()
+    }
+
+    fn is_expired(&self, limits: &SessionConfig, now: u64) -> bool {
+        self.expires_at(limits).is_some_and(|t| now >= t)
+    }
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// A `sessions.json` that does not parse is moved aside and everyone has to log in again.
+fn load_sessions(path: &Path) -> anyhow::Result<HashMap<String, SessionRecord>> {
+    let records: Vec<SessionRecord> = crate::persist::load_json(path, "Auth")?.unwrap_or_default();
+    Ok(records.into_iter().map(|r| (r.token_hash.clone(), r)).collect())
+}
+
+fn save_sessions(path: &Path, sessions: &HashMap<String, SessionRecord>) {
+    let mut records: Vec<&SessionRecord> = sessions.values().collect();
+    records.sort_by_key(|r| r.created_at);
+    let result = serde_json::to_string_pretty(&records)
+        .map_err(anyhow::Error::from)
+        .and_then(|data| Ok(crate::persist::write_atomic(path, data.as_bytes())?));
+    if let Err(e) = result {
+        println!("[Auth] Failed to save sessions.json: {e}");
+    }
+}
+
 // ── Auth state (shared across requests) ──────────────────────────────────────
 
+/// A logged-in user, attached to each authenticated request as an extension.
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// Stable session ID, as shown by `/auth/sessions`.
+    pub id:       String,
+    pub username: String,
+    pub role:     Role,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct UserInfo {
+    pub username: String,
+    pub role:     Role,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SessionInfo {
+    pub id:         String,
+    pub username:   String,
+    pub created_at: u64,
+    pub last_seen:  u64,
+    /// Unix timestamp, or null when sessions never expire.
+    pub expires_at: Option<u64>,
+}
+
 #[derive(Clone)]
 pub struct AuthState {
-    inner: Arc<RwLock<AuthInner>>,
+    inner:         Arc<RwLock<AuthInner>>,
+    /// `users.json` inside the data directory.
+    path:          PathBuf,
+    /// `sessions.json` inside the data directory.
+    sessions_path: PathBuf,
+    limits:        SessionConfig,
 }
 
 struct AuthInner {
-    /// None means not yet set up.
-    password_hash: Option<String>,
-    /// Active session token (single-user, single-session).
-    session_token: Option<String>,
+    /// Empty means not yet set up.
+    users:    Vec<UserRecord>,
+    /// Sessions by token hash; a user may hold several.
+    sessions: HashMap<String, SessionRecord>,
 }
 
 impl AuthState {
-    pub fn new() -> Self {
-        let password_hash = load_user().map(|u| u.password_hash);
-        Self {
-            inner: Arc::new(RwLock::new(AuthInner {
-                password_hash,
-                session_token: None,
-            })),
+    /// Fails when `users.json` exists but can't be read, or `sessions.json` can't be read
+    /// or moved aside; the server must not start then.
+    pub fn new(data_dir: &Path, limits: SessionConfig) -> anyhow::Result<Self> {
+        let users = load_users(data_dir)?;
+        let path = data_dir.join("users.json");
+        if !users.is_empty()
+            && !path.exists()
+            && let Err(e) = save_users(&path, &users)
+        {
+            println!("[Auth] Failed to write users.json: {e}");
+        }
+
+        let sessions_path = data_dir.join("sessions.json");
+        let mut sessions = load_sessions(&sessions_path)?;
+        let before = sessions.len();
+        let now = unix_now();
+        sessions.retain(|_, r| !r.is_expired(&limits, now));
+        if sessions.len() != before {
+            save_sessions(&sessions_path, &sessions);
         }
+
+        Ok(Self {
+            inner: Arc::new(RwLock::new(AuthInner { users, sessions })),
+            path,
+            sessions_path,
+            limits,
+        })
     }
 
     pub fn is_registered(&self) -> bool {
-        self.inner.read().unwrap().password_hash.is_some()
+        !self.inner.read().unwrap().users.is_empty()
     }
 
-    pub fn register(&self, password: &str) -> anyhow::Result<()> {
-        let salt = SaltString::generate(&mut OsRng);
-        let hash = Argon2::default()
-            .hash_password(password.as_bytes(), &salt)
-            .map_err(|e| anyhow::anyhow!("hash error: {}", e))?
-            .to_string();
+    /// Create the first admin account. Returns `Ok(false)` once any user exists; that
+    /// is checked again under the write lock, so concurrent setups can't both succeed.
+    pub fn register(&self, username: &str, password: &str) -> anyhow::Result<bool> {
+        check_username(username)?;
+        if self.is_registered() {
+            return Ok(false);
+        }
+        let hash = hash_password(password)?;
+        let mut inner = self.inner.write().unwrap();
+        if !inner.users.is_empty() {
+            return Ok(false);
+        }
+        self.insert_user(&mut inner, UserRecord { username: username.to_string(), password_hash: hash, role: Role::Admin })?;
+        Ok(true)
+    }
 
-        save_user(&UserRecord {
-            password_hash: hash.clone(),
-        })?;
+    pub fn add_user(&self, username: &str, password: &str, role: Role) -> anyhow::Result<()> {
+        check_username(username)?;
+        let hash = hash_password(password)?;
+        let mut inner = self.inner.write().unwrap();
+        if inner.users.iter().any(|u| u.username == username) {
+            anyhow::bail!("user '{username}' already exists");
+        }
+        self.insert_user(&mut inner, UserRecord { username: username.to_string(), password_hash: hash, role })
+    }
+
+    fn insert_user(&self, inner: &mut AuthInner, user: UserRecord) -> anyhow::Result<()> {
+        let mut users = inner.users.clone();
+        users.push(user);
+        save_users(&self.path, &users)?;
+        inner.users = users;
+        Ok(())
+    }
 
+    /// Change a user's role. Their sessions pick up the new role immediately.
+    pub fn set_role(&self, username: &str, role: Role) -> anyhow::Result<()> {
         let mut inner = self.inner.write().unwrap();
-        inner.password_hash = Some(hash);
-        inner.session_token = None;
+        let mut users = inner.users.clone();
+        let user = users.iter_mut()
+            .find(|u| u.username == username)
+            .ok_or_else(|| anyhow::anyhow!("no user '{username}'"))?;
+        user.role = role;
+        if !users.iter().any(|u| u.role == Role::Admin) {
+            anyhow::bail!("cannot remove the last admin");
+        }
+        save_users(&self.path, &users)?;
+        inner.users = users;
         Ok(())
     }
 
-    pub fn login(&self, password: &str) -> Option<String> {
-        let inner = self.inner.read().unwrap();
-        let hash_str = inner.password_hash.as_deref()?;
+    /// Replace a user's password and end all of their sessions except `keep_session`.
+    pub fn change_password(&self, username: &str, new_password: &str, keep_session: &str) -> anyhow::Result<()> {
+        let hash = hash_password(new_password)?;
+        let mut inner = self.inner.write().unwrap();
+        let mut users = inner.users.clone();
+        let user = users.iter_mut()
+            .find(|u| u.username == username)
+            .ok_or_else(|| anyhow::anyhow!("no user '{username}'"))?;
+        user.password_hash = hash;
+        save_users(&self.path, &users)?;
+        inner.users = users;
+        inner.sessions.retain(|_, r| r.username != username || r.id == keep_session);
+        save_sessions(&self.sessions_path, &inner.sessions);
+        Ok(())
+    }
+
+    /// Delete a user and end all of their sessions.
+    pub fn remove_user(&self, username: &str) -> anyhow::Result<()> {
+        let mut inner = self.inner.write().unwrap();
+        let users: Vec<UserRecord> = inner.users.iter()
+            .filter(|u| u.username != username)
+            .cloned()
+            .collect();
+        if users.len() == inner.users.len() {
+            anyhow::bail!("no user '{username}'");
+        }
+        if !users.iter().any(|u| u.role == Role::Admin) {
+            anyhow::bail!("cannot remove the last admin");
+        }
+        save_users(&self.path, &users)?;
+        inner.users = users;
+        inner.sessions.retain(|_, r| r.username != username);
+        save_sessions(&self.sessions_path, &inner.sessions);
+        Ok(())
+    }
 
-        let parsed = PasswordHash::new(hash_str).ok()?;
+    pub fn list_users(&self) -> Vec<UserInfo> {
+        self.inner.read().unwrap().users.iter()
+            .map(|u| UserInfo { username: u.username.clone(), role: u.role })
+            .collect()
+    }
+
+    /// Returns the user's role when `password` matches their stored hash.
+    /// The lock is released before Argon2 runs so logins don't stall other requests.
+    pub fn verify(&self, username: &str, password: &str) -> Option<Role> {
+        let (password_hash, role) = {
+            let inner = self.inner.read().unwrap();
+            let user = inner.users.iter().find(|u| u.username == username)?;
+            (user.password_hash.clone(), user.role)
+        };
+        let parsed = PasswordHash::new(&password_hash).ok()?;
         Argon2::default()
             .verify_password(password.as_bytes(), &parsed)
-            .ok()?;
-
-        drop(inner);
+            .ok()
+            .map(|_| role)
+    }
 
+    /// Start a new session; existing sessions (including the user's own) stay valid.
+    pub fn login(&self, username: &str, password: &str) -> Option<(String, Role)> {
+        let role = self.verify(username, password)?;
         let token = Uuid::new_v4().to_string();
-        self.inner.write().unwrap().session_token = Some(token.clone());
-        Some(token)
+        let now = unix_now();
+        let record = SessionRecord {
+            id:         Uuid::new_v4().to_string(),
+            token_hash: hash_token(&token),
+            username:   username.to_string(),
+            created_at: now,
+            last_seen:  now,
+        };
+        let mut inner = self.inner.write().unwrap();
+        inner.sessions.insert(record.token_hash.clone(), record);
+        save_sessions(&self.sessions_path, &inner.sessions);
+        Some((token, role))
+    }
+
+    /// The session the token belongs to, if it exists and has not expired.
+    /// Also records the request for the idle timeout.
+    pub fn session(&self, token: &str) -> Option<Session> {
+        let hash = hash_token(token);
+        let now = unix_now();
+        {
+            let inner = self.inner.read().unwrap();
+            let record = inner.sessions.get(&hash)?;
+            if !record.is_expired(&self.limits, now) && now < record.last_seen + TOUCH_EVERY {
+                let role = inner.users.iter().find(|u| u.username == record.username)?.role;
+                return Some(Session { id: record.id.clone(), username: record.username.clone(), role });
+            }
+        }
+        // Expired or due for a touch: look again under the write lock, since another
+        // request may have removed or touched it in between.
+        let mut inner = self.inner.write().unwrap();
+        let record = inner.sessions.get(&hash)?;
+        if record.is_expired(&self.limits, now) {
+            inner.sessions.remove(&hash);
+            save_sessions(&self.sessions_path, &inner.sessions);
+            return None;
+        }
+        let role = inner.users.iter().find(|u| u.username == record.username)?.role;
+        let session = Session { id: record.id.clone(), username: record.username.clone(), role };
+        if now >= record.last_seen + TOUCH_EVERY {
+            if let Some(record) = inner.sessions.get_mut(&hash) {
+                record.last_seen = now;
+            }
+            save_sessions(&self.sessions_path, &inner.sessions);
+        }
+        Some(session)
+    }
+
+    /// Unexpired sessions, oldest first; only `username`'s when given.
+    pub fn list_sessions(&self, username: Option<&str>) -> Vec<SessionInfo> {
+        let now = unix_now();
+        let inner = self.inner.read().unwrap();
+        let mut list: Vec<SessionInfo> = inner.sessions.values()
+            .filter(|r| username.is_none_or(|u| r.username == u))
+            .filter(|r| !r.is_expired(&self.limits, now))
+            .map(|r| SessionInfo {
+                id:         r.id.clone(),
+                username:   r.username.clone(),
+                created_at: r.created_at,
+                last_seen:  r.last_seen,
+                expires_at: r.expires_at(&self.limits),
+            })
+            .collect();
+        list.sort_by_key(|s| s.created_at);
+        list
+    }
+
+    /// End the session with this ID, if it belongs to `username` (any user when None).
+    pub fn revoke_session(&self, id: &str, username: Option<&str>) -> bool {
+        let mut inner = self.inner.write().unwrap();
+        let before = inner.sessions.len();
+        inner.sessions.retain(|_, r| r.id != id || username.is_some_and(|u| r.username != u));
+        let removed = inner.sessions.len() != before;
+        if removed {
+            save_sessions(&self.sessions_path, &inner.sessions);
+        }
+        removed
+    }
+
+    /// End the session for `token` only.
+    pub fn logout(&self, token: &str) {
+        let mut inner = self.inner.write().unwrap();
+        if inner.sessions.remove(&hash_token(token)).is_some() {
+            save_sessions(&self.sessions_path, &inner.sessions);
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn role_permissions() {
+        let (get, post, delete) = (Method::GET, Method::POST, Method::DELETE);
+        assert!(Role::Viewer.permits(&get, "/bots"));
+        assert!(Role::Viewer.permits(&get, "/ws"));
+        assert!(!Role::Viewer.permits(&post, "/bots/3/cmd"));
+        assert!(!Role::Viewer.permits(&get, "/audit"));
+        assert!(Role::Operator.permits(&post, "/bots/3/cmd"));
+        assert!(!Role::Operator.permits(&post, "/bots"));
+        assert!(!Role::Operator.permits(&delete, "/bots/3"));
+        assert!(!Role::Operator.permits(&post, "/bots/x/cmd"));
+        assert!(Role::Operator.permits(&post, "/bots/cmd"));
+        assert!(Role::Operator.permits(&post, "/groups/farmers/cmd"));
+        assert!(!Role::Operator.permits(&post, "/groups/farmers"));
+        assert!(!Role::Operator.permits(&get, "/users"));
+        assert!(Role::Admin.permits(&post, "/vault/export"));
+        assert!(Role::Admin.permits(&delete, "/users/bob"));
+        assert!(!Role::Operator.permits(&get, "/api-keys"));
+    }
+
+    /// An `AuthState` in `dir` with the admin registered as `hunter22` and `bob` as a viewer.
+    fn with_bob(dir: &TestDir, sessions: SessionConfig) -> AuthState {
+        let auth = AuthState::new(dir, sessions).unwrap();
+        assert!(auth.register(DEFAULT_ADMIN, "hunter22").unwrap());
+        auth.add_user("bob", "password1", Role::Viewer).unwrap();
+        auth
+    }
+
+    #[test]
+    fn imports_the_legacy_admin() {
+        let dir = TestDir::new("auth");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("user.json"),
+            format!("{{\"password_hash\":\"{}\"}}", hash_password("hunter22").unwrap()),
+        ).unwrap();
+
+        let auth = AuthState::new(&dir, SessionConfig::default()).unwrap();
+        assert!(auth.is_registered());
+        assert!(!auth.register("root", "x").unwrap());
+        assert_eq!(auth.verify(DEFAULT_ADMIN, "hunter22"), Some(Role::Admin));
+    }
+
+    #[test]
+    fn user_names_and_roles_are_checked() {
+        let dir = TestDir::new("auth");
+        let auth = with_bob(&dir, SessionConfig::default());
+        assert!(auth.add_user("bob", "password1", Role::Viewer).is_err());
+        assert!(auth.add_user("../x", "password1", Role::Viewer).is_err());
+        assert!(auth.set_role(DEFAULT_ADMIN, Role::Viewer).is_err());
+        assert!(auth.remove_user(DEFAULT_ADMIN).is_err());
+    }
+
+    #[test]
+    fn sessions_follow_role_changes_and_logout() {
+        let dir = TestDir::new("auth");
+        let auth = with_bob(&dir, SessionConfig::default());
+        let (first, _) = auth.login("bob", "password1").unwrap();
+        let (second, role) = auth.login("bob", "password1").unwrap();
+        assert_eq!(role, Role::Viewer);
+        assert!(auth.login("bob", "wrong").is_none());
+        assert_eq!(auth.session(&first).unwrap().username, "bob");
+        assert!(auth.session(&second).is_some());
+
+        auth.set_role("bob", Role::Operator).unwrap();
+        assert_eq!(auth.session(&first).unwrap().role, Role::Operator);
+
+        auth.logout(&first);
+        assert!(auth.session(&first).is_none() && auth.session(&second).is_some());
+    }
+
+    #[test]
+    fn users_and_sessions_survive_a_restart() {
+        let dir = TestDir::new("auth");
+        let (token, _) = with_bob(&dir, SessionConfig::default()).login("bob", "password1").unwrap();
+
+        let reloaded = AuthState::new(&dir, SessionConfig::default()).unwrap();
+        assert_eq!(reloaded.list_users().len(), 2);
+        assert_eq!(reloaded.session(&token).unwrap().username, "bob");
+        assert_eq!(reloaded.list_sessions(Some("bob")).len(), 1);
+        reloaded.remove_user("bob").unwrap();
+        assert!(reloaded.session(&token).is_none());
     }
 
-    /// Returns true when the given token matches the active session.
-    pub fn validate_token(&self, token: &str) -> bool {
-        self.inner
-            .read()
-            .unwrap()
-            .session_token
-            .as_deref()
-            .map(|t| t == token)
-            .unwrap_or(false)
+    #[test]
+    fn sessions_expire_when_idle_or_too_old() {
+        let dir = TestDir::new("auth");
+        let auth = with_bob(&dir, SessionConfig { idle_timeout_secs: 100, max_age_secs: 1000 });
+        let (token, _) = auth.login(DEFAULT_ADMIN, "hunter22").unwrap();
+        let record = auth.inner.read().unwrap().sessions[&hash_token(&token)].clone();
+        let limits = &auth.limits;
+        assert_eq!(record.expires_at(limits), Some(record.last_seen + 100));
+        assert!(!record.is_expired(limits, record.created_at + 99));
+        assert!(record.is_expired(limits, record.created_at + 100));
+        let busy = SessionRecord { last_seen: record.created_at + 950, ..record.clone() };
+        assert!(busy.is_expired(limits, record.created_at + 1000));
     }
 
-    pub fn logout(&self) {
-        self.inner.write().unwrap().session_token = None;
+    #[test]
+    fn password_change_ends_other_sessions() {
+        let dir = TestDir::new("auth");
+        let auth = with_bob(&dir, SessionConfig::default());
+        let (token, _) = auth.login(DEFAULT_ADMIN, "hunter22").unwrap();
+        let (other, _) = auth.login(DEFAULT_ADMIN, "hunter22").unwrap();
+
+        let current = auth.session(&token).unwrap();
+        auth.change_password(DEFAULT_ADMIN, "correct horse", &current.id).unwrap();
+        assert!(auth.session(&token).is_some());
+        assert!(auth.session(&other).is_none());
+        assert!(auth.verify(DEFAULT_ADMIN, "hunter22").is_none());
+        assert!(auth.verify(DEFAULT_ADMIN, "correct horse").is_some());
+    }
+
+    #[test]
+    fn only_the_owner_or_admin_revokes_a_session() {
+        let dir = TestDir::new("auth");
+        let auth = with_bob(&dir, SessionConfig::default());
+        let (token, _) = auth.login(DEFAULT_ADMIN, "hunter22").unwrap();
+        let current = auth.session(&token).unwrap();
+        assert!(!auth.revoke_session(&current.id, Some("bob")));
+        assert!(auth.revoke_session(&current.id, Some(DEFAULT_ADMIN)));
+        assert!(auth.session(&token).is_none());
+    }
+
+    #[test]
+    fn only_one_concurrent_setup_registers() {
+        let dir = TestDir::new("auth");
+        let auth = AuthState::new(&dir, SessionConfig::default()).unwrap();
+        assert!(auth.register("bad name", "hunter22").is_err());
+        let registered: Vec<bool> = std::thread::scope(|scope| {
+            let setups: Vec<_> = ["alice", "mallory", "trent"].into_iter()
+                .map(|name| scope.spawn(|| auth.register(name, "hunter22").unwrap()))
+                .collect();
+            setups.into_iter().map(|t| t.join().unwrap()).collect()
+        });
+        assert_eq!(registered.iter().filter(|r| **r).count(), 1);
+        assert_eq!(auth.list_users().len(), 1);
+    }
+
+    #[test]
+    fn unreadable_users_file_fails_closed() {
+        let dir = TestDir::new("auth");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("users.json"), "{\"users\": [{\"username\": \"adm").unwrap();
+        assert!(AuthState::new(&dir, SessionConfig::default()).is_err());
+
+        std::fs::remove_file(dir.join("users.json")).unwrap();
+        std::fs::write(dir.join("user.json"), "not json").unwrap();
+        assert!(AuthState::new(&dir, SessionConfig::default()).is_err());
+    }
+
+    #[test]
+    fn moves_corrupt_sessions_aside() {
+        let dir = TestDir::new("auth");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("sessions.json"), "[{\"id\": \"9d").unwrap();
+
+        let auth = AuthState::new(&dir, SessionConfig::default()).unwrap();
+        assert!(auth.list_sessions(None).is_empty());
+        assert!(!dir.join("sessions.json").exists());
+        let backups: Vec<String> = std::fs::read_dir(&dir).unwrap()
+            .map(|e| std::fs::read_to_string(e.unwrap().path()).unwrap())
+            .collect();
+        assert_eq!(backups, ["[{\"id\": \"9d"]);
     }
 }
@@ -4,6 +4,7 @@ use crate::login::{LoginError, get_legacy_token_proxied};
 use crate::server_data::{LoginInfo, get_server_data_proxied};
 use std::net::SocketAddr;
 
+use super::error::BotError;
 use super::shared::Socks5Config;
 
 pub(super) struct Credentials {
@@ -17,7 +18,7 @@ pub(super) fn fetch_credentials(
     password: &str,
     proxy: Option<&Socks5Config>,
     log: &mut dyn FnMut(String),
-) -> Credentials {
+) -> Result<Credentials, BotError> {
     let proxy_url = proxy.map(|p| p.to_url());
     let proxy_url = proxy_url.as_deref();
 
@@ -77,11 +78,11 @@ pub(super) fn fetch_credentials(
                 log(format!("[Bot] fetch: login failed: {e}"));
                 if matches!(e, LoginError::Exhausted) {
                     log("[Bot] login attempts exhausted - stopping".to_string());
-                    panic!("[Bot] login attempts exhausted - stopping");
+                    return Err(BotError::LoginExhausted);
                 }
                 if matches!(e, LoginError::WrongCredentials) {
                     log("[Bot] wrong credentials - stopping".to_string());
-                    panic!("[Bot] wrong credentials - stopping");
+                    return Err(BotError::WrongCredentials);
                 }
                 log("[Bot] retrying in 5s".to_string());
                 std::thread::sleep(std::time::Duration::from_secs(5));
@@ -91,13 +92,15 @@ pub(super) fn fetch_credentials(
 
         let addr: SocketAddr = format!("{}:{}", server_data.server, server_data.port)
             .parse()
-            .expect("Invalid server address");
+            .map_err(|_| BotError::Network(format!(
+                "invalid server address {}:{}", server_data.server, server_data.port
+            )))?;
 
         log(format!("[Bot] Got token: {ltoken}"));
-        return Credentials {
+        return Ok(Credentials {
             ltoken,
             meta: server_data.meta,
             addr,
-        };
+        });
     }
 }
@@ -1,8 +1,11 @@
 use crate::astar::{self, find_path};
 use crate::bot_state::{
-    BotCommand, BotDelays, BotState, BotStatus, CmdReceiver, InvSlot, PlayerInfo, TileInfo,
-    WorldObjectInfo,
+    BotCommand, BotDelays, BotState, BotStatus, CmdReceiver, InvSlot, PlayerInfo, QueuedCommand,
+    TileInfo, WorldObjectInfo,
 };
+use crate::command_tracker::CmdTicket;
+use crate::commands::REACH_TILES;
+use crate::console_log::ConsoleCategory;
 use crate::constants::{FHASH, GAME_VER, PROTOCOL};
 use crate::cursor::Cursor;
 use crate::protocol::crypto::{compute_klv, generate_rid, hash_string, random_hex, random_mac};
@@ -12,7 +15,10 @@ use crate::items::ItemsDat;
 use crate::login::check_token;
 use crate::protocol::packet::{self, GamePacketType, GameUpdatePacket, IncomingPacket};
 use crate::player::{LocalPlayer, Player, parse_pipe_map};
-use crate::server_data::{LoginInfo, get_server_data_proxied};
+use crate::proxy_pool::{ProxyPool, ROTATE_AFTER_FAILURES};
+use crate::metrics::BotMetrics;
+use crate::script_channel::ScriptOutcome;
+use crate::server_data::{LoginInfo, get_server_data_proxied, is_connection_error};
 use crate::socks5::Socks5UdpSocket;
 use crate::protocol::variant::VariantList;
 use crate::world::{NpcAction, NpcType, TileFlags, TileType, World, WorldNpc, WorldObject, WorldTilePermission};
@@ -23,6 +29,7 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
 
 use super::auth::fetch_credentials;
+use super::error::BotError;
 use super::shared::{BotEventRaw, Socks5Config, TemporaryData};
 
 enum BotHost {
@@ -191,6 +198,10 @@ pub struct Bot {
     script_reply_tx: Option<crossbeam_channel::Sender<crate::script_channel::ScriptReply>>,
     /// Set to true to interrupt a running Lua script.
     pub script_stop: Arc<AtomicBool>,
+    /// Counters exported on `/metrics`; shared with `BotState::metrics`.
+    metrics: Arc<BotMetrics>,
+    /// Receives the outcome of every script run (used by the headless CLI).
+    pub script_done_tx: Option<std::sync::mpsc::Sender<ScriptOutcome>>,
     /// When set, the bot will delay reconnecting until this instant (used for 2FA cooldown).
     reconnect_after: Option<std::time::Instant>,
     /// Set when an `action|log` with "Advanced Account Protection" is received,
@@ -219,6 +230,29 @@ pub struct Bot {
     ws_tx: Option<WsTx>,
     /// Last broadcast ping value — used to suppress redundant BotPing events.
     last_ping: u32,
+    /// Pool the current proxy was taken from; lets `reconnect_main` rotate to another one.
+    pub proxy_pool: Option<ProxyPool>,
+    /// Reconnect attempts since the last successful connect through the current proxy.
+    proxy_failures: u32,
+    /// Set by `fail`; returned from `run` once the loop exits.
+    exit_error: Option<BotError>,
+}
+
+/// Console sink for code that runs before (or outside) `&self`: prints, appends to
+/// the bot's console buffer and forwards to the WebSocket.
+fn console_logger(
+    state: Arc<RwLock<BotState>>,
+    ws_tx: Option<WsTx>,
+    bot_id: u32,
+) -> impl Fn(String) {
+    move |msg: String| {
+        println!("{msg}");
+        let category = ConsoleCategory::Network;
+        BotState::push_console(&state, category, msg.clone());
+        if let Some(tx) = &ws_tx {
+            let _ = tx.send(WsEvent::Console { bot_id, category, message: msg });
+        }
+    }
 }
 
 fn sorted_blacklist_vec(set: &HashSet<u16>) -> Vec<u16> {
@@ -237,27 +271,9 @@ impl Bot {
         items_dat: Arc<ItemsDat>,
         bot_id: u32,
         ws_tx: Option<WsTx>,
-    ) -> Self {
-        let log_state = Arc::clone(&state);
-        let log_ws_tx = ws_tx.clone();
-        let log_bot_id = bot_id;
-        let mut log_fn = move |msg: String| {
-            println!("{msg}");
-            {
-                let mut s = log_state.write().unwrap();
-                s.console.push(msg.clone());
-                if s.console.len() > 100 {
-                    s.console.remove(0);
-                }
-            }
-            if let Some(tx) = &log_ws_tx {
-                let _ = tx.send(WsEvent::Console {
-                    bot_id: log_bot_id,
-                    message: msg,
-                });
-            }
-        };
-        let creds = fetch_credentials(username, password, proxy.as_ref(), &mut log_fn);
+    ) -> Result<Self, BotError> {
+        let mut log_fn = console_logger(Arc::clone(&state), ws_tx.clone(), bot_id);
+        let creds = fetch_credentials(username, password, proxy.as_ref(), &mut log_fn)?;
 
         let mac = random_mac();
         let hash = hash_string(&format!("{}RT", mac));
@@ -265,7 +281,8 @@ impl Bot {
         let wk = random_hex(32);
         let rid = generate_rid();
 
-        let host = Self::create_host(proxy.as_ref());
+        let host = Self::create_host(proxy.as_ref())?;
+        let metrics = Arc::clone(&state.read().unwrap().metrics);
         let mut bot = Bot {
             host,
             proxy,
@@ -314,6 +331,8 @@ impl Bot {
             script_req_rx: None,
             script_reply_tx: None,
             script_stop: Arc::new(AtomicBool::new(false)),
+            script_done_tx: None,
+            metrics,
             reconnect_after: None,
             pending_2fa: false,
             pending_relogon: false,
@@ -325,6 +344,9 @@ impl Bot {
             bot_id,
             ws_tx,
             last_ping: 0,
+            proxy_pool: None,
+            proxy_failures: 0,
+            exit_error: None,
         };
 
         {
@@ -335,11 +357,11 @@ impl Bot {
             s.collect_blacklist = sorted_blacklist_vec(&bot.collect_blacklist);
         }
         bot.host.connect(creds.addr, 2, 0);
-        bot
+        Ok(bot)
     }
 
     /// Parses a `token|rid|mac|wk` string.
-    fn parse_ltoken_string(s: &str) -> Option<(String, String, String, String)> {
+    pub(crate) fn parse_ltoken_string(s: &str) -> Option<(String, String, String, String)> {
         let mut parts = s.splitn(4, '|');
         let token = parts.next()?.to_string();
         let rid = parts.next()?.to_string();
@@ -359,9 +381,10 @@ impl Bot {
         items_dat: Arc<ItemsDat>,
         bot_id: u32,
         ws_tx: Option<WsTx>,
-    ) -> Self {
-        let (ltoken, rid, mac, wk) = Self::parse_ltoken_string(ltoken_str)
-            .expect("[Bot] Invalid ltoken string — expected token|rid|mac|wk");
+    ) -> Result<Self, BotError> {
+        let (ltoken, rid, mac, wk) = Self::parse_ltoken_string(ltoken_str).ok_or_else(|| {
+            BotError::TokenInvalid("malformed ltoken string — expected token|rid|mac|wk".into())
+        })?;
 
         let hash = hash_string(&format!("{}RT", mac));
         let hash2 = hash_string(&format!("{}RT", random_hex(16)));
@@ -373,25 +396,7 @@ impl Bot {
             game_version: GAME_VER.into(),
         };
 
-        let log_state = Arc::clone(&state);
-        let log_ws_tx = ws_tx.clone();
-        let log_bot_id = bot_id;
-        let log_fn = move |msg: String| {
-            println!("{msg}");
-            {
-                let mut s = log_state.write().unwrap();
-                s.console.push(msg.clone());
-                if s.console.len() > 100 {
-                    s.console.remove(0);
-                }
-            }
-            if let Some(tx) = &log_ws_tx {
-                let _ = tx.send(WsEvent::Console {
-                    bot_id: log_bot_id,
-                    message: msg,
-                });
-            }
-        };
+        let log_fn = console_logger(Arc::clone(&state), ws_tx.clone(), bot_id);
 
         let mut alternate = false;
         let server_data = loop {
@@ -424,14 +429,20 @@ rid|{rid}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{hash}\nmac|{mac}
                 log_fn(format!("[Bot] ltoken validated successfully"));
                 new_token
             }
-            Err(e) => panic!("[Bot] ltoken validation failed: {e} — stopping"),
+            Err(e) => {
+                log_fn(format!("[Bot] ltoken validation failed: {e} — stopping"));
+                return Err(BotError::TokenInvalid(e.to_string()));
+            }
         };
 
         let addr: SocketAddr = format!("{}:{}", server_data.server, server_data.port)
             .parse()
-            .expect("Invalid server address");
+            .map_err(|_| BotError::Network(format!(
+                "invalid server address {}:{}", server_data.server, server_data.port
+            )))?;
 
-        let host = Self::create_host(proxy.as_ref());
+        let host = Self::create_host(proxy.as_ref())?;
+        let metrics = Arc::clone(&state.read().unwrap().metrics);
         let mut bot = Bot {
             host,
             proxy,
@@ -478,6 +489,8 @@ rid|{rid}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{hash}\nmac|{mac}
             script_req_rx: None,
             script_reply_tx: None,
             script_stop: Arc::new(AtomicBool::new(false)),
+            script_done_tx: None,
+            metrics,
             reconnect_after: None,
             pending_2fa: false,
             pending_relogon: false,
@@ -489,6 +502,9 @@ rid|{rid}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{hash}\nmac|{mac}
             bot_id,
             ws_tx,
             last_ping: 0,
+            proxy_pool: None,
+            proxy_failures: 0,
+            exit_error: None,
         };
 
         {
@@ -498,18 +514,23 @@ rid|{rid}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{hash}\nmac|{mac}
             s.collect_blacklist = sorted_blacklist_vec(&bot.collect_blacklist);
         }
         bot.host.connect(addr, 2, 0);
-        bot
+        Ok(bot)
     }
 
     fn reconnect_main(&mut self) {
-        self.host = Self::create_host(self.proxy.as_ref());
+        if !self.recreate_host() {
+            return;
+        }
         self.refresh_token();
+        if self.stop_requested {
+            return;
+        }
 
         let login_info = LoginInfo {
             protocol: PROTOCOL,
             game_version: GAME_VER.into(),
         };
-        let proxy_url = self.proxy.as_ref().map(|p| p.to_url());
+        let mut proxy_url = self.proxy.as_ref().map(|p| p.to_url());
         let mut alternate = false;
         let server_data = loop {
             match get_server_data_proxied(alternate, &login_info, proxy_url.as_deref()) {
@@ -519,19 +540,105 @@ rid|{rid}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{hash}\nmac|{mac}
                     self.log_console(format!(
                         "[Bot] reconnect: server_data failed: {e} — retrying in 5s"
                     ));
+                    if is_connection_error(e.as_ref()) && self.proxy_failed(&e.to_string()) {
+                        if !self.recreate_host() {
+                            return;
+                        }
+                        proxy_url = self.proxy.as_ref().map(|p| p.to_url());
+                    }
                     std::thread::sleep(std::time::Duration::from_secs(5));
                 }
             }
         };
         self.meta = server_data.meta.clone();
 
-        let addr: SocketAddr = format!("{}:{}", server_data.server, server_data.port)
-            .parse()
-            .expect("Invalid server address");
+        let addr: SocketAddr = match format!("{}:{}", server_data.server, server_data.port).parse() {
+            Ok(a) => a,
+            Err(_) => {
+                self.fail(BotError::Network(format!(
+                    "invalid server address {}:{}", server_data.server, server_data.port
+                )));
+                return;
+            }
+        };
         self.host.connect(addr, 2, 0);
     }
 
-    fn create_host(proxy: Option<&Socks5Config>) -> BotHost {
+    /// Replace the ENet host, rotating to another pooled proxy if the current
+    /// one refuses us. Returns false (after `fail`) when no host could be made.
+    fn recreate_host(&mut self) -> bool {
+        loop {
+            match Self::create_host(self.proxy.as_ref()) {
+                Ok(host) => {
+                    self.host = host;
+                    return true;
+                }
+                Err(e @ BotError::ProxyFailed(_)) => {
+                    self.log_console(format!("[Bot] {e}"));
+                    if !self.rotate_proxy(&e.to_string()) {
+                        self.fail(e);
+                        return false;
+                    }
+                }
+                Err(e) => {
+                    self.fail(e);
+                    return false;
+                }
+            }
+        }
+    }
+
+    /// Stop the bot with a terminal error status that `run` hands back to the manager.
+    fn fail(&mut self, err: BotError) {
+        self.log_console(format!("[Bot] {err} — stopping"));
+        let status = err.status();
+        {
+            let mut s = self.state.write().unwrap();
+            s.status = status.clone();
+            s.last_error = Some(err.to_string());
+        }
+        self.emit(WsEvent::BotStatus {
+            bot_id: self.bot_id,
+            status: status.to_string(),
+        });
+        self.exit_error = Some(err);
+        self.stop_requested = true;
+    }
+
+    /// Count a failure that points at the proxy rather than the game server, and move
+    /// to another pooled proxy after too many in a row. Returns true when it moved.
+    fn proxy_failed(&mut self, reason: &str) -> bool {
+        if self.proxy.is_none() {
+            return false;
+        }
+        self.proxy_failures += 1;
+        self.proxy_failures > ROTATE_AFTER_FAILURES && self.rotate_proxy(reason)
+    }
+
+    /// Swap to another healthy proxy from the pool. Returns false when the bot
+    /// isn't pool-managed or no other proxy is free, in which case it keeps retrying.
+    fn rotate_proxy(&mut self, reason: &str) -> bool {
+        let Some(pool) = &self.proxy_pool else {
+            return false;
+        };
+        match pool.rotate(self.bot_id, reason) {
+            Some(next) => {
+                self.log_console(format!(
+                    "[Bot] Proxy failed {} times — rotating to {}",
+                    self.proxy_failures, next.proxy_addr
+                ));
+                self.proxy = Some(next);
+                self.proxy_failures = 0;
+                true
+            }
+            None => {
+                self.log_console("[Bot] Proxy failing but no other pooled proxy is free".to_string());
+                false
+            }
+        }
+    }
+
+    fn create_host(proxy: Option<&Socks5Config>) -> Result<BotHost, BotError> {
         let settings = enet::HostSettings {
             peer_limit: 1,
             channel_limit: 2,
@@ -542,10 +649,11 @@ rid|{rid}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{hash}\nmac|{mac}
         };
         match proxy {
             None => {
-                let socket = UdpSocket::bind("0.0.0.0:0").expect("Failed to bind UDP socket");
-                BotHost::Direct(
-                    enet::Host::new(socket, settings).expect("Failed to create ENet host"),
-                )
+                let socket = UdpSocket::bind("0.0.0.0:0")
+                    .map_err(|e| BotError::Network(format!("failed to bind UDP socket: {e}")))?;
+                let host = enet::Host::new(socket, settings)
+                    .map_err(|e| BotError::Network(format!("failed to create ENet host: {e}")))?;
+                Ok(BotHost::Direct(host))
             }
             Some(cfg) => {
                 let local: SocketAddr = "0.0.0.0:0".parse().unwrap();
@@ -555,10 +663,10 @@ rid|{rid}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{hash}\nmac|{mac}
                     cfg.username.as_deref(),
                     cfg.password.as_deref(),
                 )
-                .expect("Failed to create SOCKS5 UDP socket");
-                BotHost::Socks5(
-                    enet::Host::new(socket, settings).expect("Failed to create ENet host"),
-                )
+                .map_err(|e| BotError::ProxyFailed(e.to_string()))?;
+                let host = enet::Host::new(socket, settings)
+                    .map_err(|e| BotError::Network(format!("failed to create ENet host: {e}")))?;
+                Ok(BotHost::Socks5(host))
             }
         }
     }
@@ -569,17 +677,18 @@ rid|{rid}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{hash}\nmac|{mac}
         }
     }
 
+    /// Log a `network` console line; `log_as` picks another category.
     fn log_console(&self, msg: String) {
+        self.log_as(ConsoleCategory::Network, msg);
+    }
+
+    /// Print the line, keep it in the console buffer and log file, and forward it to the WebSocket.
+    fn log_as(&self, category: ConsoleCategory, msg: String) {
         println!("{msg}");
-        {
-            let mut s = self.state.write().unwrap();
-            s.console.push(msg.clone());
-            if s.console.len() > 100 {
-                s.console.remove(0);
-            }
-        }
+        BotState::push_console(&self.state, category, msg.clone());
         self.emit(WsEvent::Console {
             bot_id: self.bot_id,
+            category,
             message: msg,
         });
     }
@@ -663,41 +772,30 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                 self.log_console(
                     "[Bot] ltoken login — no fallback credentials, stopping bot".to_string(),
                 );
-                self.stop_requested = true;
+                self.fail(BotError::TokenInvalid(
+                    "check_token failed and there is no password to fall back to".into(),
+                ));
             }
             LoginMethod::Legacy { password } => {
                 let password = password.clone();
                 self.log_console("[Bot] falling back to full re-login".to_string());
                 let username = self.username.clone();
                 let proxy_clone = self.proxy.clone();
-                let state = Arc::clone(&self.state);
-                let ws_tx = self.ws_tx.clone();
-                let bot_id = self.bot_id;
-                let mut log_fn = move |msg: String| {
-                    println!("{msg}");
-                    {
-                        let mut s = state.write().unwrap();
-                        s.console.push(msg.clone());
-                        if s.console.len() > 100 {
-                            s.console.remove(0);
-                        }
-                    }
-                    if let Some(tx) = &ws_tx {
-                        let _ = tx.send(WsEvent::Console {
-                            bot_id,
-                            message: msg,
-                        });
+                let mut log_fn =
+                    console_logger(Arc::clone(&self.state), self.ws_tx.clone(), self.bot_id);
+                match fetch_credentials(&username, &password, proxy_clone.as_ref(), &mut log_fn) {
+                    Ok(creds) => {
+                        self.ltoken = creds.ltoken;
+                        self.meta = creds.meta;
                     }
-                };
-                let creds =
-                    fetch_credentials(&username, &password, proxy_clone.as_ref(), &mut log_fn);
-                self.ltoken = creds.ltoken;
-                self.meta = creds.meta;
+                    Err(e) => self.fail(e),
+                }
             }
         }
     }
 
-    pub fn run(&mut self, stop_flag: Arc<AtomicBool>) {
+    /// Drive the bot until it is stopped. Returns the error that stopped it, if any.
+    pub fn run(&mut self, stop_flag: Arc<AtomicBool>) -> Result<(), BotError> {
         loop {
             if stop_flag.load(Ordering::Relaxed) {
                 self.log_console("[Bot] Stop flag set, exiting.".to_string());
@@ -720,8 +818,19 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                     }
                 }
             }
-            while let Ok(cmd) = self.cmd_rx.try_recv() {
-                self.handle_command(cmd);
+            while let Ok(QueuedCommand { cmd, ticket }) = self.cmd_rx.try_recv() {
+                if let Some(ticket) = &ticket {
+                    ticket.start();
+                }
+                // A script's ticket is finished by its thread once the script ends.
+                if let BotCommand::RunScript { content } = cmd {
+                    self.run_script(content, ticket);
+                    continue;
+                }
+                let result = self.handle_command(cmd);
+                if let Some(ticket) = ticket {
+                    ticket.finish(result);
+                }
             }
             if let Some(id) = self.peer_id {
                 let rtt = self.host.peer_rtt(id).as_millis() as u32;
@@ -740,10 +849,15 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                 && self.collect_timer.elapsed() >= std::time::Duration::from_millis(self.collect_interval)
             {
                 self.collect_timer = std::time::Instant::now();
-                self.collect(None, None);
+                let picked = self.collect(None, None);
+                self.metrics.collected(picked);
             }
             std::thread::sleep(std::time::Duration::from_millis(10));
         }
+        match self.exit_error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
     }
 
     /// Sleep for `ms` milliseconds while keeping ENet alive.
@@ -761,10 +875,15 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
             match event {
                 enet::EventNoRef::Connect { peer: id, .. } => {
                     self.peer_id = Some(id);
+                    self.proxy_failures = 0;
                     self.log_console(format!("[Bot] Connected: peer {}", id.0));
                 }
 
                 enet::EventNoRef::Disconnect { peer: id, .. } => {
+                    // A connect attempt that never got through is on the proxy's UDP relay.
+                    if self.peer_id.is_none() {
+                        self.proxy_failed("could not reach the game server");
+                    }
                     self.peer_id = None;
                     self.pathfind_target = None;
                     self.pathfind_recalc = false;
@@ -785,12 +904,18 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                         world_name: String::new(),
                     });
                     if let Some(r) = self.redirect.as_ref() {
-                        let addr: SocketAddr = format!("{}:{}", r.server, r.port)
-                            .parse()
-                            .expect("Invalid redirect address");
-                        self.log_console(format!("[Bot] Redirecting to {}:{}", r.server, r.port));
-                        self.host = Self::create_host(self.proxy.as_ref());
-                        self.host.connect(addr, 2, 0);
+                        let target = format!("{}:{}", r.server, r.port);
+                        match target.parse::<SocketAddr>() {
+                            Ok(addr) => {
+                                self.log_console(format!("[Bot] Redirecting to {target}"));
+                                if self.recreate_host() {
+                                    self.host.connect(addr, 2, 0);
+                                }
+                            }
+                            Err(_) => self.fail(BotError::Network(format!(
+                                "invalid redirect address {target}"
+                            ))),
+                        }
                     } else if self.reconnect_after.is_some() {
                         // Delayed reconnect already scheduled (e.g. 2FA cooldown) — do nothing here.
                     } else if self.auto_reconnect {
@@ -866,6 +991,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                             if s.contains("action|logon_fail") {
                                 if self.pending_2fa {
                                     self.pending_2fa = false;
+                                    self.metrics.logon_failure("two_factor_auth");
                                     let secs = self.delays.twofa_secs;
                                     self.log_console(format!(
                                         "[Bot] Logon failed — 2FA (Advanced Account Protection). Retrying in {secs} s."
@@ -881,6 +1007,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                                     });
                                 } else if self.pending_server_overload {
                                     self.pending_server_overload = false;
+                                    self.metrics.logon_failure("server_overloaded");
                                     let secs = self.delays.server_overload_secs;
                                     self.log_console(format!(
                                         "[Bot] Logon failed — server overloaded. Retrying in {secs} s."
@@ -897,6 +1024,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                                     });
                                 } else if self.pending_too_many_logins {
                                     self.pending_too_many_logins = false;
+                                    self.metrics.logon_failure("too_many_logins");
                                     let secs = self.delays.too_many_logins_secs;
                                     self.log_console(format!(
                                         "[Bot] Logon failed — too many logins at once. Retrying in {secs} s."
@@ -912,11 +1040,13 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                                     });
                                 } else if self.pending_relogon {
                                     self.pending_relogon = false;
+                                    self.metrics.logon_failure("relogon");
                                     self.log_console(
                                         "[Bot] Logon failed — server requested re-logon. Reconnecting.".to_string()
                                     );
                                 } else if self.pending_update_required {
                                     self.pending_update_required = false;
+                                    self.metrics.logon_failure("update_required");
                                     self.log_console(
                                         "[Bot] Logon failed — client update required. Stopping bot.".to_string()
                                     );
@@ -928,6 +1058,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                                     self.stop_requested = true;
                                 } else if self.pending_maintenance {
                                     self.pending_maintenance = false;
+                                    self.metrics.logon_failure("maintenance");
                                     let secs = self.delays.maintenance_secs;
                                     self.log_console(format!(
                                         "[Bot] Logon failed — server maintenance. Retrying in {secs} s."
@@ -942,6 +1073,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                                         status: "maintenance".into(),
                                     });
                                 } else {
+                                    self.metrics.logon_failure("other");
                                     self.log_console(
                                         "[Bot] Logon failed — disconnecting to re-fetch token"
                                             .to_string(),
@@ -952,6 +1084,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                             }
                         }
                         Some(IncomingPacket::GameUpdate(pkt)) => {
+                            self.metrics.packet_received(pkt.packet_type);
                             if let Some(tx) = &self.event_tx {
                                 tx.try_send(BotEventRaw::GameUpdate { pkt: pkt.clone() })
                                     .ok();
@@ -982,14 +1115,14 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                                 GamePacketType::SendInventoryState => {
                                     match Inventory::parse(&pkt.extra_data) {
                                         Ok(inv) => {
-                                            self.log_console(format!(
+                                            self.log_as(ConsoleCategory::World, format!(
                                                 "[Bot] Inventory: {} items",
                                                 inv.item_count
                                             ));
                                             self.inventory = inv.clone();
                                             self.emit_inventory_update();
                                         }
-                                        Err(e) => self.log_console(format!(
+                                        Err(e) => self.log_as(ConsoleCategory::World, format!(
                                             "[Bot] Inventory parse error: {e}"
                                         )),
                                     }
@@ -1000,7 +1133,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                                     self.local = LocalPlayer::default();
                                     match World::parse(&pkt.extra_data) {
                                         Ok(world) => {
-                                            self.log_console(format!(
+                                            self.log_as(ConsoleCategory::World, format!(
                                                 "[Bot] World: {}x{} tiles, {} objects",
                                                 world.tile_map.width,
                                                 world.tile_map.height,
@@ -1083,7 +1216,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                                             });
                                         }
                                         Err(e) => self
-                                            .log_console(format!("[Bot] World parse error: {e}")),
+                                            .log_as(ConsoleCategory::World, format!("[Bot] World parse error: {e}")),
                                     }
                                 }
                                 GamePacketType::State => self.on_state(&pkt),
@@ -1198,6 +1331,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
     pub fn send_game_packet(&mut self, pkt: &GameUpdatePacket, reliable: bool) {
         if let Some(id) = self.peer_id {
             let raw = packet::make_game_packet(pkt);
+            self.metrics.packet_sent(pkt.packet_type);
             let enet_pkt = if reliable {
                 enet::Packet::reliable(raw)
             } else {
@@ -1337,7 +1471,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                     self.local.net_id = data.get("netID").and_then(|s| s.parse().ok()).unwrap_or(0);
                     self.local.user_id =
                         data.get("userID").and_then(|s| s.parse().ok()).unwrap_or(0);
-                    self.log_console(format!(
+                    self.log_as(ConsoleCategory::World, format!(
                         "[Bot] OnSpawn (self) net_id={} user_id={}",
                         self.local.net_id, self.local.user_id
                     ));
@@ -1385,7 +1519,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                     let name = data.get("name").cloned().unwrap_or_default();
                     let country = data.get("country").cloned().unwrap_or_default();
 
-                    self.log_console(format!(
+                    self.log_as(ConsoleCategory::World, format!(
                         "[Bot] OnSpawn player={name} net_id={net_id} pos=({:.0},{:.0})",
                         position.0, position.1
                     ));
@@ -1410,7 +1544,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                     let is_mod = m_state > 0 || sm_state > 0 || invisible;
                     if net_id != self.local.net_id {
                         if self.auto_leave_on_mod && is_mod {
-                            self.log_console(format!(
+                            self.log_as(ConsoleCategory::World, format!(
                                 "[Bot] Mod detected ({}): mstate={} smstate={} invis={} — leaving",
                                 player.name, m_state, sm_state, invisible
                             ));
@@ -1458,7 +1592,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                     s.pos_x = x / 32.0;
                     s.pos_y = y / 32.0;
                 }
-                self.log_console(format!("[Bot] OnSetPos → ({x}, {y})"));
+                self.log_as(ConsoleCategory::World, format!("[Bot] OnSetPos → ({x}, {y})"));
                 self.emit(WsEvent::BotMove {
                     bot_id: self.bot_id,
                     x: x / 32.0,
@@ -1489,7 +1623,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                         country: p.country.clone(),
                     })
                     .collect();
-                self.log_console(format!("[Bot] OnRemove net_id={net_id}"));
+                self.log_as(ConsoleCategory::World, format!("[Bot] OnRemove net_id={net_id}"));
                 self.emit(WsEvent::PlayerLeave {
                     bot_id: self.bot_id,
                     net_id,
@@ -1506,11 +1640,11 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
             }
             "OnConsoleMessage" => {
                 let message = vl.get(1).map(|v| v.as_string()).unwrap_or_default();
-                self.log_console(message);
+                self.log_as(ConsoleCategory::Chat, message);
             }
             "OnDialogRequest" => {
                 let message = vl.get(1).map(|v| v.as_string()).unwrap_or_default();
-                self.log_console(format!("[Bot] Dialog: {}", message));
+                self.log_as(ConsoleCategory::World, format!("[Bot] Dialog: {}", message));
                 let cb = self.temporary_data.dialog_callback.lock().unwrap().take();
                 if let Some(cb) = cb {
                     cb(self);
@@ -1555,7 +1689,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                     height: 0,
                     tiles: vec![],
                 });
-                self.log_console("[Bot] OnRequestWorldSelectMenu → cleared world".to_string());
+                self.log_as(ConsoleCategory::World, "[Bot] OnRequestWorldSelectMenu → cleared world".to_string());
             }
             _ => {}
         }
@@ -1640,7 +1774,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                 bg,
             });
         }
-        self.log_console(format!("[Bot] TileChange ({x},{y}) item={item_id}"));
+        self.log_as(ConsoleCategory::World, format!("[Bot] TileChange ({x},{y}) item={item_id}"));
     }
 
     fn on_send_tile_update_data(&mut self, pkt: &GameUpdatePacket) {
@@ -1673,10 +1807,10 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                     fg,
                     bg,
                 });
-                self.log_console(format!("[Bot] TileUpdateData ({x},{y})"));
+                self.log_as(ConsoleCategory::World, format!("[Bot] TileUpdateData ({x},{y})"));
             }
             Err(e) => {
-                self.log_console(format!("[Bot] TileUpdateData failed ({x},{y}) (Error: {e})"));
+                self.log_as(ConsoleCategory::World, format!("[Bot] TileUpdateData failed ({x},{y}) (Error: {e})"));
             }
         }
     }
@@ -1717,10 +1851,10 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                         fg,
                         bg,
                     });
-                    self.log_console(format!("[Bot] TileUpdateDataMultiple ({x},{y})"));
+                    self.log_as(ConsoleCategory::World, format!("[Bot] TileUpdateDataMultiple ({x},{y})"));
                 }
                 Err(e) => {
-                    self.log_console(format!("[Bot] TileUpdateDataMultiple failed ({x},{y}) (Error: {e})"));
+                    self.log_as(ConsoleCategory::World, format!("[Bot] TileUpdateDataMultiple failed ({x},{y}) (Error: {e})"));
                     return;
                 }
             }
@@ -1756,7 +1890,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                 bg,
             });
         }
-        self.log_console(format!("[Bot] TileTreeState ({x},{y}) harvested"));
+        self.log_as(ConsoleCategory::World, format!("[Bot] TileTreeState ({x},{y}) harvested"));
     }
 
     fn on_modify_item_inventory(&mut self, pkt: &GameUpdatePacket) {
@@ -1764,14 +1898,14 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
         if pkt.jump_count != 0 {
             let amount = pkt.jump_count;
             self.inventory.sub_item(item_id, amount);
-            self.log_console(format!(
+            self.log_as(ConsoleCategory::World, format!(
                 "[Bot] ModifyItemInventory item={item_id} -{amount}"
             ));
         } else {
             // animation_type != 0
             let amount = pkt.jump_count;
             self.inventory.add_item(item_id, amount);
-            self.log_console(format!(
+            self.log_as(ConsoleCategory::World, format!(
                 "[Bot] ModifyItemInventory item={item_id} +{amount}"
             ));
         }
@@ -1963,7 +2097,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                             .unwrap_or(0);
                         let to_add = item.count.min(200u8.saturating_sub(current));
                         self.inventory.add_item(item.item_id, to_add);
-                        self.log_console(format!(
+                        self.log_as(ConsoleCategory::World, format!(
                             "[Bot] ItemCollect id={} count={}",
                             item.item_id, item.count
                         ));
@@ -2009,7 +2143,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
             fg,
             bg,
         });
-        self.log_console(format!("[Bot] SendLock tile=({x},{y}) item={fg}"));
+        self.log_as(ConsoleCategory::World, format!("[Bot] SendLock tile=({x},{y}) item={fg}"));
     }
 
     fn on_tile_apply_damage(&mut self, pkt: &GameUpdatePacket) {
@@ -2093,8 +2227,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
         let tile_x = base_x + offset_x;
         let tile_y = base_y + offset_y;
 
-        if tile_x > base_x + 4 || tile_x < base_x - 4 || tile_y > base_y + 4 || tile_y < base_y - 4
-        {
+        if offset_x.abs() > REACH_TILES || offset_y.abs() > REACH_TILES {
             return;
         }
 
@@ -2419,13 +2552,7 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                 Rep::Ack
             }
             Req::Enter { pass } => {
-                let cx = (self.pos_x / 32.0) as i32;
-                let cy = (self.pos_y / 32.0) as i32;
-                if let Some(pw) = pass {
-                    self.send_text(&format!("action|input\n|text|{pw}\n"));
-                } else {
-                    self.active_tile(cx, cy);
-                }
+                self.enter(pass.as_deref());
                 Rep::Ack
             }
             Req::Place { x, y, item } => {
@@ -2642,58 +2769,85 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
         }
     }
 
-    fn handle_command(&mut self, cmd: BotCommand) {
+    /// Start `content` on a new script thread, stopping the running one. The ticket
+    /// stays `running` until the script ends and is finished with its outcome.
+    fn run_script(&mut self, content: String, ticket: Option<CmdTicket>) {
+        // Stop any currently running script first.
+        self.script_stop.store(true, Ordering::Relaxed);
+        // Drop old channels so the previous script thread (if any) sees disconnection.
+        self.script_req_rx = None;
+        self.script_reply_tx = None;
+        self.event_tx = None;
+
+        self.script_stop.store(false, Ordering::Relaxed);
+
+        let (req_tx, req_rx) =
+            crossbeam_channel::unbounded::<crate::script_channel::ScriptRequest>();
+        let (reply_tx, reply_rx) =
+            crossbeam_channel::unbounded::<crate::script_channel::ScriptReply>();
+        let (event_tx, event_rx) = crossbeam_channel::bounded::<BotEventRaw>(256);
+
+        self.script_req_rx = Some(req_rx);
+        self.script_reply_tx = Some(reply_tx);
+        self.event_tx = Some(event_tx);
+
+        let items = self.items_dat.clone();
+        let state = self.state.clone();
+        let stop_flag = self.script_stop.clone();
+        let username = self.username.clone();
+        let done_tx = self.script_done_tx.clone();
+        let metrics = self.metrics.clone();
+
+        std::thread::spawn(move || {
+            metrics.script_started();
+            let outcome = crate::lua::run_script_threaded(
+                req_tx, reply_rx, event_rx, items, state, stop_flag, username, content,
+            );
+            if matches!(outcome, ScriptOutcome::Failed(_)) {
+                metrics.script_failed();
+            }
+            if let Some(ticket) = ticket {
+                ticket.finish(match &outcome {
+                    ScriptOutcome::Finished => Ok(()),
+                    ScriptOutcome::Stopped => Err("script was stopped".into()),
+                    ScriptOutcome::Failed(e) => Err(e.clone()),
+                });
+            }
+            if let Some(tx) = done_tx {
+                let _ = tx.send(outcome);
+            }
+        });
+    }
+
+    /// Carry out an API command. The error explains why the bot could not, for
+    /// commands it would otherwise ignore silently (out of reach, item missing, …).
+    fn handle_command(&mut self, cmd: BotCommand) -> Result<(), String> {
         match cmd {
             BotCommand::Move { x, y } => {
+                self.require_world()?;
                 let cx = ((self.pos_x / 32.0).round() as i32) + x;
                 let cy = ((self.pos_y / 32.0).round() as i32) + y;
-                if cx < 0 || cy < 0 { return; }
-                self.walk((cx + x) as u32, (cy + y) as u32);
+                self.require_tile(cx, cy)?;
+                self.walk(cx as u32, cy as u32);
             }
-            BotCommand::WalkTo { x, y } => {
+            BotCommand::WalkTo { x, y } | BotCommand::FindPath { x, y } => {
+                self.require_tile(x as i32, y as i32)?;
                 self.find_path(x, y);
             }
-            BotCommand::RunScript { content } => {
-                // Stop any currently running script first.
-                self.script_stop.store(true, Ordering::Relaxed);
-                // Drop old channels so the previous script thread (if any) sees disconnection.
-                self.script_req_rx = None;
-                self.script_reply_tx = None;
-                self.event_tx = None;
-
-                self.script_stop.store(false, Ordering::Relaxed);
-
-                let (req_tx, req_rx) =
-                    crossbeam_channel::unbounded::<crate::script_channel::ScriptRequest>();
-                let (reply_tx, reply_rx) =
-                    crossbeam_channel::unbounded::<crate::script_channel::ScriptReply>();
-                let (event_tx, event_rx) = crossbeam_channel::bounded::<BotEventRaw>(256);
-
-                self.script_req_rx = Some(req_rx);
-                self.script_reply_tx = Some(reply_tx);
-                self.event_tx = Some(event_tx);
-
-                let items = self.items_dat.clone();
-                let state = self.state.clone();
-                let stop_flag = self.script_stop.clone();
-                let username = self.username.clone();
-
-                std::thread::spawn(move || {
-                    crate::lua::run_script_threaded(
-                        req_tx, reply_rx, event_rx, items, state, stop_flag, username, content,
-                    );
-                });
-            }
+            BotCommand::RunScript { content } => self.run_script(content, None),
             BotCommand::StopScript => {
                 self.script_stop.store(true, Ordering::Relaxed);
             }
             BotCommand::Say { text } => {
+                self.require_connected()?;
                 self.say(&text);
             }
             BotCommand::Warp { name, id } => {
+                self.require_connected()?;
                 self.warp(&name, &id);
             }
             BotCommand::Disconnect => {
+                self.require_connected()?;
                 self.disconnect();
             }
             BotCommand::Reconnect => {
@@ -2704,35 +2858,86 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                 }
             }
             BotCommand::Place { x, y, item } => {
+                self.check_reach(x, y)?;
+                self.require_item(item, 1)?;
                 self.place(x, y, item, false);
             }
             BotCommand::Hit { x, y } => {
+                self.check_reach(x, y)?;
                 self.punch(x, y);
             }
             BotCommand::Wrench { x, y } => {
+                let (base_x, base_y) = self.tile_pos();
+                self.check_reach(x - base_x, y - base_y)?;
+                self.require_item(32, 1)?;
                 self.wrench_at(x, y);
             }
+            BotCommand::WrenchPlayer { net_id } => {
+                self.require_world()?;
+                if net_id != self.local.net_id && !self.players.contains_key(&net_id) {
+                    return Err(format!("no player with net_id {net_id} in this world"));
+                }
+                self.wrench_player(net_id);
+            }
+            BotCommand::Active { x, y } => {
+                self.require_tile(x, y)?;
+                self.active_tile(x, y);
+            }
+            BotCommand::Enter { pass } => {
+                if pass.is_some() {
+                    self.require_connected()?;
+                } else {
+                    self.require_world()?;
+                }
+                self.enter(pass.as_deref());
+            }
+            BotCommand::Buy { item } => {
+                self.require_connected()?;
+                self.buy(&item);
+            }
+            BotCommand::CollectObject { uid, range } => {
+                self.require_world()?;
+                let obj = self.world.as_ref().and_then(|w| w.objects.iter().find(|o| o.uid == uid).cloned());
+                let Some(obj) = obj else {
+                    return Err(format!("no object with uid {uid}"));
+                };
+                let (dx, dy) = (self.pos_x - obj.x, self.pos_y - obj.y);
+                let range_px = range * 32.0;
+                if dx * dx + dy * dy > range_px * range_px {
+                    return Err(format!("object {uid} is more than {range} tiles away"));
+                }
+                self.collect_object_at(uid, range);
+            }
+            BotCommand::SetDirection { facing_left } => {
+                self.require_world()?;
+                self.set_direction(facing_left);
+            }
             BotCommand::Wear { item_id } => {
+                self.require_item(item_id, 1)?;
                 self.wear(item_id);
             }
             BotCommand::Unwear { item_id } => {
+                self.require_item(item_id, 1)?;
                 self.unwear(item_id);
             }
             BotCommand::Drop { item_id, count } => {
+                self.require_world()?;
+                self.require_item(item_id, count)?;
                 self.drop_item(item_id, count);
             }
             BotCommand::Trash { item_id, count } => {
+                self.require_connected()?;
+                self.require_item(item_id, count)?;
                 self.trash_item(item_id, count);
             }
             BotCommand::LeaveWorld => {
+                self.require_world()?;
                 self.leave_world();
             }
             BotCommand::Respawn => {
+                self.require_world()?;
                 self.respawn();
             }
-            BotCommand::FindPath { x, y } => {
-                self.find_path(x, y);
-            }
             BotCommand::SetDelays(d) => {
                 self.delays = d.clone();
                 self.state.write().unwrap().delays = d.clone();
@@ -2765,8 +2970,62 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
                 st.collect_radius_tiles = self.collect_radius_tiles;
                 st.collect_blacklist = sorted_blacklist_vec(&self.collect_blacklist);
             }
-            BotCommand::AcceptAccess => self.accept_access(),
+            BotCommand::AcceptAccess => {
+                self.require_world()?;
+                self.accept_access();
+            }
         }
+        Ok(())
+    }
+
+    fn require_connected(&self) -> Result<(), String> {
+        if self.peer_id.is_none() {
+            return Err("not connected".into());
+        }
+        Ok(())
+    }
+
+    fn require_world(&self) -> Result<(), String> {
+        self.require_connected()?;
+        if self.world.is_none() {
+            return Err("not in a world".into());
+        }
+        Ok(())
+    }
+
+    /// The bot's current tile, as `place` computes it.
+    fn tile_pos(&self) -> (i32, i32) {
+        ((self.pos_x / 32.0).floor() as i32, (self.pos_y / 32.0).floor() as i32)
+    }
+
+    /// `x`, `y` are absolute tile coordinates.
+    fn require_tile(&self, x: i32, y: i32) -> Result<(), String> {
+        self.require_world()?;
+        let inside = x >= 0
+            && y >= 0
+            && self.world.as_ref().is_some_and(|w| w.get_tile(x as u32, y as u32).is_some());
+        if !inside {
+            return Err(format!("tile ({x}, {y}) is outside the world"));
+        }
+        Ok(())
+    }
+
+    /// The tile at this offset must be within the `REACH_TILES` reach `place` allows.
+    fn check_reach(&self, offset_x: i32, offset_y: i32) -> Result<(), String> {
+        let (base_x, base_y) = self.tile_pos();
+        self.require_tile(base_x + offset_x, base_y + offset_y)?;
+        if offset_x.abs() > REACH_TILES || offset_y.abs() > REACH_TILES {
+            return Err(format!("tile ({}, {}) is out of reach", base_x + offset_x, base_y + offset_y));
+        }
+        Ok(())
+    }
+
+    fn require_item(&self, item_id: u32, count: u32) -> Result<(), String> {
+        let have = self.inventory.items.get(&(item_id as u16)).map_or(0, |i| i.amount as u32);
+        if item_id > u16::MAX as u32 || have < count {
+            return Err(format!("only {have} of item {item_id} in the inventory, need {count}"));
+        }
+        Ok(())
     }
 
     // ── Lua-callable helpers ────────────────────────────────────────────────────
@@ -2820,6 +3079,17 @@ rid|{}\nplatformID|0,1,1\ndeviceVersion|0\ncountry|jp\nhash|{}\nmac|{}\nwk|{}\nz
         self.send_game_packet(&pkt, true);
     }
 
+    /// Enter the door the bot stands on, or answer a door password prompt.
+    pub fn enter(&mut self, pass: Option<&str>) {
+        if let Some(pw) = pass {
+            self.send_text(&format!("action|input\n|text|{pw}\n"));
+        } else {
+            let cx = (self.pos_x / 32.0) as i32;
+            let cy = (self.pos_y / 32.0) as i32;
+            self.active_tile(cx, cy);
+        }
+    }
+
     pub fn wrench_at(&mut self, tile_x: i32, tile_y: i32) {
         let base_x = (self.pos_x / 32.0).floor() as i32;
         let base_y = (self.pos_y / 32.0).floor() as i32;
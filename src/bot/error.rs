@@ -0,0 +1,46 @@
+use crate::bot_state::BotStatus;
+use std::fmt;
+
+/// Why a bot could not log in or had to stop. Each variant maps to a terminal `BotStatus`.
+#[derive(Debug)]
+pub enum BotError {
+    /// The login server rejected the username/password.
+    WrongCredentials,
+    /// Too many failed login attempts; the account is locked for a while.
+    LoginExhausted,
+    /// The ltoken string is malformed or the server refused it.
+    TokenInvalid(String),
+    /// The SOCKS5 proxy could not be reached or refused the UDP associate.
+    ProxyFailed(String),
+    /// Local socket / ENet setup failed, or the server sent an unusable address.
+    Network(String),
+}
+
+impl BotError {
+    pub fn status(&self) -> BotStatus {
+        match self {
+            BotError::WrongCredentials => BotStatus::WrongCredentials,
+            BotError::LoginExhausted   => BotStatus::LoginExhausted,
+            BotError::TokenInvalid(_)  => BotStatus::TokenInvalid,
+            BotError::ProxyFailed(_)   => BotStatus::ProxyFailed,
+            BotError::Network(_)       => BotStatus::Stopped,
+        }
+    }
+}
+
+impl fmt::Display for BotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BotError::WrongCredentials => write!(f, "Account credentials mismatched."),
+            BotError::LoginExhausted => write!(
+                f,
+                "Login attempts exhausted. Please try again after 24 hours."
+            ),
+            BotError::TokenInvalid(msg) => write!(f, "ltoken rejected: {msg}"),
+            BotError::ProxyFailed(msg)  => write!(f, "SOCKS5 proxy failed: {msg}"),
+            BotError::Network(msg)      => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl std::error::Error for BotError {}
@@ -1,6 +1,8 @@
 mod auth;
 mod core;
+mod error;
 mod shared;
 
 pub use core::Bot;
+pub use error::BotError;
 pub use shared::{BotEventRaw, Socks5Config};
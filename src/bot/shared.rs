@@ -1,11 +1,12 @@
 use crate::protocol::packet::GameUpdatePacket;
 use crate::protocol::variant::VariantList;
+use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 use std::sync::Mutex;
 
 use super::core::Bot;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Socks5Config {
     pub proxy_addr: SocketAddr,
     pub username: Option<String>,
@@ -2,17 +2,39 @@ use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc;
+use std::time::{Duration, Instant};
 
-use crate::bot::{Socks5Config};
-use crate::bot_state::{BotCommand, BotState, BotStatus, CmdSender};
+use crate::bot::BotError;
+use crate::bot_state::{BotCommand, BotState, BotStatus, CmdSender, QueuedCommand};
+use crate::command_tracker::CommandTracker;
+use crate::config::Config;
+use crate::console_log::ConsoleLog;
 use crate::events::{WsEvent, WsTx};
+use crate::groups::{Group, GroupStore};
+use crate::history::{self, HistoryStore, Sample};
 use crate::items::ItemsDat;
+use crate::metrics::BotMetrics;
+use crate::proxy_pool::ProxyPool;
+use crate::roster::{self, BotConfig, BotLogin, RosterEntry};
+use crate::scheduler::Scheduler;
+use crate::scripts::ScriptLibrary;
+use crate::supervisor::{self, BotExit, ExitOutcome, ExitRx, ExitTx};
+use crate::vault::Vault;
 
 pub struct BotEntry {
     pub username:         String,
     pub stop_flag:        Arc<AtomicBool>,
     pub state:            Arc<RwLock<BotState>>,
     pub cmd_tx:           CmdSender,
+    /// Spawn-time settings plus anything changed since, persisted to the roster.
+    pub config:           BotConfig,
+    /// Identifies the current thread; exits reported for older threads are ignored.
+    run_id:               u64,
+    started_at:           Instant,
+    /// Unix time of this launch; tags history samples so sessions can be told apart.
+    session:              u64,
+    /// Consecutive crash restarts, reset once the bot survives `supervisor::HEALTHY_RUN`.
+    restarts:             u32,
 }
 
 pub struct BotManager {
@@ -20,9 +42,27 @@ pub struct BotManager {
     pub bots:  HashMap<u32, BotEntry>,
     pub items_dat: Arc<ItemsDat>,
     pub ws_tx: WsTx,
+    pub vault: Vault,
+    pub proxy_pool: ProxyPool,
+    pub config: Arc<Config>,
+    pub scheduler: Scheduler,
+    pub groups: GroupStore,
+    pub scripts: ScriptLibrary,
+    pub commands: CommandTracker,
+    pub history: HistoryStore,
+    pub console_log: ConsoleLog,
+    /// Roster entries that were not started on restore (autostart off or no secret);
+    /// `save_roster` writes them back so they survive the restart.
+    parked:    HashMap<u32, BotConfig>,
+    /// Set once the roster has been replayed (requires an unlocked vault).
+    restored:  bool,
+    next_run_id: u64,
+    exit_tx:   ExitTx,
+    /// Handed to the supervisor task by `take_exit_rx`.
+    exit_rx:   Option<ExitRx>,
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, utoipa::ToSchema)]
 pub struct BotInfo {
     pub id:       u32,
     pub username: String,
@@ -32,85 +72,404 @@ pub struct BotInfo {
     pub pos_y:   f32,
     pub gems:    i32,
     pub ping_ms: u32,
+    pub autostart: bool,
+    pub tags:    Vec<String>,
+    pub last_error: Option<String>,
+}
+
+/// Outcome of one bot's share of a broadcast command.
+#[derive(serde::Serialize, utoipa::ToSchema)]
+pub struct Delivery {
+    pub bot_id: u32,
+    pub ok:     bool,
+    pub error:  Option<String>,
+    /// Tracking ID of the queued command, see `CommandTracker`.
+    pub cmd_id: Option<String>,
 }
 
 impl BotManager {
-    pub fn new(ws_tx: WsTx) -> Self {
-        Self { next_id: 0, bots: HashMap::new(), items_dat: Arc::new(ItemsDat::load()), ws_tx }
+    /// Errors when a state file can't be read, or does not parse and can't be moved aside.
+    pub fn new(ws_tx: WsTx, config: Arc<Config>) -> anyhow::Result<Self> {
+        let (exit_tx, exit_rx) = tokio::sync::mpsc::unbounded_channel();
+        let commands_ws_tx = ws_tx.clone();
+        let vault = Vault::new(&config.data_dir);
+        let console_log = ConsoleLog::new(&config.data_dir, &config.console_log);
+        let history = HistoryStore::new(&config.data_dir);
+        let scripts = ScriptLibrary::new(&config.scripts_dir());
+        scripts.import_loose();
+        Ok(Self {
+            // Kept console logs and history hold on to their IDs, so a new bot never inherits them.
+            next_id: console_log.bot_ids().last().into_iter()
+                .chain(history.bot_ids().last())
+                .max()
+                .map_or(0, |id| id + 1),
+            bots: HashMap::new(),
+            items_dat: Arc::new(ItemsDat::load(&config.items_dat)),
+            ws_tx,
+            proxy_pool: ProxyPool::new(&config.data_dir, vault.clone())?,
+            vault,
+            scheduler: Scheduler::new(&config.data_dir)?,
+            groups: GroupStore::new(&config.data_dir)?,
+            scripts,
+            commands: CommandTracker::new(commands_ws_tx),
+            history,
+            console_log,
+            config,
+            parked: HashMap::new(),
+            restored: false,
+            next_run_id: 0,
+            exit_tx,
+            exit_rx: Some(exit_rx),
+        })
     }
 
-    pub fn spawn(&mut self, username: String, password: String, proxy: Option<Socks5Config>) -> u32 {
-        let id = self.next_id;
-        self.next_id += 1;
-
-        let stop_flag   = Arc::new(AtomicBool::new(false));
-        let stop_clone  = stop_flag.clone();
-        let uname       = username.clone();
-        let pass        = password.clone();
-
-        let state = Arc::new(RwLock::new(BotState {
-            status: BotStatus::Connecting,
-            ..Default::default()
-        }));
-        let state_clone = state.clone();
-
-        let (cmd_tx, cmd_rx) = mpsc::channel::<BotCommand>();
+    pub fn take_exit_rx(&mut self) -> Option<ExitRx> {
+        self.exit_rx.take()
+    }
 
-        let items_dat = self.items_dat.clone();
-        let ws_tx_clone = self.ws_tx.clone();
+    /// Replay the saved roster: every `autostart` bot is spawned again under its old id.
+    /// Does nothing until the vault is unlocked, since the bots' secrets live there.
+    pub fn restore(&mut self) {
+        if self.restored {
+            return;
+        }
+        if !self.vault.is_unlocked() {
+            println!("[Roster] Vault locked — bots will be restored after login");
+            return;
+        }
+        self.proxy_pool.unseal();
+        let entries = match roster::load(&self.config.data_dir, &self.vault) {
+            Ok(entries) => entries,
+            Err(e) => {
+                // Leave `restored` unset so nothing overwrites the file until it is fixed.
+                println!("[Roster] {e} — not restoring bots and not saving the roster");
+                return;
+            }
+        };
+        self.restored = true;
 
-        std::thread::spawn(move || {
-            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                let mut bot = crate::bot::Bot::new(&uname, &pass, proxy, state_clone, cmd_rx, items_dat, id, Some(ws_tx_clone));
-                bot.run(stop_clone);
-            })) {
-                Ok(_)  => println!("[Bot:{id}] Stopped."),
-                Err(_) => println!("[Bot:{id}] Crashed."),
+        for mut entry in entries {
+            self.next_id = self.next_id.max(entry.id + 1);
+            // Rosters written before the vault existed carry the secret in plain text.
+            let plain = entry.config.login.secret().to_string();
+            if !plain.is_empty()
+                && let Err(e) = self.vault.put(entry.id, &plain)
+            {
+                println!("[Vault] Failed to store secret for bot {}: {e}", entry.id);
             }
-        });
+            if !entry.config.autostart {
+                println!("[Roster] Skipping bot {} (autostart disabled)", entry.id);
+                self.parked.insert(entry.id, entry.config);
+                continue;
+            }
+            if plain.is_empty() {
+                match self.vault.get(entry.id) {
+                    Ok(Some(secret)) => entry.config.login.set_secret(secret),
+                    Ok(None) => {
+                        println!("[Roster] No secret in vault for bot {} — skipping", entry.id);
+                        self.parked.insert(entry.id, entry.config);
+                        continue;
+                    }
+                    Err(e) => {
+                        println!("[Vault] Failed to read secret for bot {}: {e} — skipping", entry.id);
+                        self.parked.insert(entry.id, entry.config);
+                        continue;
+                    }
+                }
+            }
+            println!("[Roster] Restoring bot {}", entry.id);
+            self.launch(entry.id, entry.config, Arc::default());
+        }
+        self.save_roster();
+    }
 
-        self.bots.insert(id, BotEntry { username: username.clone(), stop_flag, state, cmd_tx });
-        let _ = self.ws_tx.send(WsEvent::BotAdded { bot_id: id, username });
-        id
+    /// Whether the saved roster has been replayed. Until then IDs of saved bots are
+    /// unknown and secrets can't be stored, so no bots may be added.
+    pub fn is_restored(&self) -> bool {
+        self.restored
     }
 
-    pub fn spawn_ltoken(&mut self, ltoken_str: String, proxy: Option<Socks5Config>) -> u32 {
+    /// Returns `Ok(None)`, spawning nothing, until the roster is restored. Errors,
+    /// also spawning nothing, when the bot's secret can't be stored in the vault: it
+    /// could not be restored after a restart.
+    pub fn spawn(&mut self, config: BotConfig) -> anyhow::Result<Option<u32>> {
+        if !self.restored {
+            return Ok(None);
+        }
         let id = self.next_id;
+        self.vault.put(id, config.login.secret())
+            .map_err(|e| anyhow::anyhow!("cannot store the secret for bot {id}: {e}"))?;
         self.next_id += 1;
+        self.launch(id, config, Arc::default());
+        self.save_roster();
+        Ok(Some(id))
+    }
 
+    /// Start the bot thread. `metrics` is passed in so restarts keep counting.
+    fn launch(&mut self, id: u32, config: BotConfig, metrics: Arc<BotMetrics>) {
         let stop_flag  = Arc::new(AtomicBool::new(false));
         let stop_clone = stop_flag.clone();
 
         let state = Arc::new(RwLock::new(BotState {
             status: BotStatus::Connecting,
+            delays: config.delays.clone(),
+            auto_collect: config.auto_collect,
+            collect_radius_tiles: config.collect_radius_tiles,
+            collect_blacklist: config.collect_blacklist.clone(),
+            auto_reconnect: config.auto_reconnect,
+            console_limit: self.config.console_lines,
+            metrics,
+            console_log: Some(Arc::new(self.console_log.writer(id))),
             ..Default::default()
         }));
         let state_clone = state.clone();
 
-        let (cmd_tx, cmd_rx) = mpsc::channel::<BotCommand>();
+        let (cmd_tx, cmd_rx) = mpsc::channel::<QueuedCommand>();
+        // Queued before the bot starts so they are applied on its first tick.
+        for cmd in config.startup_commands() {
+            let _ = cmd_tx.send(cmd.into());
+        }
 
         let items_dat = self.items_dat.clone();
         let ws_tx_clone = self.ws_tx.clone();
+        let login = config.login.clone();
+        let pool = (config.proxy.is_none() && config.use_proxy_pool).then(|| self.proxy_pool.clone());
+        let proxy = match &pool {
+            Some(pool) => pool.assign(id),
+            None => config.proxy.clone(),
+        };
+
+        let run_id = self.next_run_id;
+        self.next_run_id += 1;
+        let exit_tx = self.exit_tx.clone();
 
         std::thread::spawn(move || {
-            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                let mut bot = crate::bot::Bot::new_ltoken(&ltoken_str, proxy, state_clone, cmd_rx, items_dat, id, Some(ws_tx_clone));
-                bot.run(stop_clone);
-            })) {
-                Ok(_)  => println!("[Bot:{id}] Stopped."),
-                Err(_) => println!("[Bot:{id}] Crashed."),
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                // Never fall back to a direct connection: that would expose the host's address.
+                if pool.is_some() && proxy.is_none() {
+                    return Err(BotError::ProxyFailed("no free proxy in the pool".into()));
+                }
+                let mut bot = match &login {
+                    BotLogin::Legacy { username, password } => crate::bot::Bot::new(
+                        username, password, proxy, state_clone, cmd_rx, items_dat, id, Some(ws_tx_clone),
+                    ),
+                    BotLogin::Ltoken { ltoken } => crate::bot::Bot::new_ltoken(
+                        ltoken, proxy, state_clone, cmd_rx, items_dat, id, Some(ws_tx_clone),
+                    ),
+                }?;
+                bot.proxy_pool = pool;
+                bot.run(stop_clone)
+            }));
+            let outcome = match result {
+                Ok(Ok(())) => {
+                    println!("[Bot:{id}] Stopped.");
+                    ExitOutcome::Stopped
+                }
+                Ok(Err(e)) => {
+                    println!("[Bot:{id}] Stopped: {e}");
+                    ExitOutcome::Failed(e)
+                }
+                Err(payload) => {
+                    let msg = supervisor::panic_message(payload.as_ref());
+                    println!("[Bot:{id}] Crashed: {msg}");
+                    ExitOutcome::Crashed(msg)
+                }
+            };
+            let _ = exit_tx.send(BotExit { bot_id: id, run_id, outcome });
+        });
+
+        let username = config.username().to_string();
+        self.bots.insert(id, BotEntry {
+            username: username.clone(),
+            stop_flag,
+            state,
+            cmd_tx,
+            config,
+            run_id,
+            started_at: Instant::now(),
+            session: history::unix_now(),
+            restarts: 0,
+        });
+        let _ = self.ws_tx.send(WsEvent::BotAdded { bot_id: id, username });
+    }
+
+    /// Record a bot thread's exit: set the terminal status, notify the dashboard and
+    /// return the restart delay if the supervisor should respawn it.
+    pub fn handle_exit(&mut self, exit: BotExit) -> Option<Duration> {
+        let entry = self.bots.get_mut(&exit.bot_id)?;
+        if entry.run_id != exit.run_id || entry.stop_flag.load(Ordering::Relaxed) {
+            // Stopped through the API, or an older thread of a relaunched bot.
+            return None;
+        }
+
+        let crashed = matches!(exit.outcome, ExitOutcome::Crashed(_));
+        let restart = if crashed && entry.config.restart_on_crash {
+            if entry.started_at.elapsed() >= supervisor::HEALTHY_RUN {
+                entry.restarts = 0;
+            }
+            Some(supervisor::backoff(entry.restarts + 1))
+        } else {
+            None
+        };
+
+        let (status, error) = {
+            let mut s = entry.state.write().unwrap();
+            match exit.outcome {
+                ExitOutcome::Crashed(msg) => {
+                    s.status = BotStatus::Crashed;
+                    s.last_error = Some(msg);
+                }
+                ExitOutcome::Failed(e) => {
+                    s.status = e.status();
+                    s.last_error = Some(e.to_string());
+                }
+                // UPDATE REQUIRED is already a terminal status; keep it.
+                ExitOutcome::Stopped if s.status == BotStatus::UpdateRequired => {}
+                ExitOutcome::Stopped => s.status = BotStatus::Stopped,
             }
+            s.ping_ms = 0;
+            (s.status.to_string(), s.last_error.clone())
+        };
+
+        match restart {
+            Some(delay) => println!("[Bot:{}] Restarting in {}s", exit.bot_id, delay.as_secs()),
+            None if crashed => println!("[Bot:{}] Not restarting (restart_on_crash disabled)", exit.bot_id),
+            None => {}
+        }
+        // A restart takes the same pooled proxy again; otherwise free its slot.
+        if restart.is_none() {
+            self.proxy_pool.release(exit.bot_id);
+        }
+        let _ = self.ws_tx.send(WsEvent::BotStatus { bot_id: exit.bot_id, status: status.clone() });
+        let _ = self.ws_tx.send(WsEvent::BotExited {
+            bot_id: exit.bot_id,
+            status,
+            error,
+            restart_in_secs: restart.map(|d| d.as_secs()),
         });
+        restart
+    }
+
+    /// Start a crashed bot again under the same id and configuration.
+    /// Does nothing if it was stopped or relaunched in the meantime.
+    pub fn relaunch(&mut self, id: u32, run_id: u64) {
+        let Some(old) = self.bots.get(&id) else {
+            return;
+        };
+        if old.run_id != run_id || old.stop_flag.load(Ordering::Relaxed) {
+            return;
+        }
+        let config = old.config.clone();
+        let restarts = old.restarts + 1;
+        let (console, last_error, metrics) = {
+            let s = old.state.read().unwrap();
+            (s.console.clone(), s.last_error.clone(), s.metrics.clone())
+        };
 
-        self.bots.insert(id, BotEntry { username: String::new(), stop_flag, state, cmd_tx });
-        let _ = self.ws_tx.send(WsEvent::BotAdded { bot_id: id, username: String::new() });
-        id
+        println!("[Bot:{id}] Restart #{restarts}");
+        self.launch(id, config, metrics);
+        if let Some(entry) = self.bots.get_mut(&id) {
+            entry.restarts = restarts;
+            let mut s = entry.state.write().unwrap();
+            s.console = console;
+            s.last_error = last_error;
+            s.restarts = restarts;
+        }
+    }
+
+    /// Write every live bot to `data/bots.json`. Secrets are skipped by `BotLogin` and
+    /// proxy passwords are sealed by `roster::save`.
+    fn save_roster(&self) {
+        if !self.restored {
+            // Don't clobber a roster we haven't been able to read back yet.
+            return;
+        }
+        let mut entries: Vec<RosterEntry> = self.bots.iter()
+            .map(|(id, e)| (id, &e.config))
+            .chain(&self.parked)
+            .map(|(id, config)| RosterEntry { id: *id, config: config.clone() })
+            .collect();
+        entries.sort_by_key(|e| e.id);
+        if let Err(e) = roster::save(&self.config.data_dir, &entries, &self.vault) {
+            println!("[Roster] Failed to save bots.json: {e}");
+        }
+    }
+
+    /// Also works for parked roster entries, which then start on the next restore.
+    pub fn set_autostart(&mut self, id: u32, enabled: bool) -> bool {
+        let config = match self.bots.get_mut(&id) {
+            Some(entry) => &mut entry.config,
+            None => match self.parked.get_mut(&id) {
+                Some(config) => config,
+                None => return false,
+            },
+        };
+        config.autostart = enabled;
+        self.save_roster();
+        true
+    }
+
+    /// Also works for parked roster entries, so they can join tag groups before they start.
+    pub fn set_tags(&mut self, id: u32, tags: Vec<String>) -> bool {
+        let config = match self.bots.get_mut(&id) {
+            Some(entry) => &mut entry.config,
+            None => match self.parked.get_mut(&id) {
+                Some(config) => config,
+                None => return false,
+            },
+        };
+        config.tags = tags;
+        self.save_roster();
+        true
+    }
+
+    /// Running bots carrying at least one of `tags`, sorted.
+    pub fn tagged(&self, tags: &[String]) -> Vec<u32> {
+        let mut ids: Vec<u32> = self.bots.iter()
+            .filter(|(_, e)| e.config.tags.iter().any(|t| tags.contains(t)))
+            .map(|(id, _)| *id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Running bots in `group`: its listed IDs plus every bot with one of its tags.
+    pub fn group_members(&self, group: &Group) -> Vec<u32> {
+        let mut ids: Vec<u32> = group.bot_ids.iter()
+            .copied()
+            .filter(|id| self.bots.contains_key(id))
+            .chain(self.tagged(&group.tags))
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
     }
 
     pub fn stop(&mut self, id: u32) -> bool {
         if let Some(entry) = self.bots.remove(&id) {
             entry.stop_flag.store(true, Ordering::Relaxed);
+            // The thread may log a few more lines on its way out; keep them off disk.
+            // The log files stay until they are purged with `DELETE /bots/{id}/console`,
+            // the history until it ages out or `DELETE /bots/{id}/history`.
+            entry.state.write().unwrap().console_log = None;
+            self.proxy_pool.release(id);
+            self.scheduler.remove_for_bot(id);
+            self.groups.remove_bot(id);
+            self.commands.abandon(id);
             let _ = self.ws_tx.send(WsEvent::BotRemoved { bot_id: id });
+            if let Err(e) = self.vault.remove(id) {
+                println!("[Vault] Failed to remove secret for bot {id}: {e}");
+            }
+            self.save_roster();
+            true
+        } else if self.parked.remove(&id).is_some() {
+            // Never started this run, so only its saved traces need to go.
+            self.scheduler.remove_for_bot(id);
+            self.groups.remove_bot(id);
+            if let Err(e) = self.vault.remove(id) {
+                println!("[Vault] Failed to remove secret for bot {id}: {e}");
+            }
+            self.save_roster();
             true
         } else {
             false
@@ -129,20 +488,136 @@ impl BotManager {
                 pos_y:   s.pos_y,
                 gems:    s.gems,
                 ping_ms: s.ping_ms,
+                autostart: e.config.autostart,
+                tags:    e.config.tags.clone(),
+                last_error: s.last_error.clone(),
+            }
+        }).collect()
+    }
+
+    /// The state handle of every bot, sorted by ID, so callers can read the states
+    /// after releasing the manager lock.
+    pub fn bot_states(&self) -> Vec<(u32, Arc<RwLock<BotState>>)> {
+        let mut states: Vec<_> = self.bots.iter().map(|(id, e)| (*id, e.state.clone())).collect();
+        states.sort_unstable_by_key(|(id, _)| *id);
+        states
+    }
+
+    /// Current history sample of every bot that is in game.
+    pub fn history_samples(&self) -> Vec<(u32, Sample)> {
+        let ts = history::unix_now();
+        let tracked = &self.config.history.items;
+        self.bots.iter().filter_map(|(id, e)| {
+            let s = e.state.read().unwrap();
+            if !history::should_sample(&s.status) {
+                return None;
             }
+            let mut items: std::collections::BTreeMap<u16, u32> =
+                tracked.iter().map(|item| (*item, 0)).collect();
+            for slot in &s.inventory {
+                if let Some(count) = items.get_mut(&slot.item_id) {
+                    *count += slot.amount as u32;
+                }
+            }
+            let track = s.track_info.as_ref();
+            Some((*id, Sample {
+                ts,
+                session:     e.session,
+                gems:        s.gems,
+                level:       track.map_or(0, |t| t.level),
+                awesomeness: track.map_or(0, |t| t.awesomeness),
+                world:       s.world_name.clone(),
+                items,
+            }))
         }).collect()
     }
 
+    /// Decrypted login details for every roster entry, parked ones included, for the
+    /// explicit vault export.
+    pub fn export_logins(&self) -> anyhow::Result<Vec<(u32, BotLogin)>> {
+        let mut logins = self.bots.iter()
+            .map(|(id, e)| (id, &e.config))
+            .chain(&self.parked)
+            .map(|(id, config)| -> anyhow::Result<(u32, BotLogin)> {
+                let mut login = config.login.clone();
+                if let Some(secret) = self.vault.get(*id)? {
+                    login.set_secret(secret);
+                }
+                Ok((*id, login))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        logins.sort_by_key(|(id, _)| *id);
+        Ok(logins)
+    }
+
     pub fn get_state(&self, id: u32) -> Option<BotState> {
         self.bots.get(&id).map(|e| e.state.read().unwrap().clone())
     }
 
-    pub fn send_cmd(&self, id: u32, cmd: BotCommand) -> bool {
-        self.bots.get(&id).map(|e| e.cmd_tx.send(cmd).is_ok()).unwrap_or(false)
+    /// Send the commands of all scheduled jobs due at `now` and report each result.
+    pub fn run_due_jobs(&mut self, now: chrono::DateTime<chrono::Local>) {
+        let scripts_dir = self.config.scripts_dir();
+        for job in self.scheduler.due(now) {
+            let request = serde_json::to_value(&job.command).unwrap_or_default();
+            let result = job.command.clone().into_command(&scripts_dir).and_then(|cmd| {
+                match self.submit_cmd(job.bot_id, request, cmd) {
+                    Some(_) => Ok(()),
+                    None => Err("bot is not running".to_string()),
+                }
+            });
+            let event = match &result {
+                Ok(()) => {
+                    println!("[Scheduler] Job {} fired for bot {}", job.id, job.bot_id);
+                    WsEvent::JobFired { job_id: job.id.clone(), bot_id: job.bot_id }
+                }
+                Err(e) => {
+                    println!("[Scheduler] Job {} failed for bot {}: {e}", job.id, job.bot_id);
+                    WsEvent::JobFailed { job_id: job.id.clone(), bot_id: job.bot_id, error: e.clone() }
+                }
+            };
+            let _ = self.ws_tx.send(event);
+            self.scheduler.record(&job.id, now.timestamp() as u64, result.err());
+        }
+    }
+
+    /// Send `cmd` with a tracking record for `request` (the API body it came from).
+    /// Returns the command ID, or None if the bot is not running.
+    pub fn submit_cmd(&mut self, id: u32, request: serde_json::Value, cmd: BotCommand) -> Option<String> {
+        if !self.bots.contains_key(&id) {
+            return None;
+        }
+        let ticket = self.commands.queue(id, request);
+        let cmd_id = ticket.id().to_string();
+        self.queue_cmd(id, QueuedCommand { cmd, ticket: Some(ticket) }).then_some(cmd_id)
+    }
+
+    fn queue_cmd(&mut self, id: u32, queued: QueuedCommand) -> bool {
+        let Some(entry) = self.bots.get_mut(&id) else {
+            return false;
+        };
+        let changed = entry.config.apply(&queued.cmd);
+        let sent = entry.cmd_tx.send(queued).is_ok();
+        if changed {
+            self.save_roster();
+        }
+        sent
+    }
+
+    /// Send `cmd` to each of `ids` and report per bot whether it was queued.
+    pub fn broadcast_cmd(&mut self, ids: &[u32], request: serde_json::Value, cmd: BotCommand) -> Vec<Delivery> {
+        ids.iter().map(|&bot_id| {
+            if !self.bots.contains_key(&bot_id) {
+                return Delivery { bot_id, ok: false, error: Some("bot not found".into()), cmd_id: None };
+            }
+            match self.submit_cmd(bot_id, request.clone(), cmd.clone()) {
+                Some(cmd_id) => Delivery { bot_id, ok: true, error: None, cmd_id: Some(cmd_id) },
+                None => Delivery { bot_id, ok: false, error: Some("bot is not running".into()), cmd_id: None },
+            }
+        }).collect()
     }
 
-    pub fn run_script(&self, id: u32, content: String) -> bool {
-        self.send_cmd(id, BotCommand::RunScript { content })
+    pub fn run_script(&mut self, id: u32, content: String) -> bool {
+        self.queue_cmd(id, BotCommand::RunScript { content }.into())
     }
 
     pub fn find_by_name(&self, name: &str) -> Option<(Arc<RwLock<BotState>>, CmdSender)> {
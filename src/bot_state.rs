@@ -1,9 +1,13 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use std::fmt;
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc, RwLock};
+use crate::command_tracker::CmdTicket;
+use crate::console_log::{ConsoleCategory, ConsoleWriter};
+use crate::metrics::BotMetrics;
 use crate::world::TileType;
 
-#[derive(Default, Clone, Serialize, PartialEq)]
+#[derive(Default, Clone, Serialize, PartialEq, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum BotStatus {
     #[default]
@@ -20,6 +24,18 @@ pub enum BotStatus {
     UpdateRequired,
     /// Server is under maintenance. Retrying after 600 s.
     Maintenance,
+    /// Legacy login rejected the username/password. Bot stopped.
+    WrongCredentials,
+    /// Too many failed login attempts on the account. Bot stopped.
+    LoginExhausted,
+    /// The ltoken is malformed or was rejected by the server. Bot stopped.
+    TokenInvalid,
+    /// The SOCKS5 proxy could not be used and no pooled replacement was free. Bot stopped.
+    ProxyFailed,
+    /// The bot thread exited on its own. See `last_error`.
+    Stopped,
+    /// The bot thread panicked. See `last_error`.
+    Crashed,
 }
 
 impl fmt::Display for BotStatus {
@@ -33,11 +49,17 @@ impl fmt::Display for BotStatus {
             BotStatus::TooManyLogins    => write!(f, "too_many_logins"),
             BotStatus::UpdateRequired   => write!(f, "update_required"),
             BotStatus::Maintenance      => write!(f, "maintenance"),
+            BotStatus::WrongCredentials => write!(f, "wrong_credentials"),
+            BotStatus::LoginExhausted   => write!(f, "login_exhausted"),
+            BotStatus::TokenInvalid     => write!(f, "token_invalid"),
+            BotStatus::ProxyFailed      => write!(f, "proxy_failed"),
+            BotStatus::Stopped          => write!(f, "stopped"),
+            BotStatus::Crashed          => write!(f, "crashed"),
         }
     }
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, ToSchema)]
 pub struct TileInfo {
     pub fg_item_id: u16,
     pub bg_item_id: u16,
@@ -51,7 +73,7 @@ impl Default for TileInfo {
     }
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, ToSchema)]
 pub struct PlayerInfo {
     pub net_id:  u32,
     pub name:    String,
@@ -60,7 +82,7 @@ pub struct PlayerInfo {
     pub country: String,
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, ToSchema)]
 pub struct InvSlot {
     pub item_id:     u16,
     pub amount:      u8,
@@ -68,7 +90,7 @@ pub struct InvSlot {
     pub action_type: u8,
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, ToSchema)]
 pub struct WorldObjectInfo {
     pub uid:     u32,
     pub item_id: u16,
@@ -77,7 +99,7 @@ pub struct WorldObjectInfo {
     pub count:   u8,
 }
 
-#[derive(Default, Clone, Serialize)]
+#[derive(Default, Clone, Serialize, ToSchema)]
 pub struct TrackInfo {
     pub level:           u32,
     pub grow_id:         u64,
@@ -86,7 +108,8 @@ pub struct TrackInfo {
     pub awesomeness:     u32,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
 pub struct BotDelays {
     pub place_ms:             u64,
     pub walk_ms:              u64,
@@ -109,7 +132,7 @@ impl Default for BotDelays {
     }
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, ToSchema)]
 pub struct BotState {
     pub status:          BotStatus,
     pub username:        String,
@@ -151,6 +174,19 @@ pub struct BotState {
     pub collect_path_check: bool,
     /// Whether the bot should automatically reconnect after a disconnect.
     pub auto_reconnect: bool,
+    /// Why the bot last stopped or crashed (panic message for crashes).
+    pub last_error: Option<String>,
+    /// Times the supervisor has restarted this bot after a crash.
+    pub restarts: u32,
+    /// Maximum console lines kept (`console_lines` in the server config).
+    #[serde(skip)]
+    pub console_limit: usize,
+    /// Counters exported on `/metrics`.
+    #[serde(skip)]
+    pub metrics: Arc<BotMetrics>,
+    /// Persists console lines to `<data_dir>/console`; None keeps them in memory only.
+    #[serde(skip)]
+    pub console_log: Option<Arc<ConsoleWriter>>,
 }
 
 impl Default for BotState {
@@ -183,10 +219,35 @@ impl Default for BotState {
             auto_ban: false,
             collect_path_check: true,
             auto_reconnect: true,
+            last_error: None,
+            restarts: 0,
+            console_limit: 100,
+            metrics: Arc::default(),
+            console_log: None,
         }
     }
 }
 
+impl BotState {
+    /// Append a console line, dropping the oldest ones beyond `console_limit`, and
+    /// write it to the bot's console log once the state lock is released.
+    pub fn push_console(state: &RwLock<Self>, category: ConsoleCategory, msg: String) {
+        let log = {
+            let mut s = state.write().unwrap();
+            s.console.push(msg.clone());
+            if s.console.len() > s.console_limit {
+                let excess = s.console.len() - s.console_limit;
+                s.console.drain(..excess);
+            }
+            s.console_log.clone()
+        };
+        if let Some(log) = log {
+            log.append(category, &msg);
+        }
+    }
+}
+
+#[derive(Clone)]
 pub enum BotCommand {
     Move { x: i32, y: i32 },
     WalkTo { x: u32, y: u32 },
@@ -199,6 +260,12 @@ pub enum BotCommand {
     Place { x: i32, y: i32, item: u32 },
     Hit { x: i32, y: i32 },
     Wrench { x: i32, y: i32 },
+    WrenchPlayer { net_id: u32 },
+    Active { x: i32, y: i32 },
+    Enter { pass: Option<String> },
+    Buy { item: String },
+    CollectObject { uid: u32, range: f32 },
+    SetDirection { facing_left: bool },
     Wear { item_id: u32 },
     Unwear { item_id: u32 },
     Drop { item_id: u32, count: u32 },
@@ -216,5 +283,18 @@ pub enum BotCommand {
     AcceptAccess,
 }
 
-pub type CmdSender   = mpsc::Sender<BotCommand>;
-pub type CmdReceiver = mpsc::Receiver<BotCommand>;
+/// A command on its way to the bot thread, with the ticket that reports its outcome
+/// when it was sent through the API.
+pub struct QueuedCommand {
+    pub cmd:    BotCommand,
+    pub ticket: Option<CmdTicket>,
+}
+
+impl From<BotCommand> for QueuedCommand {
+    fn from(cmd: BotCommand) -> Self {
+        Self { cmd, ticket: None }
+    }
+}
+
+pub type CmdSender   = mpsc::Sender<QueuedCommand>;
+pub type CmdReceiver = mpsc::Receiver<QueuedCommand>;
@@ -0,0 +1,210 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, RwLock};
+use std::time::Duration;
+
+use crate::bot::{Bot, Socks5Config};
+use crate::bot_state::{BotCommand, BotState, BotStatus, QueuedCommand};
+use crate::config::Config;
+use crate::import;
+use crate::items::ItemsDat;
+use crate::roster::{BotConfig, BotLogin};
+use crate::script_channel::ScriptOutcome;
+
+// ── Headless command line ─────────────────────────────────────────────────────
+//
+//   Mori [serve]                      start the web dashboard (default)
+//   Mori run --account … script.lua   run one script on one account, then exit
+
+pub const USAGE: &str = "\
+Usage:
+  Mori [serve]                      Start the web dashboard (default)
+  Mori run [options] <script.lua>   Log in one account, run a Lua script and exit
+
+Options for run:
+  --account <line>                  Account in any bulk-import form (required):
+                                    user:pass[:host:port[:puser:ppass]] or token|rid|mac|wk
+  --proxy <host:port[:user:pass]>   SOCKS5 proxy; overrides one given in --account
+
+Exit status of run: 0 script finished, 1 script error, 2 usage error,
+3 the bot failed to log in or stopped before the script ended.";
+
+pub const EXIT_SCRIPT_FAILED: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_BOT_FAILED: i32 = 3;
+
+pub enum Command {
+    Serve,
+    Help,
+    Run(RunArgs),
+}
+
+pub struct RunArgs {
+    pub login:  BotLogin,
+    pub proxy:  Option<Socks5Config>,
+    pub script: PathBuf,
+}
+
+/// Parse the arguments after the program name.
+pub fn parse(args: &[String]) -> Result<Command, String> {
+    let Some((sub, rest)) = args.split_first() else {
+        return Ok(Command::Serve);
+    };
+    match sub.as_str() {
+        "serve" if rest.is_empty() => Ok(Command::Serve),
+        "help" | "-h" | "--help" => Ok(Command::Help),
+        "run" => parse_run(rest).map(Command::Run),
+        other => Err(format!("unknown command '{other}'")),
+    }
+}
+
+fn parse_run(args: &[String]) -> Result<RunArgs, String> {
+    let mut login = None;
+    let mut proxy = None;
+    let mut account_proxy = None;
+    let mut script = None;
+
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--account" => {
+                let value = it.next().ok_or("--account needs a value")?;
+                let (parsed, line_proxy) = import::parse_line(value)
+                    .map_err(|e| format!("--account: {e}"))?;
+                login = Some(parsed);
+                account_proxy = line_proxy;
+            }
+            "--proxy" => {
+                let value = it.next().ok_or("--proxy needs a value")?;
+                proxy = Some(import::parse_proxy(value).map_err(|e| format!("--proxy: {e}"))?);
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown option '{flag}'")),
+            path if script.is_none() => script = Some(PathBuf::from(path)),
+            extra => return Err(format!("unexpected argument '{extra}'")),
+        }
+    }
+
+    Ok(RunArgs {
+        login:  login.ok_or("--account is required")?,
+        proxy:  proxy.or(account_proxy),
+        script: script.ok_or("missing script path")?,
+    })
+}
+
+// ── mori run ──────────────────────────────────────────────────────────────────
+
+/// Log in, run the script and wait for it. Console output goes to stdout.
+/// Returns the process exit status.
+pub fn run(config: &Config, args: RunArgs) -> i32 {
+    let script = match std::fs::read_to_string(&args.script) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("[CLI] Cannot read {}: {e}", args.script.display());
+            return EXIT_USAGE;
+        }
+    };
+
+    let items_dat = Arc::new(ItemsDat::load(&config.items_dat));
+    let bot_config = BotConfig::new(args.login, args.proxy, &config.bot_defaults);
+    let state = Arc::new(RwLock::new(BotState {
+        status: BotStatus::Connecting,
+        delays: bot_config.delays.clone(),
+        auto_collect: bot_config.auto_collect,
+        collect_radius_tiles: bot_config.collect_radius_tiles,
+        auto_reconnect: bot_config.auto_reconnect,
+        console_limit: config.console_lines,
+        ..Default::default()
+    }));
+
+    let (cmd_tx, cmd_rx) = mpsc::channel::<QueuedCommand>();
+    for cmd in bot_config.startup_commands() {
+        let _ = cmd_tx.send(cmd.into());
+    }
+    let _ = cmd_tx.send(BotCommand::RunScript { content: script }.into());
+
+    let (done_tx, done_rx) = mpsc::channel::<ScriptOutcome>();
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    let stop_clone = stop_flag.clone();
+    let login = bot_config.login.clone();
+    let proxy = bot_config.proxy.clone();
+
+    let bot_thread = std::thread::spawn(move || {
+        let mut bot = match &login {
+            BotLogin::Legacy { username, password } => Bot::new(
+                username, password, proxy, state, cmd_rx, items_dat, 0, None,
+            ),
+            BotLogin::Ltoken { ltoken } => Bot::new_ltoken(
+                ltoken, proxy, state, cmd_rx, items_dat, 0, None,
+            ),
+        }?;
+        bot.script_done_tx = Some(done_tx);
+        bot.run(stop_clone)
+    });
+
+    let outcome = loop {
+        match done_rx.recv_timeout(Duration::from_millis(200)) {
+            Ok(outcome) => break outcome,
+            Err(mpsc::RecvTimeoutError::Timeout) if !bot_thread.is_finished() => continue,
+            Err(_) => {
+                match bot_thread.join() {
+                    Ok(Err(e)) => eprintln!("[CLI] Bot stopped: {e}"),
+                    Ok(Ok(())) => eprintln!("[CLI] Bot stopped before the script finished"),
+                    Err(_) => eprintln!("[CLI] Bot thread panicked"),
+                }
+                return EXIT_BOT_FAILED;
+            }
+        }
+    };
+
+    stop_flag.store(true, Ordering::Relaxed);
+    let _ = bot_thread.join();
+
+    match outcome {
+        ScriptOutcome::Finished => 0,
+        ScriptOutcome::Stopped => {
+            eprintln!("[CLI] Script was stopped");
+            EXIT_SCRIPT_FAILED
+        }
+        ScriptOutcome::Failed(e) => {
+            eprintln!("[CLI] Script failed: {e}");
+            EXIT_SCRIPT_FAILED
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn no_args_serves() {
+        assert!(matches!(parse(&[]), Ok(Command::Serve)));
+        assert!(matches!(parse(&args(&["serve"])), Ok(Command::Serve)));
+    }
+
+    #[test]
+    fn parses_run() {
+        let cmd = parse(&args(&[
+            "run", "--account", "alice:pw", "--proxy", "127.0.0.1:1080:pu:pp", "farm.lua",
+        ]));
+        let Ok(Command::Run(run)) = cmd else { panic!("expected run") };
+        assert!(matches!(run.login, BotLogin::Legacy { ref username, .. } if username == "alice"));
+        assert_eq!(run.proxy.unwrap().username.as_deref(), Some("pu"));
+        assert_eq!(run.script, PathBuf::from("farm.lua"));
+    }
+
+    #[test]
+    fn rejects_bad_run_args() {
+        assert!(parse(&args(&["run", "farm.lua"])).is_err());
+        assert!(parse(&args(&["run", "--account", "alice:pw"])).is_err());
+        assert!(parse(&args(&["run", "--account", "alice:pw", "a.lua", "b.lua"])).is_err());
+        assert!(parse(&args(&["run", "--account", "alice:pw", "--proxy", "host", "a.lua"])).is_err());
+        assert!(parse(&args(&["frobnicate"])).is_err());
+    }
+}
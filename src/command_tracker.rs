@@ -0,0 +1,209 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use utoipa::ToSchema;
+
+use crate::events::{WsEvent, WsTx};
+use crate::history::unix_now;
+
+// ── Command tracking ──────────────────────────────────────────────────────────
+//
+// Every command sent through the API gets an ID and a record that follows it from
+// the queue through the bot thread. The bot reports the outcome through the
+// `CmdTicket` that travels with the command. Records live in memory only.
+
+/// Records kept per bot; older ones are dropped first.
+const KEEP_PER_BOT: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CmdStatus {
+    /// Waiting in the bot's command channel.
+    Queued,
+    /// Picked up by the bot thread.
+    Running,
+    Succeeded,
+    /// The bot could not carry it out; `error` says why.
+    Failed,
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+pub struct CommandRecord {
+    pub id:          String,
+    pub bot_id:      u32,
+    /// The request body as sent to `/bots/{id}/cmd`.
+    pub command:     serde_json::Value,
+    pub status:      CmdStatus,
+    pub error:       Option<String>,
+    /// Unix timestamps.
+    pub queued_at:   u64,
+    pub started_at:  Option<u64>,
+    pub finished_at: Option<u64>,
+}
+
+impl CommandRecord {
+    fn is_pending(&self) -> bool {
+        matches!(self.status, CmdStatus::Queued | CmdStatus::Running)
+    }
+}
+
+#[derive(Clone)]
+pub struct CommandTracker {
+    records: Arc<Mutex<HashMap<u32, VecDeque<CommandRecord>>>>,
+    ws_tx:   WsTx,
+}
+
+impl CommandTracker {
+    pub fn new(ws_tx: WsTx) -> Self {
+        Self { records: Arc::default(), ws_tx }
+    }
+
+    /// Record a new command for `bot_id` and return the ticket that reports its outcome.
+    pub fn queue(&self, bot_id: u32, command: serde_json::Value) -> CmdTicket {
+        let id = uuid::Uuid::new_v4().to_string();
+        let mut records = self.records.lock().unwrap();
+        let list = records.entry(bot_id).or_default();
+        list.push_back(CommandRecord {
+            id: id.clone(),
+            bot_id,
+            command,
+            status:      CmdStatus::Queued,
+            error:       None,
+            queued_at:   unix_now(),
+            started_at:  None,
+            finished_at: None,
+        });
+        while list.len() > KEEP_PER_BOT {
+            list.pop_front();
+        }
+        CmdTicket { id, bot_id, tracker: self.clone(), finished: false }
+    }
+
+    pub fn get(&self, bot_id: u32, id: &str) -> Option<CommandRecord> {
+        let records = self.records.lock().unwrap();
+        records.get(&bot_id)?.iter().find(|r| r.id == id).cloned()
+    }
+
+    /// Recent commands of a bot, newest first.
+    pub fn list(&self, bot_id: u32) -> Vec<CommandRecord> {
+        let records = self.records.lock().unwrap();
+        records.get(&bot_id)
+            .map(|list| list.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Fail every unfinished command of a bot that was removed. Its thread may take a
+    /// while to exit, so this doesn't wait for the tickets to be dropped.
+    pub fn abandon(&self, bot_id: u32) {
+        let pending: Vec<String> = {
+            let records = self.records.lock().unwrap();
+            records.get(&bot_id)
+                .map(|list| list.iter().filter(|r| r.is_pending()).map(|r| r.id.clone()).collect())
+                .unwrap_or_default()
+        };
+        for id in pending {
+            self.finish(bot_id, &id, Err("bot was removed".into()));
+        }
+    }
+
+    fn start(&self, bot_id: u32, id: &str) {
+        let mut records = self.records.lock().unwrap();
+        if let Some(record) = records.get_mut(&bot_id).and_then(|l| l.iter_mut().find(|r| r.id == id)) {
+            record.status = CmdStatus::Running;
+            record.started_at = Some(unix_now());
+        }
+    }
+
+    fn finish(&self, bot_id: u32, id: &str, result: Result<(), String>) {
+        {
+            let mut records = self.records.lock().unwrap();
+            let Some(record) = records.get_mut(&bot_id).and_then(|l| l.iter_mut().find(|r| r.id == id)) else {
+                return;
+            };
+            // Already failed by `abandon`.
+            if !record.is_pending() {
+                return;
+            }
+            record.status = if result.is_ok() { CmdStatus::Succeeded } else { CmdStatus::Failed };
+            record.error = result.as_ref().err().cloned();
+            record.finished_at = Some(unix_now());
+        }
+        let _ = self.ws_tx.send(WsEvent::CommandFinished {
+            bot_id,
+            cmd_id: id.to_string(),
+            ok:     result.is_ok(),
+            error:  result.err(),
+        });
+    }
+}
+
+// ── Tickets ───────────────────────────────────────────────────────────────────
+
+/// Travels with a command to the bot thread. A ticket dropped without `finish`
+/// (the bot stopped with the command still queued) marks the command failed.
+pub struct CmdTicket {
+    id:       String,
+    bot_id:   u32,
+    tracker:  CommandTracker,
+    finished: bool,
+}
+
+impl CmdTicket {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn start(&self) {
+        self.tracker.start(self.bot_id, &self.id);
+    }
+
+    pub fn finish(mut self, result: Result<(), String>) {
+        self.finished = true;
+        self.tracker.finish(self.bot_id, &self.id, result);
+    }
+}
+
+impl Drop for CmdTicket {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.tracker.finish(self.bot_id, &self.id, Err("bot stopped before running the command".into()));
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lifecycle_and_dropped_tickets() {
+        let (ws_tx, mut rx) = tokio::sync::broadcast::channel(16);
+        let tracker = CommandTracker::new(ws_tx);
+
+        let ticket = tracker.queue(1, serde_json::json!({ "type": "respawn" }));
+        let id = ticket.id().to_string();
+        assert_eq!(tracker.get(1, &id).unwrap().status, CmdStatus::Queued);
+        ticket.start();
+        assert_eq!(tracker.get(1, &id).unwrap().status, CmdStatus::Running);
+        ticket.finish(Err("not in a world".into()));
+        let record = tracker.get(1, &id).unwrap();
+        assert_eq!(record.status, CmdStatus::Failed);
+        assert_eq!(record.error.as_deref(), Some("not in a world"));
+        assert!(matches!(rx.try_recv(), Ok(WsEvent::CommandFinished { ok: false, .. })));
+
+        let abandoned = tracker.queue(1, serde_json::Value::Null);
+        tracker.abandon(1);
+        abandoned.finish(Ok(()));
+        let record = tracker.list(1)[0].clone();
+        assert_eq!((record.status, record.error.as_deref()), (CmdStatus::Failed, Some("bot was removed")));
+
+        let dropped = tracker.queue(1, serde_json::Value::Null);
+        let dropped_id = dropped.id().to_string();
+        drop(dropped);
+        assert_eq!(tracker.get(1, &dropped_id).unwrap().status, CmdStatus::Failed);
+        assert_eq!(tracker.list(1)[0].id, dropped_id);
+        assert!(tracker.get(2, &id).is_none());
+    }
+}
@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use std::path::Path;
+
+use crate::bot_state::{BotCommand, BotDelays};
+use crate::scripts::ScriptLibrary;
+
+// ── API command payloads ──────────────────────────────────────────────────────
+//
+// The JSON form of a `BotCommand`, shared by `POST /bots/{id}/cmd` and scheduled jobs.
+
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CmdRequest {
+    Move { x: i32, y: i32 },
+    WalkTo { x: u32, y: u32 },
+    RunScript { content: String },
+    /// Run a script from the library; the latest version when `version` is omitted.
+    RunStoredScript { name: String, version: Option<u32> },
+    StopScript,
+    Say { text: String },
+    /// Place `item_id` at an offset from the bot's tile (at most 4 tiles away).
+    Place { x: i32, y: i32, item_id: u32 },
+    /// Punch the tile at an offset from the bot's tile (at most 4 tiles away).
+    Hit { x: i32, y: i32 },
+    /// Wrench the tile at absolute tile coordinates.
+    Wrench { x: i32, y: i32 },
+    WrenchPlayer { net_id: u32 },
+    /// Activate the tile at absolute tile coordinates (doors, vending machines, …).
+    Active { x: i32, y: i32 },
+    /// Enter the door under the bot, or answer a door password prompt with `pass`.
+    Enter {
+        #[serde(default)]
+        pass: Option<String>,
+    },
+    /// Buy a store pack by its pack ID, e.g. `world_lock`.
+    Buy { item: String },
+    /// Pick up a dropped object if it is within `range` tiles.
+    CollectObject {
+        uid: u32,
+        #[serde(default = "default_collect_range")]
+        range: f32,
+    },
+    SetDirection { facing_left: bool },
+    LeaveWorld,
+    Respawn,
+    FindPath { x: u32, y: u32 },
+    Wear { item_id: u32 },
+    Unwear { item_id: u32 },
+    Drop { item_id: u32, count: u32 },
+    Trash { item_id: u32, count: u32 },
+    SetDelays(BotDelays),
+    SetAutoCollect { enabled: bool },
+    SetCollectConfig {
+        radius_tiles: u8,
+        #[serde(default)]
+        blacklist: Vec<u16>,
+    },
+    SetAutoReconnect { enabled: bool },
+    Disconnect,
+    Reconnect,
+    AcceptAccess,
+    Warp {
+        name: String,
+        #[serde(default)]
+        id: String,
+    },
+}
+
+fn default_collect_range() -> f32 {
+    5.0
+}
+
+/// Furthest tile offset `place` and `hit` can reach. Shared with the bot, which
+/// refuses anything beyond, so validation and the bot can't disagree.
+pub(crate) const REACH_TILES: i32 = 4;
+
+/// Longest chat line the game accepts.
+const MAX_SAY_LEN: usize = 120;
+
+impl CmdRequest {
+    /// Reject values the bot would silently ignore or that would break the text protocol.
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            CmdRequest::Say { text } => {
+                check_line("text", text)?;
+                if text.chars().count() > MAX_SAY_LEN {
+                    return Err(format!("text must be at most {MAX_SAY_LEN} characters"));
+                }
+            }
+            CmdRequest::Place { x, y, .. } | CmdRequest::Hit { x, y }
+                if x.abs() > REACH_TILES || y.abs() > REACH_TILES =>
+            {
+                return Err(format!("x and y must be offsets within {REACH_TILES} tiles"));
+            }
+            CmdRequest::Place { item_id, .. } if *item_id == 0 || *item_id > u16::MAX as u32 => {
+                return Err(format!("invalid item_id {item_id}"));
+            }
+            CmdRequest::Wrench { x, y } | CmdRequest::Active { x, y } if *x < 0 || *y < 0 => {
+                return Err("x and y must not be negative".into());
+            }
+            CmdRequest::Enter { pass: Some(pass) } => check_line("pass", pass)?,
+            CmdRequest::Buy { item }
+                if item.is_empty() || !item.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+            {
+                return Err(format!("invalid pack id '{item}'"));
+            }
+            CmdRequest::CollectObject { range, .. } if !range.is_finite() || *range <= 0.0 => {
+                return Err("range must be a positive number of tiles".into());
+            }
+            CmdRequest::Drop { count: 0, .. } | CmdRequest::Trash { count: 0, .. } => {
+                return Err("count must be at least 1".into());
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Convert to the bot-thread command. `scripts_dir` holds the script library that
+    /// `run_stored_script` reads from.
+    pub fn into_command(self, scripts_dir: &Path) -> Result<BotCommand, String> {
+        self.validate()?;
+        Ok(match self {
+            CmdRequest::Move { x, y }   => BotCommand::Move { x, y },
+            CmdRequest::WalkTo { x, y } => BotCommand::WalkTo { x, y },
+            CmdRequest::RunScript { content }   => BotCommand::RunScript { content },
+            CmdRequest::RunStoredScript { name, version } => BotCommand::RunScript {
+                content: ScriptLibrary::new(scripts_dir).read(&name, version).map_err(|e| e.to_string())?.1,
+            },
+            CmdRequest::StopScript              => BotCommand::StopScript,
+            CmdRequest::Say { text }            => BotCommand::Say { text },
+            CmdRequest::Place { x, y, item_id } => BotCommand::Place { x, y, item: item_id },
+            CmdRequest::Hit { x, y }            => BotCommand::Hit { x, y },
+            CmdRequest::Wrench { x, y }         => BotCommand::Wrench { x, y },
+            CmdRequest::WrenchPlayer { net_id } => BotCommand::WrenchPlayer { net_id },
+            CmdRequest::Active { x, y }         => BotCommand::Active { x, y },
+            CmdRequest::Enter { pass }          => BotCommand::Enter { pass },
+            CmdRequest::Buy { item }            => BotCommand::Buy { item },
+            CmdRequest::CollectObject { uid, range } => BotCommand::CollectObject { uid, range },
+            CmdRequest::SetDirection { facing_left } => BotCommand::SetDirection { facing_left },
+            CmdRequest::LeaveWorld              => BotCommand::LeaveWorld,
+            CmdRequest::Respawn                 => BotCommand::Respawn,
+            CmdRequest::FindPath { x, y }       => BotCommand::FindPath { x, y },
+            CmdRequest::Wear { item_id }        => BotCommand::Wear { item_id },
+            CmdRequest::Unwear { item_id }      => BotCommand::Unwear { item_id },
+            CmdRequest::Drop { item_id, count } => BotCommand::Drop { item_id, count },
+            CmdRequest::Trash { item_id, count } => BotCommand::Trash { item_id, count },
+            CmdRequest::SetDelays(d) => BotCommand::SetDelays(d),
+            CmdRequest::SetAutoCollect { enabled } => BotCommand::SetAutoCollect { enabled },
+            CmdRequest::SetCollectConfig {
+                radius_tiles,
+                blacklist,
+            } => BotCommand::SetCollectConfig {
+                radius_tiles,
+                blacklist,
+            },
+            CmdRequest::SetAutoReconnect { enabled } => BotCommand::SetAutoReconnect { enabled },
+            CmdRequest::Disconnect => BotCommand::Disconnect,
+            CmdRequest::Reconnect => BotCommand::Reconnect,
+            CmdRequest::AcceptAccess => BotCommand::AcceptAccess,
+            CmdRequest::Warp { name, id } => BotCommand::Warp { name, id },
+        })
+    }
+}
+
+/// Whether `path` is one of the routes that send a `CmdRequest`: `/bots/{id}/cmd`,
+/// `/bots/cmd` and `/groups/{name}/cmd`.
+pub fn is_cmd_path(path: &str) -> bool {
+    if path == "/bots/cmd" {
+        return true;
+    }
+    if let Some(id) = path.strip_prefix("/bots/").and_then(|rest| rest.strip_suffix("/cmd")) {
+        return id.parse::<u32>().is_ok();
+    }
+    path.strip_prefix("/groups/")
+        .and_then(|rest| rest.strip_suffix("/cmd"))
+        .is_some_and(|name| !name.is_empty() && !name.contains('/'))
+}
+
+/// Text sent inside a game packet; a line break would start a new packet field.
+fn check_line(field: &str, value: &str) -> Result<(), String> {
+    if value.is_empty() {
+        return Err(format!("{field} must not be empty"));
+    }
+    if value.contains(['\n', '\r']) {
+        return Err(format!("{field} must not contain line breaks"));
+    }
+    Ok(())
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Result<BotCommand, String> {
+        serde_json::from_str::<CmdRequest>(json).unwrap().into_command(Path::new("."))
+    }
+
+    #[test]
+    fn converts_and_validates() {
+        assert!(matches!(
+            parse(r#"{"type":"place","x":1,"y":-1,"item_id":2}"#),
+            Ok(BotCommand::Place { x: 1, y: -1, item: 2 })
+        ));
+        assert!(matches!(
+            parse(r#"{"type":"collect_object","uid":7}"#),
+            Ok(BotCommand::CollectObject { uid: 7, range }) if range == 5.0
+        ));
+        assert!(matches!(parse(r#"{"type":"enter"}"#), Ok(BotCommand::Enter { pass: None })));
+        assert!(matches!(parse(r#"{"type":"leave_world"}"#), Ok(BotCommand::LeaveWorld)));
+
+        assert!(parse(r#"{"type":"hit","x":5,"y":0}"#).is_err());
+        assert!(parse(r#"{"type":"say","text":"hi\naction|quit"}"#).is_err());
+        assert!(parse(r#"{"type":"say","text":""}"#).is_err());
+        assert!(parse(r#"{"type":"buy","item":"world_lock\n"}"#).is_err());
+        assert!(parse(r#"{"type":"wrench","x":-1,"y":3}"#).is_err());
+        assert!(parse(r#"{"type":"drop","item_id":2,"count":0}"#).is_err());
+    }
+}
@@ -0,0 +1,274 @@
+use serde::Deserialize;
+use std::path::PathBuf;
+
+use crate::bot_state::BotDelays;
+
+// ── Server configuration (mori.toml + MORI_* env overrides) ──────────────────
+
+/// Settings applied to newly spawned bots unless the spawn request overrides them.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct BotDefaults {
+    pub delays:               BotDelays,
+    pub auto_collect:         bool,
+    pub collect_radius_tiles: u8,
+    pub auto_reconnect:       bool,
+    pub restart_on_crash:     bool,
+    pub autostart:            bool,
+    pub use_proxy_pool:       bool,
+}
+
+impl Default for BotDefaults {
+    fn default() -> Self {
+        Self {
+            delays:               BotDelays::default(),
+            auto_collect:         true,
+            collect_radius_tiles: 3,
+            auto_reconnect:       true,
+            restart_on_crash:     true,
+            autostart:            true,
+            use_proxy_pool:       false,
+        }
+    }
+}
+
+/// Periodic per-bot samples kept under `<data_dir>/history`.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Seconds between samples (at least 10).
+    pub interval_secs:  u64,
+    /// Samples older than this are deleted; 0 keeps them forever.
+    pub retention_days: u64,
+    /// Item IDs whose inventory counts are recorded.
+    pub items:          Vec<u16>,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs:  60,
+            retention_days: 30,
+            // World Lock, Diamond Lock, Blue Gem Lock.
+            items:          vec![242, 1796, 7188],
+        }
+    }
+}
+
+/// Per-bot console log files kept under `<data_dir>/console`.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct ConsoleLogConfig {
+    /// A log file is rotated once it would grow past this size.
+    pub max_file_kb: u64,
+    /// Rotated files kept per bot besides the current one; 0 keeps none.
+    pub keep_files:  usize,
+}
+
+impl Default for ConsoleLogConfig {
+    fn default() -> Self {
+        Self {
+            max_file_kb: 1024,
+            keep_files:  4,
+        }
+    }
+}
+
+/// Lifetime of dashboard sessions, which are kept in `<data_dir>/sessions.json`.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    /// A session unused for this long is ended; 0 disables the idle timeout.
+    pub idle_timeout_secs: u64,
+    /// A session is ended this long after login regardless of use; 0 disables it.
+    pub max_age_secs:      u64,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout_secs: 7 * 86_400,
+            max_age_secs:      30 * 86_400,
+        }
+    }
+}
+
+/// HTTPS for the dashboard, API and WebSocket.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TlsConfig {
+    pub enabled:   bool,
+    /// PEM certificate chain. With `key_path` unset as well, a self-signed
+    /// certificate is generated under `<data_dir>/tls` instead.
+    pub cert_path: Option<PathBuf>,
+    /// PEM private key for `cert_path`.
+    pub key_path:  Option<PathBuf>,
+    /// Extra DNS names or IPs for the self-signed certificate besides localhost.
+    pub hostnames: Vec<String>,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub listen_host:   String,
+    pub listen_port:   u16,
+    /// Directory for users.json, sessions.json, bots.json, vault.json, …
+    pub data_dir:      PathBuf,
+    /// Built web dashboard.
+    pub dist_dir:      PathBuf,
+    pub items_dat:     PathBuf,
+    /// Console lines kept per bot.
+    pub console_lines: usize,
+    /// Bearer token that may read `/metrics` without logging in.
+    pub metrics_token: Option<String>,
+    pub bot_defaults:  BotDefaults,
+    pub history:       HistoryConfig,
+    pub console_log:   ConsoleLogConfig,
+    pub sessions:      SessionConfig,
+    pub tls:           TlsConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            listen_host:   "0.0.0.0".into(),
+            listen_port:   3000,
+            data_dir:      "data".into(),
+            dist_dir:      "dist".into(),
+            items_dat:     "items.dat".into(),
+            console_lines: 100,
+            metrics_token: None,
+            bot_defaults:  BotDefaults::default(),
+            history:       HistoryConfig::default(),
+            console_log:   ConsoleLogConfig::default(),
+            sessions:      SessionConfig::default(),
+            tls:           TlsConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Read `mori.toml` (or the file named by `MORI_CONFIG`) from the working
+    /// directory, then apply `MORI_*` environment overrides. A missing file
+    /// yields the defaults. A file that can't be read or parsed, or an invalid
+    /// override, is an error: falling back to the defaults would silently move
+    /// the data directory and the bind address.
+    pub fn load() -> anyhow::Result<Self> {
+        let path = std::env::var("MORI_CONFIG").unwrap_or_else(|_| "mori.toml".into());
+        let mut config = match std::fs::read_to_string(&path) {
+            Ok(data) => {
+                let config = toml::from_str(&data).map_err(|e| anyhow::anyhow!("cannot parse {path}: {e}"))?;
+                println!("[Config] Loaded {path}");
+                config
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Config::default(),
+            Err(e) => anyhow::bail!("cannot read {path}: {e}"),
+        };
+        config.apply_env(|key| std::env::var(key).ok())?;
+
+        let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        let tls_paths = [&mut config.tls.cert_path, &mut config.tls.key_path]
+            .into_iter()
+            .filter_map(Option::as_mut);
+        for path in [&mut config.data_dir, &mut config.dist_dir, &mut config.items_dat]
+            .into_iter()
+            .chain(tls_paths)
+        {
+            if path.is_relative() {
+                *path = cwd.join(&*path);
+            }
+        }
+        Ok(config)
+    }
+
+    /// `<data_dir>/scripts`, the root passed to `ScriptLibrary`, which keeps each
+    /// stored script under `library/<name>/`.
+    pub fn scripts_dir(&self) -> PathBuf {
+        self.data_dir.join("scripts")
+    }
+
+    fn apply_env(&mut self, get: impl Fn(&str) -> Option<String>) -> anyhow::Result<()> {
+        fn number<T: std::str::FromStr>(key: &str, v: &str) -> anyhow::Result<T> {
+            v.parse().map_err(|_| anyhow::anyhow!("{key}={v:?} is not a valid number"))
+        }
+        if let Some(v) = get("MORI_LISTEN_HOST") {
+            self.listen_host = v;
+        }
+        if let Some(v) = get("MORI_LISTEN_PORT") {
+            self.listen_port = number("MORI_LISTEN_PORT", &v)?;
+        }
+        if let Some(v) = get("MORI_DATA_DIR") {
+            self.data_dir = v.into();
+        }
+        if let Some(v) = get("MORI_DIST_DIR") {
+            self.dist_dir = v.into();
+        }
+        if let Some(v) = get("MORI_ITEMS_DAT") {
+            self.items_dat = v.into();
+        }
+        if let Some(v) = get("MORI_CONSOLE_LINES") {
+            self.console_lines = number("MORI_CONSOLE_LINES", &v)?;
+        }
+        if let Some(v) = get("MORI_METRICS_TOKEN") {
+            self.metrics_token = Some(v);
+        }
+        if let Some(v) = get("MORI_TLS") {
+            self.tls.enabled = match v.as_str() {
+                "1" | "true" | "yes" | "on"  => true,
+                "0" | "false" | "no" | "off" => false,
+                _ => anyhow::bail!("MORI_TLS={v:?} is not one of 1/true/yes/on or 0/false/no/off"),
+            };
+        }
+        if let Some(v) = get("MORI_TLS_CERT") {
+            self.tls.cert_path = Some(v.into());
+        }
+        if let Some(v) = get("MORI_TLS_KEY") {
+            self.tls.key_path = Some(v.into());
+        }
+        Ok(())
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_file_keeps_defaults() {
+        let config: Config = toml::from_str(
+            "listen_port = 8080\n[bot_defaults]\nautostart = false\n[bot_defaults.delays]\nwalk_ms = 250\n",
+        ).unwrap();
+        assert_eq!(config.listen_port, 8080);
+        assert_eq!(config.listen_host, "0.0.0.0");
+        assert_eq!(config.console_lines, 100);
+        assert!(!config.bot_defaults.autostart);
+        assert!(config.bot_defaults.auto_collect);
+        assert_eq!(config.bot_defaults.delays.walk_ms, 250);
+        assert_eq!(config.bot_defaults.delays.place_ms, 500);
+    }
+
+    #[test]
+    fn env_overrides_file() {
+        let mut config = Config::default();
+        config.apply_env(|key| match key {
+            "MORI_LISTEN_PORT" => Some("4000".into()),
+            "MORI_DATA_DIR"    => Some("/var/lib/mori".into()),
+            "MORI_TLS"         => Some("true".into()),
+            _ => None,
+        }).unwrap();
+        assert!(config.tls.enabled);
+        assert_eq!(config.listen_port, 4000);
+        assert_eq!(config.data_dir, PathBuf::from("/var/lib/mori"));
+        assert_eq!(config.console_lines, 100);
+    }
+
+    #[test]
+    fn invalid_env_values_are_errors() {
+        for (key, value) in [("MORI_LISTEN_PORT", "70000"), ("MORI_CONSOLE_LINES", "lots"), ("MORI_TLS", "maybe")] {
+            let err = Config::default().apply_env(|k| (k == key).then(|| value.to_string())).unwrap_err();
+            assert!(err.to_string().contains(key), "{err}");
+        }
+    }
+}
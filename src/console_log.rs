@@ -0,0 +1,319 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use utoipa::ToSchema;
+
+use crate::config::ConsoleLogConfig;
+
+// ── Lines ─────────────────────────────────────────────────────────────────────
+//
+// Every console line of a bot is also appended to `<data_dir>/console/<bot_id>.jsonl`.
+// When the file grows past `console_log.max_file_kb` it is renamed to
+// `<bot_id>.1.jsonl` (older files move up to `.2`, `.3`, …) and a new one is started;
+// at most `console_log.keep_files` rotated files are kept. Files outlive their bot
+// until they are purged, and their IDs are not handed to new bots.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsoleCategory {
+    /// Login, connection, packets and proxies.
+    Network,
+    /// World, tile, player and inventory updates.
+    World,
+    /// Lua script errors.
+    Script,
+    /// Console messages from the game server.
+    Chat,
+}
+
+impl ConsoleCategory {
+    pub fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "network" => ConsoleCategory::Network,
+            "world"   => ConsoleCategory::World,
+            "script"  => ConsoleCategory::Script,
+            "chat"    => ConsoleCategory::Chat,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+pub struct ConsoleLine {
+    /// Unix time in milliseconds; strictly increasing per bot, so it also pages.
+    pub ts:       u64,
+    pub category: ConsoleCategory,
+    pub message:  String,
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+/// Filters for `ConsoleLog::read`.
+pub struct ConsoleQuery {
+    /// Only lines after this timestamp, oldest first. Without it the newest lines are returned.
+    pub since:      Option<u64>,
+    /// Only lines before this timestamp.
+    pub before:     Option<u64>,
+    /// Case-insensitive text the message must contain.
+    pub q:          Option<String>,
+    /// Empty means every category.
+    pub categories: Vec<ConsoleCategory>,
+    pub limit:      usize,
+}
+
+impl ConsoleQuery {
+    /// `q` is `self.q` lowercased.
+    fn matches(&self, line: &ConsoleLine, q: Option<&str>) -> bool {
+        self.since.is_none_or(|t| line.ts > t)
+            && self.before.is_none_or(|t| line.ts < t)
+            && (self.categories.is_empty() || self.categories.contains(&line.category))
+            && q.is_none_or(|q| line.message.to_lowercase().contains(q))
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ConsolePage {
+    /// Oldest first.
+    pub lines: Vec<ConsoleLine>,
+    /// More lines match beyond this page: later ones when `since` was given, earlier ones otherwise.
+    pub more:  bool,
+}
+
+// ── Store ─────────────────────────────────────────────────────────────────────
+
+#[derive(Clone)]
+pub struct ConsoleLog {
+    dir:        PathBuf,
+    max_bytes:  u64,
+    keep_files: usize,
+}
+
+impl ConsoleLog {
+    pub fn new(data_dir: &Path, config: &ConsoleLogConfig) -> Self {
+        Self {
+            dir:        data_dir.join("console"),
+            max_bytes:  config.max_file_kb.max(1) * 1024,
+            keep_files: config.keep_files,
+        }
+    }
+
+    /// `<bot_id>.jsonl` for `part` 0, `<bot_id>.<part>.jsonl` for rotated files.
+    fn path(&self, bot_id: u32, part: usize) -> PathBuf {
+        match part {
+            0 => self.dir.join(format!("{bot_id}.jsonl")),
+            n => self.dir.join(format!("{bot_id}.{n}.jsonl")),
+        }
+    }
+
+    pub fn writer(&self, bot_id: u32) -> ConsoleWriter {
+        ConsoleWriter { log: self.clone(), bot_id, inner: Mutex::default() }
+    }
+
+    /// Shift the rotated files up by one and move the current file to `.1`.
+    fn rotate(&self, bot_id: u32) -> std::io::Result<()> {
+        if self.keep_files == 0 {
+            return std::fs::remove_file(self.path(bot_id, 0));
+        }
+        let _ = std::fs::remove_file(self.path(bot_id, self.keep_files));
+        for part in (1..self.keep_files).rev() {
+            let from = self.path(bot_id, part);
+            if from.exists() {
+                std::fs::rename(from, self.path(bot_id, part + 1))?;
+            }
+        }
+        std::fs::rename(self.path(bot_id, 0), self.path(bot_id, 1))
+    }
+
+    /// Lines of `bot_id` matching `query`, from the rotated files and the current one.
+    pub fn read(&self, bot_id: u32, query: &ConsoleQuery) -> ConsolePage {
+        let q = query.q.as_deref().map(str::to_lowercase);
+        let limit = query.limit.max(1);
+        let mut lines: VecDeque<ConsoleLine> = VecDeque::new();
+        let mut more = false;
+        'files: for part in (0..=self.keep_files).rev() {
+            let Ok(file) = File::open(self.path(bot_id, part)) else {
+                continue;
+            };
+            let matching = BufReader::new(file)
+                .lines()
+                .map_while(Result::ok)
+                .filter_map(|line| serde_json::from_str::<ConsoleLine>(&line).ok())
+                .filter(|line| query.matches(line, q.as_deref()));
+            for line in matching {
+                if lines.len() == limit {
+                    more = true;
+                    if query.since.is_some() {
+                        break 'files;
+                    }
+                    lines.pop_front();
+                }
+                lines.push_back(line);
+            }
+        }
+        ConsolePage { lines: lines.into(), more }
+    }
+
+    /// IDs with at least one log file on disk.
+    pub fn bot_ids(&self) -> Vec<u32> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut ids: Vec<u32> = entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let name = entry.file_name();
+                name.to_str()?.strip_suffix(".jsonl")?.split('.').next()?.parse().ok()
+            })
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    pub fn exists(&self, bot_id: u32) -> bool {
+        (0..=self.keep_files).any(|part| self.path(bot_id, part).exists())
+    }
+
+    /// Delete every log file of `bot_id`. Returns false when there were none.
+    pub fn remove(&self, bot_id: u32) -> bool {
+        let mut removed = false;
+        for part in 0..=self.keep_files {
+            removed |= std::fs::remove_file(self.path(bot_id, part)).is_ok();
+        }
+        removed
+    }
+}
+
+// ── Writer ────────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+struct WriterState {
+    file:    Option<File>,
+    size:    u64,
+    last_ts: u64,
+}
+
+/// Appends one bot's console lines; held by its `BotState`.
+pub struct ConsoleWriter {
+    log:    ConsoleLog,
+    bot_id: u32,
+    inner:  Mutex<WriterState>,
+}
+
+impl ConsoleWriter {
+    pub fn append(&self, category: ConsoleCategory, message: &str) {
+        let mut st = self.inner.lock().unwrap();
+        let ts = unix_millis().max(st.last_ts + 1);
+        st.last_ts = ts;
+        let line = ConsoleLine { ts, category, message: message.to_string() };
+        if let Err(e) = self.write(&mut st, &line) {
+            println!("[Console] Failed to write log for bot {}: {e}", self.bot_id);
+        }
+    }
+
+    fn open(&self) -> std::io::Result<File> {
+        std::fs::create_dir_all(&self.log.dir)?;
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.log.path(self.bot_id, 0))
+    }
+
+    /// On error the file is left closed and reopened by the next line.
+    fn write(&self, st: &mut WriterState, line: &ConsoleLine) -> anyhow::Result<()> {
+        let line = serde_json::to_string(line)?;
+        let len = line.len() as u64 + 1;
+        let (mut file, mut size) = match st.file.take() {
+            Some(file) => (file, st.size),
+            None => {
+                let file = self.open()?;
+                let size = file.metadata()?.len();
+                (file, size)
+            }
+        };
+        if size > 0 && size + len > self.log.max_bytes {
+            drop(file);
+            self.log.rotate(self.bot_id)?;
+            (file, size) = (self.open()?, 0);
+        }
+        writeln!(file, "{line}")?;
+        st.file = Some(file);
+        st.size = size + len;
+        Ok(())
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    fn query(since: Option<u64>, q: Option<&str>, categories: Vec<ConsoleCategory>, limit: usize) -> ConsoleQuery {
+        ConsoleQuery { since, before: None, q: q.map(str::to_string), categories, limit }
+    }
+
+    /// 60 lines for bot 3, every third one `chat`, rotated over `.1` and `.2`.
+    fn sixty_lines(dir: &TestDir) -> ConsoleLog {
+        let log = ConsoleLog::new(dir, &ConsoleLogConfig { max_file_kb: 1, keep_files: 2 });
+        let writer = log.writer(3);
+        for i in 0..60 {
+            let category = if i % 3 == 0 { ConsoleCategory::Chat } else { ConsoleCategory::Network };
+            writer.append(category, &format!("line {i:02} padding padding padding"));
+        }
+        log
+    }
+
+    #[test]
+    fn rotates_and_keeps_at_most_keep_files() {
+        let dir = TestDir::new("console");
+        let log = sixty_lines(&dir);
+        assert!(log.path(3, 1).exists() && log.path(3, 2).exists());
+        assert!(!log.path(3, 3).exists());
+    }
+
+    #[test]
+    fn pages_across_files() {
+        let dir = TestDir::new("console");
+        let log = sixty_lines(&dir);
+        // The oldest lines were rotated out; the newest ones page backwards.
+        let newest = log.read(3, &query(None, None, vec![], 5));
+        assert!(newest.more);
+        let text: Vec<&str> = newest.lines.iter().map(|l| &l.message[..7]).collect();
+        assert_eq!(text, ["line 55", "line 56", "line 57", "line 58", "line 59"]);
+
+        let all = log.read(3, &query(None, None, vec![], 1000));
+        assert!(!all.more && all.lines.len() < 60);
+        assert!(all.lines.windows(2).all(|w| w[0].ts < w[1].ts));
+
+        let after = log.read(3, &query(Some(all.lines[0].ts), None, vec![], 2));
+        assert!(after.more);
+        assert_eq!(after.lines[0].ts, all.lines[1].ts);
+    }
+
+    #[test]
+    fn filters_by_text_and_category() {
+        let dir = TestDir::new("console");
+        let log = sixty_lines(&dir);
+        let chat = log.read(3, &query(None, Some("LINE 5"), vec![ConsoleCategory::Chat], 100));
+        let text: Vec<&str> = chat.lines.iter().map(|l| &l.message[..7]).collect();
+        assert_eq!(text, ["line 51", "line 54", "line 57"]);
+    }
+
+    #[test]
+    fn logs_are_kept_until_removed() {
+        let dir = TestDir::new("console");
+        let log = sixty_lines(&dir);
+        assert_eq!(log.bot_ids(), [3]);
+        assert!(log.remove(3));
+        assert!(!log.remove(3) && !log.exists(3));
+        assert!(log.read(3, &query(None, None, vec![], 10)).lines.is_empty());
+    }
+}
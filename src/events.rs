@@ -1,7 +1,12 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use std::collections::BTreeSet;
+
+use crate::commands::CmdRequest;
+use crate::console_log::ConsoleCategory;
 use crate::world::TileType;
 
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Clone, Debug, ToSchema)]
 pub struct WsTile {
     pub fg:        u16,
     pub bg:        u16,
@@ -9,7 +14,7 @@ pub struct WsTile {
     pub tile_type: TileType,
 }
 
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Clone, Debug, ToSchema)]
 pub struct WsObject {
     pub uid:     u32,
     pub item_id: u16,
@@ -18,7 +23,7 @@ pub struct WsObject {
     pub count:   u8,
 }
 
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Clone, Debug, ToSchema)]
 pub struct WsInvItem {
     pub item_id:     u16,
     pub amount:      u8,
@@ -26,9 +31,30 @@ pub struct WsInvItem {
     pub action_type: u8,
 }
 
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Clone, Copy, Debug, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotReason {
+    /// First message on every new socket.
+    Connect,
+    /// The socket fell behind the broadcast channel and events were dropped.
+    Lagged,
+}
+
+#[derive(Serialize, Clone, Debug, ToSchema)]
 #[serde(tag = "event", content = "data")]
 pub enum WsEvent {
+    /// Full state of every bot; clients replace what they hold rather than merge.
+    /// Each entry is the `/bots/{id}/state` body plus `bot_id`. Only sent to one socket.
+    Snapshot { reason: SnapshotReason, bots: Vec<serde_json::Value> },
+    /// Reply to a client message, echoing its `id`. `cmd_id` is set for queued `cmd` messages.
+    /// Only sent to that socket.
+    Ack {
+        id:     Option<String>,
+        ok:     bool,
+        error:  Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cmd_id: Option<String>,
+    },
     /// A new bot was spawned.
     BotAdded { bot_id: u32, username: String },
     /// A bot was stopped/removed.
@@ -58,15 +84,267 @@ pub enum WsEvent {
     /// Bot inventory updated.
     InventoryUpdate { bot_id: u32, gems: i32, inventory_size: u32, items: Vec<WsInvItem> },
     /// A new console message arrived.
-    Console { bot_id: u32, message: String },
+    Console { bot_id: u32, category: ConsoleCategory, message: String },
     /// Account info extracted from the Track packet (sent once per login).
     BotTrackInfo { bot_id: u32, level: u32, grow_id: u64, install_date: u64, global_playtime: u64, awesomeness: u32 },
     /// GrowID resolved from SetHasGrowID (useful when logged in via ltoken).
     BotUsername { bot_id: u32, username: String },
     /// Bot auto-collect toggled.
     BotAutoCollect { bot_id: u32, enabled: bool },
+    /// A bot thread exited without being stopped from the API. `restart_in_secs` is set
+    /// when the supervisor will respawn it.
+    BotExited { bot_id: u32, status: String, error: Option<String>, restart_in_secs: Option<u64> },
+    /// A scheduled job sent its command to the bot.
+    JobFired { job_id: String, bot_id: u32 },
+    /// A scheduled job could not run (bot not running, script file missing, …).
+    JobFailed { job_id: String, bot_id: u32, error: String },
+    /// A tracked command finished on the bot thread (see `GET /bots/{id}/commands/{cmd_id}`).
+    CommandFinished { bot_id: u32, cmd_id: String, ok: bool, error: Option<String> },
     /// Bot delays updated.
     BotDelays { bot_id: u32, place_ms: u64, walk_ms: u64, twofa_secs: u64, server_overload_secs: u64, too_many_logins_secs: u64, maintenance_secs: u64 },
 }
 
+impl WsEvent {
+    /// Variant name, as sent in `event`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            WsEvent::Snapshot { .. }        => "Snapshot",
+            WsEvent::Ack { .. }             => "Ack",
+            WsEvent::BotAdded { .. }        => "BotAdded",
+            WsEvent::BotRemoved { .. }      => "BotRemoved",
+            WsEvent::BotStatus { .. }       => "BotStatus",
+            WsEvent::BotWorld { .. }        => "BotWorld",
+            WsEvent::BotMove { .. }         => "BotMove",
+            WsEvent::BotGems { .. }         => "BotGems",
+            WsEvent::BotPing { .. }         => "BotPing",
+            WsEvent::PlayerSpawn { .. }     => "PlayerSpawn",
+            WsEvent::PlayerMove { .. }      => "PlayerMove",
+            WsEvent::PlayerLeave { .. }     => "PlayerLeave",
+            WsEvent::WorldLoaded { .. }     => "WorldLoaded",
+            WsEvent::TileUpdate { .. }      => "TileUpdate",
+            WsEvent::ObjectsUpdate { .. }   => "ObjectsUpdate",
+            WsEvent::InventoryUpdate { .. } => "InventoryUpdate",
+            WsEvent::Console { .. }         => "Console",
+            WsEvent::BotTrackInfo { .. }    => "BotTrackInfo",
+            WsEvent::BotUsername { .. }     => "BotUsername",
+            WsEvent::BotAutoCollect { .. }  => "BotAutoCollect",
+            WsEvent::BotExited { .. }       => "BotExited",
+            WsEvent::JobFired { .. }        => "JobFired",
+            WsEvent::JobFailed { .. }       => "JobFailed",
+            WsEvent::CommandFinished { .. } => "CommandFinished",
+            WsEvent::BotDelays { .. }       => "BotDelays",
+        }
+    }
+
+    /// The bot the event is about; None for per-socket replies.
+    pub fn bot_id(&self) -> Option<u32> {
+        match self {
+            WsEvent::Snapshot { .. } | WsEvent::Ack { .. } => None,
+            WsEvent::BotAdded { bot_id, .. }
+            | WsEvent::BotRemoved { bot_id }
+            | WsEvent::BotStatus { bot_id, .. }
+            | WsEvent::BotWorld { bot_id, .. }
+            | WsEvent::BotMove { bot_id, .. }
+            | WsEvent::BotGems { bot_id, .. }
+            | WsEvent::BotPing { bot_id, .. }
+            | WsEvent::PlayerSpawn { bot_id, .. }
+            | WsEvent::PlayerMove { bot_id, .. }
+            | WsEvent::PlayerLeave { bot_id, .. }
+            | WsEvent::WorldLoaded { bot_id, .. }
+            | WsEvent::TileUpdate { bot_id, .. }
+            | WsEvent::ObjectsUpdate { bot_id, .. }
+            | WsEvent::InventoryUpdate { bot_id, .. }
+            | WsEvent::Console { bot_id, .. }
+            | WsEvent::BotTrackInfo { bot_id, .. }
+            | WsEvent::BotUsername { bot_id, .. }
+            | WsEvent::BotAutoCollect { bot_id, .. }
+            | WsEvent::BotExited { bot_id, .. }
+            | WsEvent::JobFired { bot_id, .. }
+            | WsEvent::JobFailed { bot_id, .. }
+            | WsEvent::CommandFinished { bot_id, .. }
+            | WsEvent::BotDelays { bot_id, .. } => Some(*bot_id),
+        }
+    }
+}
+
+/// Event kinds a client may subscribe to (everything except the per-socket replies).
+pub const SUBSCRIBABLE_EVENTS: &[&str] = &[
+    "BotAdded", "BotRemoved", "BotStatus", "BotWorld", "BotMove", "BotGems", "BotPing",
+    "PlayerSpawn", "PlayerMove", "PlayerLeave", "WorldLoaded", "TileUpdate", "ObjectsUpdate",
+    "InventoryUpdate", "Console", "BotTrackInfo", "BotUsername", "BotAutoCollect", "BotExited",
+    "JobFired", "JobFailed", "CommandFinished", "BotDelays",
+];
+
 pub type WsTx = tokio::sync::broadcast::Sender<WsEvent>;
+
+// ── Client messages ───────────────────────────────────────────────────────────
+
+/// A message from a WebSocket client. Every message is answered with an `Ack`
+/// carrying the same `id`.
+#[derive(Deserialize, ToSchema)]
+pub struct WsClientMessage {
+    #[serde(default)]
+    pub id:      Option<String>,
+    #[serde(flatten)]
+    pub request: WsRequest,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WsRequest {
+    /// Receive events for these bots / of these kinds. Empty lists leave that filter unchanged.
+    Subscribe {
+        #[serde(default)]
+        bots:   Vec<u32>,
+        #[serde(default)]
+        events: Vec<String>,
+    },
+    /// Stop receiving events for these bots / of these kinds.
+    Unsubscribe {
+        #[serde(default)]
+        bots:   Vec<u32>,
+        #[serde(default)]
+        events: Vec<String>,
+    },
+    /// Receive every event of every bot again.
+    Reset,
+    /// Same as `POST /bots/{bot_id}/cmd`.
+    Cmd { bot_id: u32, cmd: CmdRequest },
+}
+
+// ── Subscriptions ─────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selection<T: Ord> {
+    All,
+    Only(BTreeSet<T>),
+    Except(BTreeSet<T>),
+}
+
+impl<T: Ord> Selection<T> {
+    fn contains(&self, value: &T) -> bool {
+        match self {
+            Selection::All          => true,
+            Selection::Only(set)    => set.contains(value),
+            Selection::Except(set)  => !set.contains(value),
+        }
+    }
+
+    /// The first subscribe on `All` narrows the selection to `items`.
+    fn add(&mut self, items: Vec<T>) {
+        if items.is_empty() {
+            return;
+        }
+        match self {
+            Selection::All          => *self = Selection::Only(items.into_iter().collect()),
+            Selection::Only(set)    => set.extend(items),
+            Selection::Except(set)  => items.iter().for_each(|v| { set.remove(v); }),
+        }
+    }
+
+    fn remove(&mut self, items: Vec<T>) {
+        if items.is_empty() {
+            return;
+        }
+        match self {
+            Selection::All          => *self = Selection::Except(items.into_iter().collect()),
+            Selection::Only(set)    => items.iter().for_each(|v| { set.remove(v); }),
+            Selection::Except(set)  => set.extend(items),
+        }
+    }
+}
+
+/// Which broadcast events one socket receives. Starts with everything.
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    bots:   Selection<u32>,
+    events: Selection<String>,
+}
+
+impl Default for Subscription {
+    fn default() -> Self {
+        Self { bots: Selection::All, events: Selection::All }
+    }
+}
+
+impl Subscription {
+    pub fn wants_bot(&self, bot_id: u32) -> bool {
+        self.bots.contains(&bot_id)
+    }
+
+    /// Per-socket replies are always delivered.
+    pub fn wants(&self, event: &WsEvent) -> bool {
+        match event.bot_id() {
+            None => true,
+            Some(bot_id) => self.wants_bot(bot_id) && self.events.contains(&event.kind().to_string()),
+        }
+    }
+
+    fn check_events(events: &[String]) -> Result<(), String> {
+        match events.iter().find(|e| !SUBSCRIBABLE_EVENTS.contains(&e.as_str())) {
+            Some(unknown) => Err(format!("unknown event '{unknown}'")),
+            None => Ok(()),
+        }
+    }
+
+    pub fn subscribe(&mut self, bots: Vec<u32>, events: Vec<String>) -> Result<(), String> {
+        Self::check_events(&events)?;
+        self.bots.add(bots);
+        self.events.add(events);
+        Ok(())
+    }
+
+    pub fn unsubscribe(&mut self, bots: Vec<u32>, events: Vec<String>) -> Result<(), String> {
+        Self::check_events(&events)?;
+        self.bots.remove(bots);
+        self.events.remove(events);
+        Ok(())
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn console(bot_id: u32) -> WsEvent {
+        WsEvent::Console { bot_id, category: ConsoleCategory::Chat, message: String::new() }
+    }
+
+    #[test]
+    fn subscriptions_narrow_and_widen() {
+        let mut sub = Subscription::default();
+        assert!(sub.wants(&console(1)));
+
+        sub.unsubscribe(vec![], vec!["Console".into()]).unwrap();
+        assert!(!sub.wants(&console(1)));
+        assert!(sub.wants(&WsEvent::BotGems { bot_id: 1, gems: 0 }));
+
+        sub.subscribe(vec![2], vec!["Console".into()]).unwrap();
+        assert!(sub.wants(&console(2)));
+        assert!(!sub.wants(&console(1)));
+        assert!(!sub.wants_bot(1));
+
+        sub.unsubscribe(vec![2], vec![]).unwrap();
+        assert!(!sub.wants(&console(2)));
+        assert!(sub.wants(&WsEvent::Ack { id: None, ok: true, error: None, cmd_id: None }));
+        assert!(sub.subscribe(vec![], vec!["Nope".into()]).is_err());
+    }
+
+    #[test]
+    fn parses_client_messages() {
+        let msg: WsClientMessage = serde_json::from_str(
+            r#"{"id":"r1","type":"cmd","bot_id":3,"cmd":{"type":"move","x":1,"y":0}}"#,
+        ).unwrap();
+        assert_eq!(msg.id.as_deref(), Some("r1"));
+        assert!(matches!(msg.request, WsRequest::Cmd { bot_id: 3, cmd: CmdRequest::Move { x: 1, y: 0 } }));
+
+        let msg: WsClientMessage = serde_json::from_str(r#"{"type":"subscribe","bots":[1]}"#).unwrap();
+        assert!(msg.id.is_none());
+        assert!(matches!(msg.request, WsRequest::Subscribe { ref bots, ref events } if bots == &[1] && events.is_empty()));
+
+        let kinds: Vec<&str> = [console(1), WsEvent::BotRemoved { bot_id: 1 }].iter().map(WsEvent::kind).collect();
+        assert!(kinds.iter().all(|k| SUBSCRIBABLE_EVENTS.contains(k)));
+    }
+}
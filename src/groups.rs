@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use utoipa::ToSchema;
+
+// ── Groups ────────────────────────────────────────────────────────────────────
+//
+// A named set of bots that commands can be broadcast to. Members are the listed
+// bot IDs plus every bot carrying one of the listed tags, resolved when used.
+
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+pub struct Group {
+    pub name:    String,
+    #[serde(default)]
+    pub bot_ids: Vec<u32>,
+    #[serde(default)]
+    pub tags:    Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct GroupInfo {
+    #[serde(flatten)]
+    pub group:   Group,
+    /// Running bots the group currently resolves to.
+    pub members: Vec<u32>,
+}
+
+/// Changes accepted by `GroupStore::update`; missing fields are left as they are.
+#[derive(Deserialize, ToSchema)]
+pub struct GroupPatch {
+    pub bot_ids: Option<Vec<u32>>,
+    pub tags:    Option<Vec<String>>,
+}
+
+/// Group names and tags: 1–32 ASCII letters, digits, `-` or `_`.
+pub fn check_name(kind: &str, name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.len() <= 32
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("invalid {kind} '{name}': use 1-32 letters, digits, '-' or '_'"))
+    }
+}
+
+/// Validate, sort and dedup a tag list.
+pub fn normalize_tags(mut tags: Vec<String>) -> Result<Vec<String>, String> {
+    for tag in &tags {
+        check_name("tag", tag)?;
+    }
+    tags.sort_unstable();
+    tags.dedup();
+    Ok(tags)
+}
+
+fn normalize_ids(mut ids: Vec<u32>) -> Vec<u32> {
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+fn save_groups(path: &Path, groups: &[Group]) -> anyhow::Result<()> {
+    let data = serde_json::to_string_pretty(groups)?;
+    crate::persist::write_atomic(path, data.as_bytes())?;
+    Ok(())
+}
+
+// ── Store ─────────────────────────────────────────────────────────────────────
+
+/// Bot groups, persisted in `groups.json`. Owned by `BotManager`.
+pub struct GroupStore {
+    path:   PathBuf,
+    groups: Vec<Group>,
+}
+
+impl GroupStore {
+    /// Errors when `groups.json` can't be read, or does not parse and can't be moved aside.
+    pub fn new(data_dir: &Path) -> anyhow::Result<Self> {
+        let path = data_dir.join("groups.json");
+        let groups = crate::persist::load_json(&path, "Groups")?.unwrap_or_default();
+        Ok(Self { path, groups })
+    }
+
+    fn save(&self) {
+        if let Err(e) = save_groups(&self.path, &self.groups) {
+            println!("[Groups] Failed to save groups.json: {e}");
+        }
+    }
+
+    pub fn list(&self) -> &[Group] {
+        &self.groups
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Group> {
+        self.groups.iter().find(|g| g.name == name)
+    }
+
+    /// Returns `Ok(None)` when a group with this name already exists.
+    pub fn create(&mut self, name: String, bot_ids: Vec<u32>, tags: Vec<String>) -> Result<Option<Group>, String> {
+        check_name("group name", &name)?;
+        let group = Group { name, bot_ids: normalize_ids(bot_ids), tags: normalize_tags(tags)? };
+        if self.get(&group.name).is_some() {
+            return Ok(None);
+        }
+        self.groups.push(group.clone());
+        self.groups.sort_by(|a, b| a.name.cmp(&b.name));
+        self.save();
+        Ok(Some(group))
+    }
+
+    /// Returns `Ok(None)` when no group has this name.
+    pub fn update(&mut self, name: &str, patch: GroupPatch) -> Result<Option<Group>, String> {
+        let tags = patch.tags.map(normalize_tags).transpose()?;
+        let Some(group) = self.groups.iter_mut().find(|g| g.name == name) else {
+            return Ok(None);
+        };
+        if let Some(ids) = patch.bot_ids {
+            group.bot_ids = normalize_ids(ids);
+        }
+        if let Some(tags) = tags {
+            group.tags = tags;
+        }
+        let group = group.clone();
+        self.save();
+        Ok(Some(group))
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.groups.len();
+        self.groups.retain(|g| g.name != name);
+        let removed = self.groups.len() != before;
+        if removed {
+            self.save();
+        }
+        removed
+    }
+
+    /// Drop a removed bot from every group that lists it by ID.
+    pub fn remove_bot(&mut self, bot_id: u32) {
+        let mut changed = false;
+        for group in &mut self.groups {
+            let before = group.bot_ids.len();
+            group.bot_ids.retain(|id| *id != bot_id);
+            changed |= group.bot_ids.len() != before;
+        }
+        if changed {
+            self.save();
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    fn farmers(store: &mut GroupStore) -> Group {
+        store.create("farmers".into(), vec![3, 1, 3], vec!["b".into(), "a".into()]).unwrap().unwrap()
+    }
+
+    #[test]
+    fn create_sorts_and_validates() {
+        let dir = TestDir::new("groups");
+        let mut store = GroupStore::new(&dir).unwrap();
+        let group = farmers(&mut store);
+        assert_eq!(group.bot_ids, vec![1, 3]);
+        assert_eq!(group.tags, vec!["a", "b"]);
+        assert!(store.create("farmers".into(), vec![], vec![]).unwrap().is_none());
+        assert!(store.create("bad name".into(), vec![], vec![]).is_err());
+        assert!(store.update("farmers", GroupPatch { bot_ids: None, tags: Some(vec!["x|y".into()]) }).is_err());
+    }
+
+    #[test]
+    fn changes_persist_until_removed() {
+        let dir = TestDir::new("groups");
+        let mut store = GroupStore::new(&dir).unwrap();
+        farmers(&mut store);
+        store.update("farmers", GroupPatch { bot_ids: Some(vec![2]), tags: None }).unwrap().unwrap();
+        store.remove_bot(2);
+
+        let reloaded = GroupStore::new(&dir).unwrap();
+        let group = reloaded.get("farmers").unwrap();
+        assert!(group.bot_ids.is_empty());
+        assert_eq!(group.tags, vec!["a", "b"]);
+        assert!(store.remove("farmers"));
+        assert!(!store.remove("farmers"));
+    }
+
+    #[test]
+    fn corrupt_groups_are_moved_aside() {
+        let dir = TestDir::new("groups");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("groups.json"), "[{\"name\": \"fa").unwrap();
+        let mut store = GroupStore::new(&dir).unwrap();
+        assert!(store.list().is_empty());
+
+        farmers(&mut store);
+        let backup = std::fs::read_dir(&*dir).unwrap()
+            .map(|e| e.unwrap().path())
+            .find(|p| p.to_string_lossy().contains("groups.json.corrupt-"))
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(backup).unwrap(), "[{\"name\": \"fa");
+    }
+}
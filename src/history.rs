@@ -0,0 +1,284 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use utoipa::ToSchema;
+
+use crate::bot_state::BotStatus;
+use crate::web::SharedManager;
+
+// ── Samples ───────────────────────────────────────────────────────────────────
+//
+// One JSON object per line in `<data_dir>/history/<bot_id>.jsonl`, appended every
+// `history.interval_secs` while the bot is `in_game`. Files outlive their bots until
+// `prune` ages out their last sample or they are purged.
+
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+pub struct Sample {
+    /// Unix timestamp.
+    pub ts:          u64,
+    /// Unix timestamp of the launch this sample belongs to; changes on every (re)start.
+    pub session:     u64,
+    pub gems:        i32,
+    pub level:       u32,
+    pub awesomeness: u32,
+    pub world:       String,
+    /// Counts of the items listed in `history.items`, keyed by item ID.
+    #[serde(default)]
+    pub items:       BTreeMap<u16, u32>,
+}
+
+impl Sample {
+    /// Value of `metric` (`gems`, `level`, `awesomeness`, `world` or `item:<id>`).
+    pub fn metric(&self, metric: &str) -> Option<serde_json::Value> {
+        Some(match metric {
+            "gems"        => self.gems.into(),
+            "level"       => self.level.into(),
+            "awesomeness" => self.awesomeness.into(),
+            "world"       => self.world.clone().into(),
+            other => {
+                let id: u16 = other.strip_prefix("item:")?.parse().ok()?;
+                self.items.get(&id).copied().unwrap_or(0).into()
+            }
+        })
+    }
+}
+
+/// Whether `metric` names something `Sample::metric` can return.
+pub fn is_metric(metric: &str) -> bool {
+    matches!(metric, "gems" | "level" | "awesomeness" | "world")
+        || metric.strip_prefix("item:").is_some_and(|id| id.parse::<u16>().is_ok())
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Point {
+    pub ts:      u64,
+    pub session: u64,
+    pub value:   serde_json::Value,
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// ── Store ─────────────────────────────────────────────────────────────────────
+
+#[derive(Clone)]
+pub struct HistoryStore {
+    dir: PathBuf,
+}
+
+impl HistoryStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self { dir: data_dir.join("history") }
+    }
+
+    fn path(&self, bot_id: u32) -> PathBuf {
+        self.dir.join(format!("{bot_id}.jsonl"))
+    }
+
+    pub fn append(&self, bot_id: u32, sample: &Sample) -> anyhow::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(bot_id))?;
+        writeln!(file, "{}", serde_json::to_string(sample)?)?;
+        Ok(())
+    }
+
+    /// Samples with `from <= ts <= to`, oldest first. Unparsable lines are skipped.
+    pub fn read(&self, bot_id: u32, from: u64, to: u64) -> Vec<Sample> {
+        let Ok(file) = std::fs::File::open(self.path(bot_id)) else {
+            return Vec::new();
+        };
+        BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str::<Sample>(&line).ok())
+            .filter(|s| s.ts >= from && s.ts <= to)
+            .collect()
+    }
+
+    pub fn exists(&self, bot_id: u32) -> bool {
+        self.path(bot_id).exists()
+    }
+
+    /// IDs of every bot with a history file, sorted.
+    pub fn bot_ids(&self) -> Vec<u32> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut ids: Vec<u32> = entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| entry.file_name().to_str()?.strip_suffix(".jsonl")?.parse().ok())
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Delete the history of `bot_id`. Returns false when there was none.
+    pub fn remove(&self, bot_id: u32) -> bool {
+        std::fs::remove_file(self.path(bot_id)).is_ok()
+    }
+
+    /// Drop samples older than `cutoff` from every file, and files left empty.
+    pub fn prune(&self, cutoff: u64) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        for path in entries.flatten().map(|e| e.path()) {
+            if path.extension().is_none_or(|ext| ext != "jsonl") {
+                continue;
+            }
+            let Ok(data) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let kept: Vec<&str> = data.lines()
+                .filter(|line| {
+                    serde_json::from_str::<Sample>(line).is_ok_and(|s| s.ts >= cutoff)
+                })
+                .collect();
+            if kept.len() == data.lines().count() {
+                continue;
+            }
+            let result = if kept.is_empty() {
+                std::fs::remove_file(&path)
+            } else {
+                let mut out = kept.join("\n");
+                out.push('\n');
+                crate::persist::write_atomic(&path, out.as_bytes())
+            };
+            if let Err(e) = result {
+                println!("[History] Failed to prune {}: {e}", path.display());
+            }
+        }
+    }
+}
+
+/// Reduce `points` to the last one in each `bucket_secs` window.
+pub fn downsample(points: Vec<Point>, bucket_secs: u64) -> Vec<Point> {
+    let mut out: Vec<Point> = Vec::new();
+    for point in points {
+        match out.last_mut() {
+            Some(last) if last.ts / bucket_secs == point.ts / bucket_secs => *last = point,
+            _ => out.push(point),
+        }
+    }
+    out
+}
+
+// ── Sampler ───────────────────────────────────────────────────────────────────
+
+const PRUNE_EVERY: Duration = Duration::from_secs(3600);
+
+/// Sample every in-game bot each `history.interval_secs` and prune old samples hourly.
+pub fn spawn_sampler(manager: SharedManager) {
+    tokio::spawn(async move {
+        let (interval, retention) = {
+            let mgr = manager.lock().unwrap();
+            let h = &mgr.config.history;
+            (Duration::from_secs(h.interval_secs.max(10)), h.retention_days * 86_400)
+        };
+        let mut last_prune: Option<std::time::Instant> = None;
+        loop {
+            let (store, samples) = {
+                let mgr = manager.lock().unwrap();
+                (mgr.history.clone(), mgr.history_samples())
+            };
+            let prune = retention > 0 && last_prune.is_none_or(|t| t.elapsed() >= PRUNE_EVERY);
+            if prune {
+                last_prune = Some(std::time::Instant::now());
+            }
+            let _ = tokio::task::spawn_blocking(move || {
+                for (bot_id, sample) in &samples {
+                    if let Err(e) = store.append(*bot_id, sample) {
+                        println!("[History] Failed to write sample for bot {bot_id}: {e}");
+                    }
+                }
+                if prune {
+                    store.prune(unix_now().saturating_sub(retention));
+                }
+            }).await;
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+/// Whether a bot with this status has meaningful gems/inventory to record.
+pub fn should_sample(status: &BotStatus) -> bool {
+    matches!(status, BotStatus::InGame)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    fn sample(ts: u64, gems: i32) -> Sample {
+        Sample {
+            ts,
+            session: 1,
+            gems,
+            level: 5,
+            awesomeness: 0,
+            world: "START".into(),
+            items: BTreeMap::from([(242, 3)]),
+        }
+    }
+
+    #[test]
+    fn metric_lookup() {
+        let s = sample(10, 100);
+        assert_eq!(s.metric("gems"), Some(100.into()));
+        assert_eq!(s.metric("item:242"), Some(3.into()));
+        assert_eq!(s.metric("item:1796"), Some(0.into()));
+        assert_eq!(s.metric("world"), Some("START".into()));
+        assert_eq!(s.metric("item:x"), None);
+        assert_eq!(s.metric("bogus"), None);
+        assert!(is_metric("item:242") && is_metric("gems"));
+        assert!(!is_metric("item:x") && !is_metric("bogus"));
+    }
+
+    #[test]
+    fn append_read_and_prune() {
+        let dir = TestDir::new("history");
+        let store = HistoryStore::new(&dir);
+        for ts in [100, 200, 300] {
+            store.append(7, &sample(ts, ts as i32)).unwrap();
+        }
+        assert_eq!(store.read(7, 150, 300).len(), 2);
+        store.prune(250);
+        let left = store.read(7, 0, u64::MAX);
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].ts, 300);
+    }
+
+    #[test]
+    fn prune_deletes_files_left_empty() {
+        let dir = TestDir::new("history");
+        let store = HistoryStore::new(&dir);
+        store.append(3, &sample(100, 1)).unwrap();
+        store.append(7, &sample(300, 1)).unwrap();
+        assert_eq!(store.bot_ids(), [3, 7]);
+
+        store.prune(250);
+        assert!(!store.exists(3));
+        assert_eq!(store.bot_ids(), [7]);
+        assert!(store.remove(7));
+        assert!(!store.remove(7));
+    }
+
+    #[test]
+    fn downsample_keeps_last_per_bucket() {
+        let points = [10, 50, 3610, 3700, 7300]
+            .into_iter()
+            .map(|ts| Point { ts, session: 1, value: ts.into() })
+            .collect();
+        let out: Vec<u64> = downsample(points, 3600).into_iter().map(|p| p.ts).collect();
+        assert_eq!(out, vec![50, 3700, 7300]);
+    }
+}
@@ -0,0 +1,197 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+use std::net::ToSocketAddrs;
+use std::time::Duration;
+
+use crate::bot::{Bot, Socks5Config};
+use crate::config::BotDefaults;
+use crate::roster::{BotConfig, BotLogin};
+use crate::web::SharedManager;
+
+// ── Line parsing ──────────────────────────────────────────────────────────────
+//
+// Accepted forms, one account per line (blank lines and `#` comments skipped):
+//   user:pass
+//   user:pass:proxy_host:proxy_port
+//   user:pass:proxy_host:proxy_port:proxy_user:proxy_pass
+//   token|rid|mac|wk
+
+#[derive(Serialize, ToSchema)]
+pub struct ImportError {
+    /// 1-based line number in the submitted text.
+    pub line:  usize,
+    pub error: String,
+}
+
+/// Parse one account line into a login and optional proxy.
+pub fn parse_line(line: &str) -> Result<(BotLogin, Option<Socks5Config>), String> {
+    // The mac field of an ltoken contains ':', so detect ltokens before splitting.
+    // Passwords may contain '|' too, so anything that isn't a valid ltoken is a login.
+    let ltoken_like = line.split('|').count() == 4;
+    if ltoken_like && Bot::parse_ltoken_string(line).is_some() {
+        return Ok((BotLogin::Ltoken { ltoken: line.to_string() }, None));
+    }
+    parse_legacy(line).map_err(|error| if ltoken_like {
+        "invalid ltoken — expected token|rid|mac|wk with 32-char rid and wk".into()
+    } else {
+        error
+    })
+}
+
+fn parse_legacy(line: &str) -> Result<(BotLogin, Option<Socks5Config>), String> {
+    let parts: Vec<&str> = line.split(':').collect();
+    let (username, password) = match parts.as_slice() {
+        [u, p, ..] if !u.is_empty() && !p.is_empty() => (u.to_string(), p.to_string()),
+        _ => return Err("expected user:pass".into()),
+    };
+
+    let proxy = match parts.len() {
+        2 => None,
+        4 | 6 => Some(proxy_from_fields(&parts[2..])?),
+        n => return Err(format!("expected 2, 4 or 6 ':'-separated fields, got {n}")),
+    };
+
+    Ok((BotLogin::Legacy { username, password }, proxy))
+}
+
+/// Parse a `host:port` or `host:port:user:pass` SOCKS5 proxy spec.
+pub fn parse_proxy(spec: &str) -> Result<Socks5Config, String> {
+    let fields: Vec<&str> = spec.split(':').collect();
+    match fields.len() {
+        2 | 4 => proxy_from_fields(&fields),
+        n => Err(format!("expected host:port or host:port:user:pass, got {n} fields")),
+    }
+}
+
+fn proxy_from_fields(fields: &[&str]) -> Result<Socks5Config, String> {
+    let port: u16 = fields[1].parse().map_err(|_| format!("invalid proxy port '{}'", fields[1]))?;
+    let proxy_addr = (fields[0], port)
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut a| a.next())
+        .ok_or_else(|| format!("cannot resolve proxy host '{}'", fields[0]))?;
+    let (username, password) = if fields.len() == 4 {
+        (Some(fields[2].to_string()), Some(fields[3].to_string()))
+    } else {
+        (None, None)
+    };
+    Ok(Socks5Config { proxy_addr, username, password })
+}
+
+/// Parse a whole import body. Returns the valid configs and a list of per-line errors.
+/// Proxy hosts are resolved here, so async callers should run it on a blocking thread.
+pub fn parse_accounts(text: &str, defaults: &BotDefaults) -> (Vec<BotConfig>, Vec<ImportError>) {
+    let mut configs = Vec::new();
+    let mut errors = Vec::new();
+    for (i, raw) in text.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match parse_line(line) {
+            Ok((login, proxy)) => {
+                configs.push(BotConfig::new(login, proxy, defaults));
+            }
+            Err(error) => errors.push(ImportError { line: i + 1, error }),
+        }
+    }
+    (configs, errors)
+}
+
+// ── Staged spawning ───────────────────────────────────────────────────────────
+
+/// Spawn `configs` in waves of `concurrency`, sleeping `delay` between waves so
+/// the login server doesn't answer with "Too many people logging in".
+pub async fn spawn_in_waves(
+    manager: SharedManager,
+    configs: Vec<BotConfig>,
+    concurrency: usize,
+    delay: Duration,
+) {
+    let total = configs.len();
+    let mut spawned = 0;
+    for wave in configs.chunks(concurrency.max(1)) {
+        if spawned > 0 {
+            tokio::time::sleep(delay).await;
+        }
+        {
+            let mut mgr = manager.lock().unwrap();
+            for config in wave {
+                match mgr.spawn(config.clone()) {
+                    Ok(Some(_)) => {}
+                    Ok(None) => {
+                        println!("[Import] Roster not restored — dropping the remaining bots");
+                        return;
+                    }
+                    Err(e) => {
+                        println!("[Import] {e} — dropping the remaining bots");
+                        return;
+                    }
+                }
+            }
+        }
+        spawned += wave.len();
+        println!("[Import] Spawned {spawned}/{total} bots");
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RID: &str = "0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn parses_user_pass() {
+        let (login, proxy) = parse_line("alice:hunter2").unwrap();
+        assert!(matches!(login, BotLogin::Legacy { ref username, ref password }
+            if username == "alice" && password == "hunter2"));
+        assert!(proxy.is_none());
+    }
+
+    #[test]
+    fn parses_proxy_with_auth() {
+        let (_, proxy) = parse_line("alice:hunter2:127.0.0.1:1080:pu:pp").unwrap();
+        let proxy = proxy.unwrap();
+        assert_eq!(proxy.proxy_addr.port(), 1080);
+        assert_eq!(proxy.username.as_deref(), Some("pu"));
+        assert_eq!(proxy.password.as_deref(), Some("pp"));
+    }
+
+    #[test]
+    fn parses_ltoken_with_colons_in_mac() {
+        let line = format!("tok|{RID}|02:00:00:00:00:00|{RID}");
+        let (login, proxy) = parse_line(&line).unwrap();
+        assert!(matches!(login, BotLogin::Ltoken { ref ltoken } if *ltoken == line));
+        assert!(proxy.is_none());
+    }
+
+    #[test]
+    fn parses_user_pass_with_pipes() {
+        let (login, _) = parse_line("alice:pa|ss").unwrap();
+        assert!(matches!(login, BotLogin::Legacy { ref password, .. } if password == "pa|ss"));
+        let (login, _) = parse_line("alice:a|b|c|d").unwrap();
+        assert!(matches!(login, BotLogin::Legacy { ref password, .. } if password == "a|b|c|d"));
+    }
+
+    #[test]
+    fn rejects_bad_lines() {
+        assert!(parse_line("alice").is_err());
+        assert!(parse_line("alice:hunter2:127.0.0.1").is_err());
+        assert!(parse_line("alice:hunter2:127.0.0.1:notaport").is_err());
+        assert!(parse_line("tok|short|mac|wk").is_err());
+    }
+
+    #[test]
+    fn reports_line_numbers_and_skips_comments() {
+        let text = "# accounts\nalice:pw\n\nbroken\nbob:pw\n";
+        let defaults = BotDefaults { autostart: false, ..Default::default() };
+        let (configs, errors) = parse_accounts(text, &defaults);
+        assert_eq!(configs.len(), 2);
+        assert!(configs.iter().all(|c| !c.autostart));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 4);
+    }
+}
@@ -5,7 +5,7 @@ const XOR_KEY: &[u8; 16] = b"PBG892FXX982ABC*";
 
 // ── Item record ───────────────────────────────────────────────────────────────
 
-#[derive(Debug, Default, Clone, serde::Serialize)]
+#[derive(Debug, Default, Clone, serde::Serialize, utoipa::ToSchema)]
 pub struct ItemInfo {
     pub id:                  u32,
     pub flags:               u16,
@@ -101,9 +101,9 @@ impl ItemsDat {
         self.items.iter().find(|i| i.name.to_lowercase() == lower)
     }
 
-    /// Load from `items.dat` on disk; returns an empty database on failure.
-    pub fn load() -> Self {
-        match std::fs::read("items.dat").and_then(|d| Self::parse(&d).map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))) {
+    /// Load `items.dat` from `path`; returns an empty database on failure.
+    pub fn load(path: &std::path::Path) -> Self {
+        match std::fs::read(path).and_then(|d| Self::parse(&d).map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))) {
             Ok(db) => {
                 println!("[Items] Loaded {} items", db.items.len());
                 db
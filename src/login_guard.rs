@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+// ── Failed-login tracking ─────────────────────────────────────────────────────
+//
+// Every endpoint that checks a password reserves an `Attempt` here first and reports
+// its outcome through it. Checks still in flight count against the IP's free
+// attempts, so parallel requests can't all get in before the first failure is
+// recorded. Once an IP passes its free attempts, each further failure doubles its
+// lockout, up to `MAX_LOCKOUT`. Failures across all clients never lock anyone out,
+// since that would let an attacker lock out the real users: past the global free
+// attempts, checks run one at a time, each after a `GLOBAL_THROTTLE` delay, for the
+// length of the lockout instead, except for IPs that logged in successfully within
+// `FORGET_AFTER`. Counters are forgotten after `FORGET_AFTER` without failures; a
+// successful login resets the IP's counter and the global one.
+
+/// Failures per IP before the first lockout.
+const IP_FREE_ATTEMPTS:     u32 = 5;
+/// Failures across all IPs before checks are throttled.
+const GLOBAL_FREE_ATTEMPTS: u32 = 50;
+/// Delay before each password check while the global counter is over its limit.
+const GLOBAL_THROTTLE:      Duration = Duration::from_secs(2);
+const BASE_LOCKOUT:         Duration = Duration::from_secs(30);
+const MAX_LOCKOUT:          Duration = Duration::from_secs(3600);
+const FORGET_AFTER:         Duration = Duration::from_secs(24 * 3600);
+/// `Retry-After` for an IP whose remaining attempts are all in flight.
+const IN_FLIGHT_RETRY:      Duration = Duration::from_secs(1);
+
+#[derive(Default)]
+struct Attempts {
+    failures:     u32,
+    last_failure: Option<Instant>,
+    locked_until: Option<Instant>,
+    /// Password checks reserved by `check_at` that have not finished yet.
+    in_flight:    u32,
+}
+
+impl Attempts {
+    fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.locked_until.filter(|t| *t > now).map(|t| t - now)
+    }
+
+    fn is_stale(&self, now: Instant) -> bool {
+        self.last_failure.is_none_or(|t| now.duration_since(t) >= FORGET_AFTER)
+    }
+
+    /// Count a failure; returns the new lockout once past `free` attempts.
+    fn fail(&mut self, free: u32, now: Instant) -> Option<Duration> {
+        if self.is_stale(now) {
+            *self = Attempts { in_flight: self.in_flight, ..Attempts::default() };
+        }
+        self.failures += 1;
+        self.last_failure = Some(now);
+        if self.failures < free {
+            return None;
+        }
+        let doublings = (self.failures - free).min(16);
+        let lockout = BASE_LOCKOUT.saturating_mul(1 << doublings).min(MAX_LOCKOUT);
+        self.locked_until = Some(now + lockout);
+        Some(lockout)
+    }
+}
+
+/// A lockout (or, for `global`, a throttle) that was just imposed, for the console
+/// and the audit log.
+pub struct Lockout {
+    /// `ip` or `global`.
+    pub scope:    String,
+    pub failures: u32,
+    pub duration: Duration,
+}
+
+impl std::fmt::Display for Lockout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.scope == "global" {
+            return write!(
+                f,
+                "throttling logins for {}s after {} failed attempts across all clients",
+                self.duration.as_secs(), self.failures,
+            );
+        }
+        write!(
+            f,
+            "locked out {} for {}s after {} failed attempts",
+            self.scope, self.duration.as_secs(), self.failures,
+        )
+    }
+}
+
+#[derive(Default)]
+struct GuardInner {
+    by_ip:   HashMap<IpAddr, Attempts>,
+    global:  Attempts,
+    /// Last successful login per IP; these skip the global throttle.
+    trusted: HashMap<IpAddr, Instant>,
+}
+
+#[derive(Clone)]
+pub struct LoginGuard {
+    inner:    Arc<Mutex<GuardInner>>,
+    /// Held by each check while the global throttle applies, so they run one at a time.
+    throttle: Arc<Semaphore>,
+}
+
+impl Default for LoginGuard {
+    fn default() -> Self {
+        Self { inner: Arc::default(), throttle: Arc::new(Semaphore::new(1)) }
+    }
+}
+
+/// A password check reserved by `LoginGuard::check`. Report its outcome with `failed`
+/// or `succeeded`; dropped without either, it is released without counting.
+#[must_use]
+pub struct Attempt {
+    guard:     LoginGuard,
+    ip:        IpAddr,
+    _throttle: Option<OwnedSemaphorePermit>,
+}
+
+impl Attempt {
+    pub fn failed(self) -> Option<Lockout> {
+        self.guard.failed_at(self.ip, Instant::now())
+    }
+
+    pub fn succeeded(self) {
+        self.guard.succeeded_at(self.ip, Instant::now());
+    }
+}
+
+impl Drop for Attempt {
+    fn drop(&mut self) {
+        self.guard.release(self.ip);
+    }
+}
+
+impl LoginGuard {
+    /// `Err(retry_after)` while `ip` is locked out or its remaining attempts are in
+    /// flight. Waits its turn under the global throttle before returning the attempt.
+    pub async fn check(&self, ip: IpAddr) -> Result<Attempt, Duration> {
+        let delay = self.check_at(ip, Instant::now())?;
+        let mut attempt = Attempt { guard: self.clone(), ip, _throttle: None };
+        if !delay.is_zero() {
+            let permit = self.throttle.clone().acquire_owned().await.expect("throttle is never closed");
+            attempt._throttle = Some(permit);
+            tokio::time::sleep(delay).await;
+        }
+        Ok(attempt)
+    }
+
+    /// Reserve an attempt for `ip`: `Ok(delay)` to wait before checking the password,
+    /// `Err(retry_after)` while locked out. Every `Ok` must be matched by `release`.
+    fn check_at(&self, ip: IpAddr, now: Instant) -> Result<Duration, Duration> {
+        let mut inner = self.inner.lock().unwrap();
+        let trusted = inner.trusted.get(&ip).is_some_and(|t| now.duration_since(*t) < FORGET_AFTER);
+        let throttled = !trusted && inner.global.remaining(now).is_some();
+        let attempts = inner.by_ip.entry(ip).or_default();
+        if let Some(retry) = attempts.remaining(now) {
+            return Err(retry);
+        }
+        // Past the free attempts, only one check at a time, so each failure locks out.
+        let failures = if attempts.is_stale(now) { 0 } else { attempts.failures };
+        if attempts.in_flight > 0 && failures + attempts.in_flight >= IP_FREE_ATTEMPTS {
+            return Err(IN_FLIGHT_RETRY);
+        }
+        attempts.in_flight += 1;
+        Ok(if throttled { GLOBAL_THROTTLE } else { Duration::ZERO })
+    }
+
+    fn release(&self, ip: IpAddr) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(attempts) = inner.by_ip.get_mut(&ip) {
+            attempts.in_flight = attempts.in_flight.saturating_sub(1);
+        }
+    }
+
+    fn succeeded_at(&self, ip: IpAddr, now: Instant) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(attempts) = inner.by_ip.get_mut(&ip) {
+            *attempts = Attempts { in_flight: attempts.in_flight, ..Attempts::default() };
+        }
+        inner.global = Attempts::default();
+        inner.trusted.insert(ip, now);
+    }
+
+    fn failed_at(&self, ip: IpAddr, now: Instant) -> Option<Lockout> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.by_ip.retain(|_, a| a.in_flight > 0 || !a.is_stale(now));
+        inner.trusted.retain(|_, t| now.duration_since(*t) < FORGET_AFTER);
+
+        let global = inner.global.fail(GLOBAL_FREE_ATTEMPTS, now).map(|duration| Lockout {
+            scope:    "global".into(),
+            failures: inner.global.failures,
+            duration,
+        });
+        let attempts = inner.by_ip.entry(ip).or_default();
+        let by_ip = attempts.fail(IP_FREE_ATTEMPTS, now).map(|duration| Lockout {
+            scope:    ip.to_string(),
+            failures: attempts.failures,
+            duration,
+        });
+
+        // Report the longer of the two when both trigger.
+        let lockout = match (by_ip, global) {
+            (Some(a), Some(b)) => Some(if b.duration > a.duration { b } else { a }),
+            (a, b) => a.or(b),
+        }?;
+        println!("[Auth] {lockout}");
+        Some(lockout)
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn per_ip_lockout_doubles_and_resets() {
+        let guard = LoginGuard::default();
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        let other: IpAddr = "10.0.0.2".parse().unwrap();
+        let t0 = Instant::now();
+
+        for _ in 0..IP_FREE_ATTEMPTS - 1 {
+            assert!(guard.failed_at(ip, t0).is_none());
+        }
+        assert_eq!(guard.failed_at(ip, t0).unwrap().duration, BASE_LOCKOUT);
+        assert_eq!(guard.check_at(ip, t0), Err(BASE_LOCKOUT));
+        assert_eq!(guard.check_at(other, t0), Ok(Duration::ZERO));
+
+        let t1 = t0 + BASE_LOCKOUT;
+        assert!(guard.check_at(ip, t1).is_ok());
+        assert_eq!(guard.failed_at(ip, t1).unwrap().duration, BASE_LOCKOUT * 2);
+
+        guard.succeeded_at(ip, t1);
+        assert!(guard.check_at(ip, t1).is_ok());
+        assert!(guard.failed_at(ip, t1).is_none());
+    }
+
+    #[test]
+    fn attempts_in_flight_count_against_the_free_ones() {
+        let guard = LoginGuard::default();
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        let t0 = Instant::now();
+
+        for _ in 0..IP_FREE_ATTEMPTS {
+            assert_eq!(guard.check_at(ip, t0), Ok(Duration::ZERO));
+        }
+        assert_eq!(guard.check_at(ip, t0), Err(IN_FLIGHT_RETRY));
+        guard.release(ip);
+        assert!(guard.check_at(ip, t0).is_ok());
+
+        // Past the free attempts, a second check waits for the first to finish.
+        for _ in 0..IP_FREE_ATTEMPTS {
+            guard.release(ip);
+            guard.failed_at(ip, t0);
+        }
+        let t1 = t0 + MAX_LOCKOUT;
+        assert!(guard.check_at(ip, t1).is_ok());
+        assert_eq!(guard.check_at(ip, t1), Err(IN_FLIGHT_RETRY));
+    }
+
+    /// Fail once from each of `GLOBAL_FREE_ATTEMPTS` different IPs.
+    fn spread_failures(guard: &LoginGuard, now: Instant) -> Option<Lockout> {
+        let mut last = None;
+        for i in 0..GLOBAL_FREE_ATTEMPTS {
+            let ip = IpAddr::from([10, 1, (i / 256) as u8, (i % 256) as u8]);
+            last = guard.failed_at(ip, now);
+        }
+        last
+    }
+
+    #[test]
+    fn global_failures_throttle_instead_of_locking_out() {
+        let guard = LoginGuard::default();
+        let user: IpAddr = "192.168.1.1".parse().unwrap();
+        let t0 = Instant::now();
+        guard.succeeded_at(user, t0);
+
+        let throttle = spread_failures(&guard, t0).unwrap();
+        assert_eq!(throttle.scope, "global");
+        assert!(throttle.to_string().starts_with("throttling logins"));
+        assert_eq!(guard.check_at("192.168.1.2".parse().unwrap(), t0), Ok(GLOBAL_THROTTLE));
+        // A recent successful login skips the throttle.
+        assert_eq!(guard.check_at(user, t0), Ok(Duration::ZERO));
+        assert_eq!(guard.check_at(user, t0 + throttle.duration), Ok(Duration::ZERO));
+    }
+
+    #[test]
+    fn success_resets_the_global_counter() {
+        let guard = LoginGuard::default();
+        let t0 = Instant::now();
+        spread_failures(&guard, t0).unwrap();
+        guard.succeeded_at("192.168.1.1".parse().unwrap(), t0);
+        assert_eq!(guard.check_at("192.168.1.2".parse().unwrap(), t0), Ok(Duration::ZERO));
+        assert!(guard.failed_at("192.168.1.2".parse().unwrap(), t0).is_none());
+    }
+
+    #[test]
+    fn lockout_is_capped_and_forgotten() {
+        let t0 = Instant::now();
+
+        let mut a = Attempts::default();
+        for _ in 0..40 {
+            a.fail(1, t0);
+        }
+        assert_eq!(a.remaining(t0), Some(MAX_LOCKOUT));
+        assert!(a.is_stale(t0 + FORGET_AFTER));
+    }
+}
@@ -5,9 +5,10 @@ use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 use crate::bot::BotEventRaw;
+use crate::console_log::ConsoleCategory;
 use crate::protocol::packet::{GamePacketType, GameUpdatePacket, PacketFlags};
 use crate::player::Player;
-use crate::script_channel::{ScriptRequest as Req, ScriptReply as Rep};
+use crate::script_channel::{ScriptOutcome, ScriptRequest as Req, ScriptReply as Rep};
 use crate::protocol::variant::Variant;
 use crate::world::{TileFlags, TileType, World};
 
@@ -947,8 +948,8 @@ pub fn run_script_threaded(
     stop_flag: std::sync::Arc<std::sync::atomic::AtomicBool>,
     username:  String,
     script:    String,
-) {
-    run_script_inner(req_tx, reply_rx, event_rx, items, state, stop_flag, username, script);
+) -> ScriptOutcome {
+    run_script_inner(req_tx, reply_rx, event_rx, items, state, stop_flag, username, script)
 }
 
 fn run_script_inner(
@@ -960,7 +961,7 @@ fn run_script_inner(
     stop_flag: std::sync::Arc<std::sync::atomic::AtomicBool>,
     username:  String,
     script:    String,
-) {
+) -> ScriptOutcome {
     let lua = Lua::new_with(
         mlua::StdLib::TABLE | mlua::StdLib::STRING | mlua::StdLib::MATH | mlua::StdLib::IO,
         mlua::LuaOptions::default(),
@@ -1415,13 +1416,16 @@ end
     };
 
     if let Err(e) = setup() {
-        state.write().unwrap().console.push(format!("`4[Lua setup error] {e}"));
-        return;
+        crate::bot_state::BotState::push_console(&state, ConsoleCategory::Script, format!("`4[Lua setup error] {e}"));
+        return ScriptOutcome::Failed(e.to_string());
     }
 
-    if let Err(e) = lua.load(&script).exec() {
-        if !e.to_string().contains("__script_stop__") {
-            state.write().unwrap().console.push(format!("`4[Lua] {e}"));
+    match lua.load(&script).exec() {
+        Ok(()) => ScriptOutcome::Finished,
+        Err(e) if e.to_string().contains("__script_stop__") => ScriptOutcome::Stopped,
+        Err(e) => {
+            crate::bot_state::BotState::push_console(&state, ConsoleCategory::Script, format!("`4[Lua] {e}"));
+            ScriptOutcome::Failed(e.to_string())
         }
     }
 }
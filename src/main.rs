@@ -1,6 +1,13 @@
+mod api_keys;
 mod astar;
+mod audit;
 mod auth;
 mod bot;
+mod cli;
+mod command_tracker;
+mod commands;
+mod config;
+mod console_log;
 mod constants;
 mod cursor;
 mod inventory;
@@ -8,15 +15,31 @@ pub mod events;
 mod bot_state;
 mod bot_manager;
 mod dashboard;
+mod groups;
+mod history;
 pub mod items;
+mod import;
 mod login;
+mod login_guard;
 mod lua;
+mod metrics;
+mod openapi;
+mod persist;
 mod player;
 mod protocol;
+mod proxy_pool;
 mod proxy_test;
+mod roster;
 pub mod save_dat;
+mod scheduler;
+mod scripts;
 mod server_data;
 mod socks5;
+mod supervisor;
+#[cfg(test)]
+mod test_dir;
+mod tls;
+mod vault;
 pub mod world;
 mod web;
 pub mod script_channel;
@@ -24,9 +47,45 @@ pub mod script_channel;
 use std::sync::{Arc, Mutex};
 use bot_manager::BotManager;
 
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = match cli::parse(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{e}\n\n{}", cli::USAGE);
+            std::process::exit(cli::EXIT_USAGE);
+        }
+    };
+    let config = match config::Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            println!("[Config] {e} — refusing to start");
+            std::process::exit(1);
+        }
+    };
+    match command {
+        cli::Command::Serve => serve(Arc::new(config)),
+        cli::Command::Help => println!("{}", cli::USAGE),
+        cli::Command::Run(run) => std::process::exit(cli::run(&config, run)),
+    }
+}
+
 #[tokio::main]
-async fn main() {
+async fn serve(config: Arc<config::Config>) {
     let (ws_tx, _) = tokio::sync::broadcast::channel(256);
-    let mgr = Arc::new(Mutex::new(BotManager::new(ws_tx.clone())));
-    web::serve(mgr, ws_tx).await;
+    let mut mgr = match BotManager::new(ws_tx.clone(), config.clone()) {
+        Ok(mgr) => mgr,
+        Err(e) => {
+            println!("[Mori] {e} — refusing to start; fix or restore the file");
+            std::process::exit(1);
+        }
+    };
+    proxy_pool::spawn_health_checker(mgr.proxy_pool.clone());
+    mgr.restore();
+    let exit_rx = mgr.take_exit_rx().expect("exit receiver taken once");
+    let mgr = Arc::new(Mutex::new(mgr));
+    supervisor::spawn(mgr.clone(), exit_rx);
+    scheduler::spawn(mgr.clone());
+    history::spawn_sampler(mgr.clone());
+    web::serve(mgr, ws_tx, config).await;
 }
@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::bot_manager::BotManager;
+use crate::protocol::packet::GamePacketType;
+
+// ── Per-bot counters ──────────────────────────────────────────────────────────
+
+/// Counters updated by the bot thread. Shared through `BotState::metrics` and kept
+/// across supervisor restarts, so they only reset when the bot is removed.
+pub struct BotMetrics {
+    packets_sent:     [AtomicU64; 256],
+    packets_received: [AtomicU64; 256],
+    /// `action|logon_fail` count by the reason the bot recognised.
+    logon_failures:   Mutex<BTreeMap<&'static str, u64>>,
+    collect_pickups:  AtomicU64,
+    script_runs:      AtomicU64,
+    script_errors:    AtomicU64,
+}
+
+impl Default for BotMetrics {
+    fn default() -> Self {
+        Self {
+            packets_sent:     [const { AtomicU64::new(0) }; 256],
+            packets_received: [const { AtomicU64::new(0) }; 256],
+            logon_failures:   Mutex::new(BTreeMap::new()),
+            collect_pickups:  AtomicU64::new(0),
+            script_runs:      AtomicU64::new(0),
+            script_errors:    AtomicU64::new(0),
+        }
+    }
+}
+
+impl BotMetrics {
+    pub fn packet_sent(&self, t: GamePacketType) {
+        self.packets_sent[t.as_u8() as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn packet_received(&self, t: GamePacketType) {
+        self.packets_received[t.as_u8() as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn logon_failure(&self, reason: &'static str) {
+        *self.logon_failures.lock().unwrap().entry(reason).or_default() += 1;
+    }
+
+    pub fn collected(&self, pickups: usize) {
+        self.collect_pickups.fetch_add(pickups as u64, Ordering::Relaxed);
+    }
+
+    pub fn script_started(&self) {
+        self.script_runs.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn script_failed(&self) {
+        self.script_errors.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+// ── Prometheus text format ────────────────────────────────────────────────────
+
+/// One metric family: `# HELP`/`# TYPE` header followed by its samples.
+struct Family {
+    name:    &'static str,
+    help:    &'static str,
+    kind:    &'static str,
+    samples: Vec<(String, u64)>,
+}
+
+impl Family {
+    fn new(name: &'static str, kind: &'static str, help: &'static str) -> Self {
+        Self { name, help, kind, samples: Vec::new() }
+    }
+
+    fn push(&mut self, labels: String, value: u64) {
+        self.samples.push((labels, value));
+    }
+
+    fn write(&self, out: &mut String) {
+        let _ = writeln!(out, "# HELP {} {}", self.name, self.help);
+        let _ = writeln!(out, "# TYPE {} {}", self.name, self.kind);
+        for (labels, value) in &self.samples {
+            let _ = writeln!(out, "{}{{{}}} {}", self.name, labels, value);
+        }
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn packet_type_label(t: GamePacketType) -> String {
+    match t {
+        GamePacketType::Unknown(v) => format!("unknown_{v}"),
+        t => format!("{t:?}"),
+    }
+}
+
+/// Render every bot's gauges and counters in the Prometheus exposition format.
+pub fn render(manager: &BotManager) -> String {
+    let mut status    = Family::new("mori_bot_status", "gauge", "Current bot status (1 for the active status label).");
+    let mut ping      = Family::new("mori_bot_ping_ms", "gauge", "ENet round-trip time in milliseconds.");
+    let mut gems      = Family::new("mori_bot_gems", "gauge", "Gems balance.");
+    let mut inv_used  = Family::new("mori_bot_inventory_used_slots", "gauge", "Inventory slots in use.");
+    let mut inv_size  = Family::new("mori_bot_inventory_size_slots", "gauge", "Inventory slots available.");
+    let mut logon     = Family::new("mori_bot_logon_failures_total", "counter", "Logon failures that led to a reconnect, by reason.");
+    let mut sent      = Family::new("mori_bot_packets_sent_total", "counter", "Game packets sent, by GamePacketType.");
+    let mut received  = Family::new("mori_bot_packets_received_total", "counter", "Game packets received, by GamePacketType.");
+    let mut pickups   = Family::new("mori_bot_collect_pickups_total", "counter", "Objects picked up by auto-collect.");
+    let mut scripts   = Family::new("mori_bot_script_runs_total", "counter", "Lua scripts started.");
+    let mut script_errors = Family::new("mori_bot_script_errors_total", "counter", "Lua scripts that ended with an error.");
+
+    let mut ids: Vec<u32> = manager.bots.keys().copied().collect();
+    ids.sort_unstable();
+    for id in ids {
+        let entry = &manager.bots[&id];
+        let s = entry.state.read().unwrap();
+        let bot = format!("bot_id=\"{id}\",username=\"{}\"", escape(&entry.username));
+        let m = &s.metrics;
+
+        status.push(format!("{bot},status=\"{}\"", s.status), 1);
+        ping.push(bot.clone(), s.ping_ms as u64);
+        gems.push(bot.clone(), s.gems.max(0) as u64);
+        inv_used.push(bot.clone(), s.inventory.len() as u64);
+        inv_size.push(bot.clone(), s.inventory_size as u64);
+        for (reason, count) in m.logon_failures.lock().unwrap().iter() {
+            logon.push(format!("{bot},reason=\"{reason}\""), *count);
+        }
+        for (family, counters) in [(&mut sent, &m.packets_sent), (&mut received, &m.packets_received)] {
+            for (i, counter) in counters.iter().enumerate() {
+                let n = counter.load(Ordering::Relaxed);
+                if n > 0 {
+                    let label = packet_type_label(GamePacketType::from(i as u8));
+                    family.push(format!("{bot},type=\"{label}\""), n);
+                }
+            }
+        }
+        pickups.push(bot.clone(), m.collect_pickups.load(Ordering::Relaxed));
+        scripts.push(bot.clone(), m.script_runs.load(Ordering::Relaxed));
+        script_errors.push(bot, m.script_errors.load(Ordering::Relaxed));
+    }
+
+    let mut out = String::new();
+    for family in [
+        &status, &ping, &gems, &inv_used, &inv_size, &logon,
+        &sent, &received, &pickups, &scripts, &script_errors,
+    ] {
+        family.write(&mut out);
+    }
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn family_renders_header_and_samples() {
+        let mut f = Family::new("mori_bot_gems", "gauge", "Gems balance.");
+        f.push("bot_id=\"1\",username=\"a\\\"b\"".into(), 42);
+        let mut out = String::new();
+        f.write(&mut out);
+        assert_eq!(
+            out,
+            "# HELP mori_bot_gems Gems balance.\n\
+             # TYPE mori_bot_gems gauge\n\
+             mori_bot_gems{bot_id=\"1\",username=\"a\\\"b\"} 42\n"
+        );
+        assert_eq!(escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[test]
+    fn counts_packets_by_type() {
+        let m = BotMetrics::default();
+        m.packet_sent(GamePacketType::State);
+        m.packet_sent(GamePacketType::State);
+        m.packet_received(GamePacketType::Unknown(0xF0));
+        assert_eq!(m.packets_sent[0].load(Ordering::Relaxed), 2);
+        assert_eq!(m.packets_received[0xF0].load(Ordering::Relaxed), 1);
+        assert_eq!(packet_type_label(GamePacketType::from(0xF0)), "unknown_240");
+        assert_eq!(packet_type_label(GamePacketType::TileChangeRequest), "TileChangeRequest");
+    }
+}
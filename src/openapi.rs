@@ -0,0 +1,146 @@
+use std::sync::OnceLock;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::events::{WsClientMessage, WsEvent};
+use crate::history::Point;
+use crate::web;
+
+// ── OpenAPI document ──────────────────────────────────────────────────────────
+//
+// Served at `/openapi.json`. Operations come from the `#[utoipa::path]` attribute on
+// each handler in `web.rs`, collected by `web::api_routes` from the same table the
+// server routes with, and schemas are derived from the request and response types,
+// so the document follows the code. The WebSocket messages have no route of their
+// own and are listed as plain schemas.
+
+#[derive(OpenApi)]
+#[openapi(
+    info(description = "REST API of the Mori bot manager. Live events are on the `/ws` WebSocket, see `WsEvent` and `WsClientMessage`."),
+    components(schemas(WsEvent, WsClientMessage, Point)),
+    modifiers(&BearerAuth),
+    security(("bearer" = [])),
+)]
+pub struct ApiDoc;
+
+/// `ApiDoc` with every operation of `web::api_routes`, built once.
+pub fn spec() -> &'static utoipa::openapi::OpenApi {
+    static SPEC: OnceLock<utoipa::openapi::OpenApi> = OnceLock::new();
+    SPEC.get_or_init(|| web::api_routes().into_openapi())
+}
+
+/// Session tokens from `/auth/login` and API keys are both sent as `Authorization: Bearer …`.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn every_route_is_documented() {
+        let expected: BTreeSet<(&str, &str)> = [
+            ("GET", "/api-keys"),
+            ("POST", "/api-keys"),
+            ("DELETE", "/api-keys/{id}"),
+            ("GET", "/audit"),
+            ("POST", "/auth/login"),
+            ("POST", "/auth/logout"),
+            ("GET", "/auth/me"),
+            ("POST", "/auth/password"),
+            ("GET", "/auth/sessions"),
+            ("DELETE", "/auth/sessions/{id}"),
+            ("POST", "/auth/setup"),
+            ("GET", "/auth/status"),
+            ("GET", "/bots"),
+            ("POST", "/bots"),
+            ("POST", "/bots/cmd"),
+            ("POST", "/bots/import"),
+            ("POST", "/bots/ltoken"),
+            ("DELETE", "/bots/{id}"),
+            ("POST", "/bots/{id}/autostart"),
+            ("POST", "/bots/{id}/cmd"),
+            ("GET", "/bots/{id}/commands"),
+            ("GET", "/bots/{id}/commands/{cmd_id}"),
+            ("GET", "/bots/{id}/console"),
+            ("DELETE", "/bots/{id}/console"),
+            ("GET", "/bots/{id}/history"),
+            ("DELETE", "/bots/{id}/history"),
+            ("GET", "/bots/{id}/state"),
+            ("POST", "/bots/{id}/tags"),
+            ("GET", "/groups"),
+            ("POST", "/groups"),
+            ("POST", "/groups/{name}"),
+            ("DELETE", "/groups/{name}"),
+            ("POST", "/groups/{name}/cmd"),
+            ("GET", "/items"),
+            ("GET", "/items/colors"),
+            ("GET", "/items/names"),
+            ("GET", "/metrics"),
+            ("GET", "/proxies"),
+            ("POST", "/proxies"),
+            ("POST", "/proxies/check"),
+            ("POST", "/proxies/settings"),
+            ("DELETE", "/proxies/{id}"),
+            ("POST", "/proxy/test"),
+            ("GET", "/schedules"),
+            ("POST", "/schedules"),
+            ("POST", "/schedules/{id}"),
+            ("DELETE", "/schedules/{id}"),
+            ("GET", "/scripts"),
+            ("POST", "/scripts"),
+            ("GET", "/scripts/{name}"),
+            ("POST", "/scripts/{name}"),
+            ("DELETE", "/scripts/{name}"),
+            ("GET", "/scripts/{name}/versions/{version}"),
+            ("GET", "/users"),
+            ("POST", "/users"),
+            ("POST", "/users/{username}"),
+            ("DELETE", "/users/{username}"),
+            ("POST", "/vault/export"),
+            ("POST", "/vault/unlock"),
+        ].into_iter().collect();
+        let paths = &spec().paths.paths;
+        let documented: BTreeSet<(&str, &str)> = paths.iter()
+            .flat_map(|(path, item)| {
+                [
+                    ("GET", &item.get),
+                    ("POST", &item.post),
+                    ("PUT", &item.put),
+                    ("PATCH", &item.patch),
+                    ("DELETE", &item.delete),
+                ]
+                .into_iter()
+                .filter(|(_, op)| op.is_some())
+                .map(move |(method, _)| (method, path.as_str()))
+            })
+            .collect();
+        assert_eq!(documented, expected);
+        assert!(!paths.contains_key("/ws"));
+    }
+
+    #[test]
+    fn api_types_are_described() {
+        let spec = spec();
+        let schemas = &spec.components.as_ref().unwrap().schemas;
+        let names = [
+            "SpawnRequest", "CmdRequest", "BotState", "ItemsResponse", "ProxyTestResult", "WsEvent",
+            "LoginResponse", "CmdQueued", "ImportResponse", "SessionListEntry", "CreatedApiKey",
+        ];
+        for name in names {
+            assert!(schemas.contains_key(name), "{name} is missing from the OpenAPI schemas");
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+
+// ── Atomic writes ─────────────────────────────────────────────────────────────
+//
+// State files in the data directory (the vault, users, the roster, …) are written
+// to `<name>.tmp` next to the target and renamed over it, so a crash or a full disk
+// leaves either the old file or the new one.
+
+pub fn write_atomic(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, data)?;
+    std::fs::rename(&tmp, path)
+}
+
+// ── Unreadable files ──────────────────────────────────────────────────────────
+//
+// A state file that no longer parses is renamed to `<name>.corrupt-<unix time>`
+// before starting without it, so the next save can't overwrite what was in it.
+
+/// Read a JSON state file. A missing file is `None`, and so is one that does not
+/// parse once it has been moved aside (logged under `tag`). Errors when the file
+/// can't be read or moved.
+pub fn load_json<T: DeserializeOwned>(path: &Path, tag: &str) -> anyhow::Result<Option<T>> {
+    let name = path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned());
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => anyhow::bail!("cannot read {name}: {e}"),
+    };
+    match serde_json::from_str(&data) {
+        Ok(value) => Ok(Some(value)),
+        Err(e) => {
+            let backup = move_aside(path)
+                .map_err(|err| anyhow::anyhow!("{name} does not parse ({e}) and cannot be moved aside: {err}"))?;
+            println!("[{tag}] Failed to parse {name}: {e} — moved it to {} and starting without it", backup.display());
+            Ok(None)
+        }
+    }
+}
+
+/// Move `path` aside and return where it went.
+fn move_aside(path: &Path) -> std::io::Result<PathBuf> {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".corrupt-{}", crate::history::unix_now()));
+    let backup = PathBuf::from(backup);
+    std::fs::rename(path, &backup)?;
+    Ok(backup)
+}
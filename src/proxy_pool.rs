@@ -0,0 +1,422 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use utoipa::ToSchema;
+
+use crate::bot::Socks5Config;
+use crate::proxy_test::{ProxyTestResult, run_proxy_test};
+use crate::vault::{Sealed, Vault};
+
+/// Consecutive reconnect failures through one proxy before a pooled bot is moved.
+pub const ROTATE_AFTER_FAILURES: u32 = 3;
+/// Proxies `check_all` tests at the same time.
+const CHECK_WORKERS: usize = 8;
+
+// ── On-disk format ────────────────────────────────────────────────────────────
+
+fn default_max_bots() -> usize { 3 }
+fn default_check_interval() -> u64 { 300 }
+
+#[derive(Clone, Serialize, Deserialize)]
+struct PoolProxy {
+    id:              u32,
+    /// `password` is only written for proxies saved by older versions, until the
+    /// vault is unlocked and it can be sealed.
+    #[serde(flatten)]
+    config:          Socks5Config,
+    /// The password, sealed with the vault's data key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sealed_password: Option<Sealed>,
+}
+
+impl PoolProxy {
+    /// A sealed password that hasn't been opened yet can't authenticate.
+    fn is_locked(&self) -> bool {
+        self.sealed_password.is_some() && self.config.password.is_none()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct PoolFile {
+    #[serde(default = "default_max_bots")]
+    max_bots_per_proxy:  usize,
+    #[serde(default = "default_check_interval")]
+    check_interval_secs: u64,
+    #[serde(default)]
+    proxies:             Vec<PoolProxy>,
+}
+
+impl Default for PoolFile {
+    fn default() -> Self {
+        Self {
+            max_bots_per_proxy:  default_max_bots(),
+            check_interval_secs: default_check_interval(),
+            proxies:             Vec::new(),
+        }
+    }
+}
+
+/// Sealed passwords are written without their plain-text copy.
+fn save_file(path: &Path, file: &PoolFile) -> anyhow::Result<()> {
+    let mut file = file.clone();
+    for proxy in file.proxies.iter_mut().filter(|p| p.sealed_password.is_some()) {
+        proxy.config.password = None;
+    }
+    let data = serde_json::to_string_pretty(&file)?;
+    crate::persist::write_atomic(path, data.as_bytes())?;
+    Ok(())
+}
+
+// ── Runtime state ─────────────────────────────────────────────────────────────
+
+#[derive(Clone, Default)]
+struct Health {
+    /// None until the first check has run.
+    healthy:      Option<bool>,
+    last_checked: Option<u64>,
+    last_error:   Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ProxyInfo {
+    pub id:           u32,
+    pub proxy_addr:   String,
+    pub username:     Option<String>,
+    pub healthy:      Option<bool>,
+    /// Unix timestamp of the last health check.
+    pub last_checked: Option<u64>,
+    pub last_error:   Option<String>,
+    /// IDs of the bots currently assigned to this proxy.
+    pub bots:         Vec<u32>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PoolInfo {
+    pub max_bots_per_proxy:  usize,
+    pub check_interval_secs: u64,
+    pub proxies:             Vec<ProxyInfo>,
+}
+
+struct PoolInner {
+    /// `proxies.json` inside the data directory.
+    path:        PathBuf,
+    file:        PoolFile,
+    health:      HashMap<u32, Health>,
+    /// bot_id → proxy id.
+    assignments: HashMap<u32, u32>,
+}
+
+impl PoolInner {
+    fn save(&self) {
+        if let Err(e) = save_file(&self.path, &self.file) {
+            println!("[ProxyPool] Failed to save proxies.json: {e}");
+        }
+    }
+
+    fn load_of(&self, proxy_id: u32) -> usize {
+        self.assignments.values().filter(|p| **p == proxy_id).count()
+    }
+
+    /// Least-loaded usable proxy under the per-proxy limit, skipping `exclude`.
+    /// Proxies that have not been checked yet are treated as usable.
+    fn pick(&self, exclude: Option<u32>) -> Option<&PoolProxy> {
+        self.file.proxies.iter()
+            .filter(|p| Some(p.id) != exclude && !p.is_locked())
+            .filter(|p| self.health.get(&p.id).and_then(|h| h.healthy) != Some(false))
+            .map(|p| (p, self.load_of(p.id)))
+            .filter(|(_, load)| *load < self.file.max_bots_per_proxy)
+            .min_by_key(|(_, load)| *load)
+            .map(|(p, _)| p)
+    }
+}
+
+// ── Pool (shared across the manager, bots and web layer) ──────────────────────
+
+/// Registered SOCKS5 proxies, their health, and which bot uses which.
+#[derive(Clone)]
+pub struct ProxyPool {
+    inner: Arc<RwLock<PoolInner>>,
+    /// Seals proxy passwords; proxies with one can't be used until it is unlocked.
+    vault: Vault,
+}
+
+impl ProxyPool {
+    /// Errors when `proxies.json` can't be read, or does not parse and can't be moved aside.
+    pub fn new(data_dir: &Path, vault: Vault) -> anyhow::Result<Self> {
+        let path = data_dir.join("proxies.json");
+        Ok(Self {
+            inner: Arc::new(RwLock::new(PoolInner {
+                file:        crate::persist::load_json(&path, "ProxyPool")?.unwrap_or_default(),
+                path,
+                health:      HashMap::new(),
+                assignments: HashMap::new(),
+            })),
+            vault,
+        })
+    }
+
+    /// Open the sealed passwords once the vault is unlocked, and seal the ones that
+    /// older versions stored in plain text.
+    pub fn unseal(&self) {
+        let mut inner = self.inner.write().unwrap();
+        let mut migrated = false;
+        for proxy in &mut inner.file.proxies {
+            match (&proxy.sealed_password, &proxy.config.password) {
+                (Some(sealed), None) => match self.vault.open_value(sealed) {
+                    Ok(password) => proxy.config.password = Some(password),
+                    Err(e) => println!("[ProxyPool] Failed to open password of proxy {}: {e}", proxy.id),
+                },
+                (None, Some(password)) => match self.vault.seal_value(password) {
+                    Ok(sealed) => {
+                        proxy.sealed_password = Some(sealed);
+                        migrated = true;
+                    }
+                    Err(e) => println!("[ProxyPool] Failed to seal password of proxy {}: {e}", proxy.id),
+                },
+                _ => {}
+            }
+        }
+        if migrated {
+            inner.save();
+        }
+    }
+
+    pub fn info(&self) -> PoolInfo {
+        let inner = self.inner.read().unwrap();
+        let proxies = inner.file.proxies.iter().map(|p| {
+            let health = inner.health.get(&p.id).cloned().unwrap_or_default();
+            let mut bots: Vec<u32> = inner.assignments.iter()
+                .filter(|(_, pid)| **pid == p.id)
+                .map(|(bid, _)| *bid)
+                .collect();
+            bots.sort_unstable();
+            ProxyInfo {
+                id:           p.id,
+                proxy_addr:   p.config.proxy_addr.to_string(),
+                username:     p.config.username.clone(),
+                healthy:      health.healthy,
+                last_checked: health.last_checked,
+                last_error:   health.last_error,
+                bots,
+            }
+        }).collect();
+        PoolInfo {
+            max_bots_per_proxy:  inner.file.max_bots_per_proxy,
+            check_interval_secs: inner.file.check_interval_secs,
+            proxies,
+        }
+    }
+
+    /// Fails when the proxy has a password and the vault is locked.
+    pub fn add(&self, config: Socks5Config) -> anyhow::Result<u32> {
+        let sealed_password = config.password.as_deref().map(|p| self.vault.seal_value(p)).transpose()?;
+        let mut inner = self.inner.write().unwrap();
+        let id = inner.file.proxies.iter().map(|p| p.id + 1).max().unwrap_or(0);
+        inner.file.proxies.push(PoolProxy { id, config, sealed_password });
+        inner.save();
+        Ok(id)
+    }
+
+    /// Remove a proxy. Bots using it keep their connection and move on their next rotation.
+    pub fn remove(&self, id: u32) -> bool {
+        let mut inner = self.inner.write().unwrap();
+        let before = inner.file.proxies.len();
+        inner.file.proxies.retain(|p| p.id != id);
+        if inner.file.proxies.len() == before {
+            return false;
+        }
+        inner.health.remove(&id);
+        inner.assignments.retain(|_, pid| *pid != id);
+        inner.save();
+        true
+    }
+
+    pub fn set_settings(&self, max_bots_per_proxy: Option<usize>, check_interval_secs: Option<u64>) {
+        let mut inner = self.inner.write().unwrap();
+        if let Some(n) = max_bots_per_proxy {
+            inner.file.max_bots_per_proxy = n.max(1);
+        }
+        if let Some(secs) = check_interval_secs {
+            inner.file.check_interval_secs = secs.max(10);
+        }
+        inner.save();
+    }
+
+    /// Pick a proxy for `bot_id` and record the assignment.
+    pub fn assign(&self, bot_id: u32) -> Option<Socks5Config> {
+        let mut inner = self.inner.write().unwrap();
+        if let Some(pid) = inner.assignments.get(&bot_id).copied()
+            && let Some(p) = inner.file.proxies.iter().find(|p| p.id == pid)
+        {
+            return Some(p.config.clone());
+        }
+        let (pid, config) = inner.pick(None).map(|p| (p.id, p.config.clone()))?;
+        inner.assignments.insert(bot_id, pid);
+        Some(config)
+    }
+
+    /// Move `bot_id` off its current proxy, which is marked unhealthy until the
+    /// next check. Returns the new proxy, or None (keeping the old assignment)
+    /// if no other proxy is free.
+    pub fn rotate(&self, bot_id: u32, reason: &str) -> Option<Socks5Config> {
+        let mut inner = self.inner.write().unwrap();
+        let current = inner.assignments.get(&bot_id).copied();
+        if let Some(pid) = current {
+            let health = inner.health.entry(pid).or_default();
+            health.healthy = Some(false);
+            health.last_error = Some(reason.to_string());
+        }
+        let (pid, config) = inner.pick(current).map(|p| (p.id, p.config.clone()))?;
+        inner.assignments.insert(bot_id, pid);
+        Some(config)
+    }
+
+    pub fn release(&self, bot_id: u32) {
+        self.inner.write().unwrap().assignments.remove(&bot_id);
+    }
+
+    fn check_interval(&self) -> Duration {
+        Duration::from_secs(self.inner.read().unwrap().file.check_interval_secs)
+    }
+
+    /// Run the socks5 / server_data / enet checks against every proxy, up to
+    /// `CHECK_WORKERS` at a time. Blocking — call from a blocking context.
+    pub fn check_all(&self) {
+        let proxies = self.inner.read().unwrap().file.proxies.clone();
+        let queue = Mutex::new(proxies.into_iter().filter(|p| !p.is_locked()));
+        std::thread::scope(|scope| {
+            for _ in 0..CHECK_WORKERS {
+                scope.spawn(|| loop {
+                    let Some(proxy) = queue.lock().unwrap().next() else {
+                        break;
+                    };
+                    let test = std::panic::catch_unwind(|| run_proxy_test(proxy.config));
+                    if let Ok(result) = test {
+                        self.record_check(proxy.id, &result);
+                    }
+                });
+            }
+        });
+    }
+
+    fn record_check(&self, id: u32, result: &ProxyTestResult) {
+        let error = [("socks5", &result.socks5), ("server_data", &result.server_data), ("enet", &result.enet)]
+            .into_iter()
+            .find(|(_, c)| !c.ok)
+            .map(|(name, c)| format!("{name}: {}", c.error.as_deref().unwrap_or("failed")));
+
+        let mut inner = self.inner.write().unwrap();
+        if !inner.file.proxies.iter().any(|p| p.id == id) {
+            return;
+        }
+        inner.health.insert(id, Health {
+            healthy:      Some(error.is_none()),
+            last_checked: Some(crate::history::unix_now()),
+            last_error:   error,
+        });
+    }
+}
+
+/// Background task that re-checks the whole pool every `check_interval_secs`.
+pub fn spawn_health_checker(pool: ProxyPool) {
+    tokio::spawn(async move {
+        loop {
+            let p = pool.clone();
+            let _ = tokio::task::spawn_blocking(move || p.check_all()).await;
+            tokio::time::sleep(pool.check_interval()).await;
+        }
+    });
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    fn pool_with(n: u32, max: usize) -> PoolInner {
+        let proxies = (0..n).map(|id| PoolProxy {
+            id,
+            config: Socks5Config {
+                proxy_addr: format!("127.0.0.1:{}", 1080 + id).parse().unwrap(),
+                username:   None,
+                password:   None,
+            },
+            sealed_password: None,
+        }).collect();
+        PoolInner {
+            path: PathBuf::new(),
+            file: PoolFile { max_bots_per_proxy: max, proxies, ..Default::default() },
+            health: HashMap::new(),
+            assignments: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn pick_prefers_least_loaded_and_respects_limit() {
+        let mut inner = pool_with(2, 1);
+        inner.assignments.insert(10, 0);
+        assert_eq!(inner.pick(None).map(|p| p.id), Some(1));
+        inner.assignments.insert(11, 1);
+        assert!(inner.pick(None).is_none());
+    }
+
+    #[test]
+    fn pick_skips_unhealthy_and_excluded() {
+        let mut inner = pool_with(3, 5);
+        inner.health.insert(0, Health { healthy: Some(false), ..Default::default() });
+        assert_eq!(inner.pick(Some(1)).map(|p| p.id), Some(2));
+    }
+
+    fn authenticated() -> Socks5Config {
+        Socks5Config {
+            proxy_addr: "127.0.0.1:1080".parse().unwrap(),
+            username:   Some("user".into()),
+            password:   Some("hunter2".into()),
+        }
+    }
+
+    #[test]
+    fn passwords_need_an_unlocked_vault() {
+        let dir = TestDir::new("pool");
+        let vault = Vault::new(&dir);
+        assert!(ProxyPool::new(&dir, vault.clone()).unwrap().add(authenticated()).is_err());
+        vault.unlock("admin", "master").unwrap();
+        ProxyPool::new(&dir, vault).unwrap().add(authenticated()).unwrap();
+        assert!(!std::fs::read_to_string(dir.join("proxies.json")).unwrap().contains("hunter2"));
+    }
+
+    #[test]
+    fn sealed_proxies_are_skipped_until_unsealed() {
+        let dir = TestDir::new("pool");
+        let vault = Vault::new(&dir);
+        vault.unlock("admin", "master").unwrap();
+        let id = ProxyPool::new(&dir, vault).unwrap().add(authenticated()).unwrap();
+
+        // Reloaded with a locked vault the proxy is skipped until `unseal`.
+        let reopened = Vault::new(&dir);
+        let pool = ProxyPool::new(&dir, reopened.clone()).unwrap();
+        assert!(pool.assign(1).is_none());
+        reopened.unlock("admin", "master").unwrap();
+        pool.unseal();
+        let assigned = pool.assign(1).unwrap();
+        assert_eq!((assigned.password.as_deref(), pool.info().proxies[0].id), (Some("hunter2"), id));
+    }
+
+    #[test]
+    fn corrupt_pool_is_moved_aside() {
+        let dir = TestDir::new("pool");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("proxies.json"), "{\"proxies\": [{\"id").unwrap();
+        let pool = ProxyPool::new(&dir, Vault::new(&dir)).unwrap();
+        assert!(pool.info().proxies.is_empty());
+        let backup = std::fs::read_dir(&*dir).unwrap()
+            .map(|e| e.unwrap().path())
+            .find(|p| p.to_string_lossy().contains("proxies.json.corrupt-"))
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(backup).unwrap(), "{\"proxies\": [{\"id");
+    }
+}
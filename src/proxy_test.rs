@@ -2,13 +2,14 @@ use rusty_enet as enet;
 use serde::Serialize;
 use std::net::SocketAddr;
 use std::time::{Duration, Instant};
+use utoipa::ToSchema;
 
 use crate::bot::Socks5Config;
 use crate::constants::{GAME_VER, PROTOCOL};
 use crate::server_data::{LoginInfo, get_server_data_proxied};
 use crate::socks5::Socks5UdpSocket;
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct CheckResult {
     pub ok: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -17,7 +18,7 @@ pub struct CheckResult {
     pub detail: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct ProxyTestResult {
     pub socks5: CheckResult,
     pub server_data: CheckResult,
@@ -0,0 +1,256 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::bot::Socks5Config;
+use crate::bot_state::{BotCommand, BotDelays};
+use crate::config::BotDefaults;
+use crate::vault::{Sealed, Vault};
+
+// ── Bot configuration ─────────────────────────────────────────────────────────
+
+/// How a bot logs in. Mirrors the two spawn endpoints.
+///
+/// The secret half (password / ltoken string) is never written to the roster;
+/// it lives in the encrypted vault and is filled back in on restore.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum BotLogin {
+    Legacy {
+        username: String,
+        #[serde(default, skip_serializing)]
+        password: String,
+    },
+    Ltoken {
+        #[serde(default, skip_serializing)]
+        ltoken: String,
+    },
+}
+
+impl BotLogin {
+    pub fn secret(&self) -> &str {
+        match self {
+            BotLogin::Legacy { password, .. } => password,
+            BotLogin::Ltoken { ltoken }       => ltoken,
+        }
+    }
+
+    pub fn set_secret(&mut self, secret: String) {
+        match self {
+            BotLogin::Legacy { password, .. } => *password = secret,
+            BotLogin::Ltoken { ltoken }       => *ltoken = secret,
+        }
+    }
+}
+
+fn default_true() -> bool { true }
+
+/// Everything needed to bring a bot back exactly as it was spawned and tuned.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BotConfig {
+    pub login:                BotLogin,
+    pub proxy:                Option<Socks5Config>,
+    /// Take a proxy from the pool at launch (ignored when `proxy` is set).
+    #[serde(default)]
+    pub use_proxy_pool:       bool,
+    #[serde(default)]
+    pub delays:               BotDelays,
+    pub auto_collect:         bool,
+    pub collect_radius_tiles: u8,
+    #[serde(default)]
+    pub collect_blacklist:    Vec<u16>,
+    pub auto_reconnect:       bool,
+    /// Respawn the bot with backoff when its thread panics.
+    #[serde(default = "default_true")]
+    pub restart_on_crash:     bool,
+    /// Spawn this bot again when the server starts.
+    pub autostart:            bool,
+    /// Free-form labels that groups and broadcast commands can select bots by.
+    #[serde(default)]
+    pub tags:                 Vec<String>,
+}
+
+impl BotConfig {
+    pub fn new(login: BotLogin, proxy: Option<Socks5Config>, defaults: &BotDefaults) -> Self {
+        Self {
+            login,
+            proxy,
+            use_proxy_pool:       defaults.use_proxy_pool,
+            delays:               defaults.delays.clone(),
+            auto_collect:         defaults.auto_collect,
+            collect_radius_tiles: defaults.collect_radius_tiles.clamp(1, 5),
+            collect_blacklist:    Vec::new(),
+            auto_reconnect:       defaults.auto_reconnect,
+            restart_on_crash:     defaults.restart_on_crash,
+            autostart:            defaults.autostart,
+            tags:                 Vec::new(),
+        }
+    }
+
+    pub fn username(&self) -> &str {
+        match &self.login {
+            BotLogin::Legacy { username, .. } => username,
+            BotLogin::Ltoken { .. }           => "",
+        }
+    }
+
+    /// Records the settings carried by `cmd`. Returns true when something changed.
+    pub fn apply(&mut self, cmd: &BotCommand) -> bool {
+        match cmd {
+            BotCommand::SetDelays(d) => {
+                self.delays = d.clone();
+            }
+            BotCommand::SetAutoCollect { enabled } => {
+                self.auto_collect = *enabled;
+            }
+            BotCommand::SetCollectConfig { radius_tiles, blacklist } => {
+                self.collect_radius_tiles = (*radius_tiles).clamp(1, 5);
+                let mut list = blacklist.clone();
+                list.sort_unstable();
+                list.dedup();
+                self.collect_blacklist = list;
+            }
+            BotCommand::SetAutoReconnect { enabled } => {
+                self.auto_reconnect = *enabled;
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Commands that bring a freshly constructed `Bot` in line with this config.
+    pub fn startup_commands(&self) -> Vec<BotCommand> {
+        vec![
+            BotCommand::SetDelays(self.delays.clone()),
+            BotCommand::SetAutoCollect { enabled: self.auto_collect },
+            BotCommand::SetCollectConfig {
+                radius_tiles: self.collect_radius_tiles,
+                blacklist:    self.collect_blacklist.clone(),
+            },
+            BotCommand::SetAutoReconnect { enabled: self.auto_reconnect },
+        ]
+    }
+}
+
+// ── Persistent roster ─────────────────────────────────────────────────────────
+
+#[derive(Serialize, Deserialize)]
+pub struct RosterEntry {
+    pub id: u32,
+    #[serde(flatten)]
+    pub config: BotConfig,
+}
+
+/// A roster entry as written to `bots.json`. The proxy password is only kept sealed
+/// with the vault's data key; a plain one is only read from rosters saved by older
+/// versions.
+#[derive(Serialize, Deserialize)]
+struct StoredEntry {
+    #[serde(flatten)]
+    entry:                 RosterEntry,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sealed_proxy_password: Option<Sealed>,
+}
+
+/// Load `bots.json` from the data directory; a missing file is an empty roster.
+/// Sealed proxy passwords are opened with `vault`, which must be unlocked.
+///
+/// A file that does not parse is moved aside to `bots.json.corrupt-<unix time>` so the
+/// next save can't overwrite it; an error means it could not even be read or moved,
+/// or a proxy password could not be opened.
+pub fn load(data_dir: &Path, vault: &Vault) -> anyhow::Result<Vec<RosterEntry>> {
+    let stored: Vec<StoredEntry> =
+        crate::persist::load_json(&data_dir.join("bots.json"), "Roster")?.unwrap_or_default();
+    stored.into_iter().map(|StoredEntry { mut entry, sealed_proxy_password }| -> anyhow::Result<RosterEntry> {
+        if let (Some(proxy), Some(sealed)) = (entry.config.proxy.as_mut(), sealed_proxy_password) {
+            let password = vault.open_value(&sealed)
+                .map_err(|e| anyhow::anyhow!("cannot open the proxy password of bot {}: {e}", entry.id))?;
+            proxy.password = Some(password);
+        }
+        Ok(entry)
+    }).collect()
+}
+
+/// Proxy passwords are sealed with `vault` and never written in plain text.
+pub fn save(data_dir: &Path, entries: &[RosterEntry], vault: &Vault) -> anyhow::Result<()> {
+    let stored = entries.iter().map(|entry| -> anyhow::Result<StoredEntry> {
+        let mut config = entry.config.clone();
+        let sealed_proxy_password = match config.proxy.as_mut().and_then(|p| p.password.take()) {
+            Some(password) => Some(vault.seal_value(&password)
+                .map_err(|e| anyhow::anyhow!("cannot seal the proxy password of bot {}: {e}", entry.id))?),
+            None => None,
+        };
+        Ok(StoredEntry { entry: RosterEntry { id: entry.id, config }, sealed_proxy_password })
+    }).collect::<anyhow::Result<Vec<_>>>()?;
+    let data = serde_json::to_string_pretty(&stored)?;
+    crate::persist::write_atomic(&data_dir.join("bots.json"), data.as_bytes())?;
+    Ok(())
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    fn entry(id: u32, autostart: bool) -> RosterEntry {
+        let login = BotLogin::Legacy { username: format!("bot{id}"), password: "secret".into() };
+        let mut config = BotConfig::new(login, None, &BotDefaults::default());
+        config.autostart = autostart;
+        RosterEntry { id, config }
+    }
+
+    fn unlocked_vault(dir: &TestDir) -> Vault {
+        let vault = Vault::new(dir);
+        vault.unlock("admin", "hunter2").unwrap();
+        vault
+    }
+
+    #[test]
+    fn round_trips_without_secrets() {
+        let dir = TestDir::new("roster");
+        let vault = unlocked_vault(&dir);
+        assert!(load(&dir, &vault).unwrap().is_empty());
+
+        save(&dir, &[entry(0, true), entry(3, false)], &vault).unwrap();
+        assert!(!std::fs::read_to_string(dir.join("bots.json")).unwrap().contains("secret"));
+        let entries = load(&dir, &vault).unwrap();
+        assert_eq!(entries.iter().map(|e| (e.id, e.config.autostart)).collect::<Vec<_>>(), [(0, true), (3, false)]);
+        assert_eq!(entries[1].config.username(), "bot3");
+        assert_eq!(entries[1].config.login.secret(), "");
+    }
+
+    #[test]
+    fn seals_the_proxy_password() {
+        let dir = TestDir::new("roster");
+        let vault = unlocked_vault(&dir);
+        let mut proxied = entry(1, true);
+        proxied.config.proxy = Some(Socks5Config {
+            proxy_addr: "127.0.0.1:1080".parse().unwrap(),
+            username:   Some("proxyuser".into()),
+            password:   Some("proxypass".into()),
+        });
+
+        save(&dir, &[proxied], &vault).unwrap();
+        let saved = std::fs::read_to_string(dir.join("bots.json")).unwrap();
+        assert!(saved.contains("proxyuser"));
+        assert!(!saved.contains("proxypass"));
+        let proxy = load(&dir, &vault).unwrap().remove(0).config.proxy.unwrap();
+        assert_eq!(proxy.password.as_deref(), Some("proxypass"));
+        assert!(load(&dir, &Vault::new(&dir)).is_err());
+    }
+
+    #[test]
+    fn moves_a_corrupt_roster_aside() {
+        let dir = TestDir::new("roster");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("bots.json"), "[{\"id\": 1, \"log").unwrap();
+
+        assert!(load(&dir, &Vault::new(&dir)).unwrap().is_empty());
+        assert!(!dir.join("bots.json").exists());
+        let backups: Vec<String> = std::fs::read_dir(&dir).unwrap()
+            .map(|e| std::fs::read_to_string(e.unwrap().path()).unwrap())
+            .collect();
+        assert_eq!(backups, ["[{\"id\": 1, \"log"]);
+    }
+}
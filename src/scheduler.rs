@@ -0,0 +1,418 @@
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDateTime, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use utoipa::ToSchema;
+
+use crate::commands::CmdRequest;
+use crate::web::SharedManager;
+
+// ── Cron expressions ──────────────────────────────────────────────────────────
+//
+// Five fields in server local time: minute hour day-of-month month day-of-week.
+// Each field takes `*`, `n`, `a-b`, lists `a,b` and steps `*/n`, `a-b/n`, `a/n`.
+// Day-of-week is 0-6 from Sunday (7 is also Sunday). When both day fields are
+// restricted a time matches if either does, as in classic cron; a field that
+// covers every value (`*`, `*/1`, `1-31`, …) is not restricted.
+
+#[derive(Debug, PartialEq)]
+pub struct Cron {
+    minutes:  u64,
+    hours:    u64,
+    days:     u64,
+    months:   u64,
+    weekdays: u64,
+    dom_any:  bool,
+    dow_any:  bool,
+}
+
+impl Cron {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expr = match expr.trim() {
+            "@hourly"  => "0 * * * *",
+            "@daily"   => "0 0 * * *",
+            "@weekly"  => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other      => other,
+        };
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [min, hour, dom, month, dow] = fields.as_slice() else {
+            return Err(format!("expected 5 fields, got {}", fields.len()));
+        };
+        let mut weekdays = parse_field(dow, 0, 7).map_err(|e| format!("day-of-week: {e}"))?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        let days = parse_field(dom, 1, 31).map_err(|e| format!("day-of-month: {e}"))?;
+        Ok(Self {
+            minutes:  parse_field(min, 0, 59).map_err(|e| format!("minute: {e}"))?,
+            hours:    parse_field(hour, 0, 23).map_err(|e| format!("hour: {e}"))?,
+            days,
+            months:   parse_field(month, 1, 12).map_err(|e| format!("month: {e}"))?,
+            weekdays,
+            dom_any:  days == full_mask(1, 31),
+            dow_any:  weekdays == full_mask(0, 6),
+        })
+    }
+
+    fn day_matches(&self, t: &NaiveDateTime) -> bool {
+        let dom = self.days & (1 << t.day()) != 0;
+        let dow = self.weekdays & (1 << t.weekday().num_days_from_sunday()) != 0;
+        let day = if self.dom_any || self.dow_any { dom && dow } else { dom || dow };
+        day && self.months & (1 << t.month()) != 0
+    }
+
+    pub fn matches(&self, t: &NaiveDateTime) -> bool {
+        self.day_matches(t)
+            && self.hours & (1 << t.hour()) != 0
+            && self.minutes & (1 << t.minute()) != 0
+    }
+
+    /// First matching minute strictly after `from`, looking up to five years ahead.
+    pub fn next_after(&self, from: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = from.naive_local().with_second(0)?.with_nanosecond(0)?;
+        let end = start + ChronoDuration::days(5 * 366);
+        let mut t = start + ChronoDuration::minutes(1);
+        while t < end {
+            if !self.day_matches(&t) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << t.hour()) == 0 {
+                t = t.with_minute(0)? + ChronoDuration::hours(1);
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t += ChronoDuration::minutes(1);
+            } else if let Some(dt) = Local.from_local_datetime(&t).earliest() {
+                return Some(dt);
+            } else {
+                // Skipped by a DST change.
+                t += ChronoDuration::minutes(1);
+            }
+        }
+        None
+    }
+}
+
+/// Bits `min..=max` set, i.e. a field that matches every value.
+fn full_mask(min: u32, max: u32) -> u64 {
+    (min..=max).fold(0, |mask, v| mask | 1 << v)
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((r, s)) => {
+                let step: u32 = s.parse().map_err(|_| format!("invalid step '{s}'"))?;
+                if step == 0 {
+                    return Err("step must be at least 1".into());
+                }
+                (r, Some(step))
+            }
+            None => (part, None),
+        };
+        let num = |s: &str| -> Result<u32, String> {
+            let n: u32 = s.parse().map_err(|_| format!("invalid value '{s}'"))?;
+            if n < min || n > max {
+                return Err(format!("{n} is outside {min}-{max}"));
+            }
+            Ok(n)
+        };
+        let (lo, hi) = match range {
+            "*" => (min, max),
+            r => match r.split_once('-') {
+                Some((a, b)) => (num(a)?, num(b)?),
+                // `a/n` runs from a to the end of the range.
+                None if step.is_some() => (num(r)?, max),
+                None => { let n = num(r)?; (n, n) }
+            },
+        };
+        if lo > hi {
+            return Err(format!("empty range {lo}-{hi}"));
+        }
+        for v in (lo..=hi).step_by(step.unwrap_or(1) as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+// ── Jobs ──────────────────────────────────────────────────────────────────────
+
+fn default_true() -> bool { true }
+
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+pub struct Job {
+    pub id:         String,
+    pub bot_id:     u32,
+    pub cron:       String,
+    pub command:    CmdRequest,
+    #[serde(default = "default_true")]
+    pub enabled:    bool,
+    /// Unix timestamp of the last time the job fired.
+    #[serde(default)]
+    pub last_run:   Option<u64>,
+    /// Error from the last run, cleared when a run succeeds.
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct JobInfo {
+    #[serde(flatten)]
+    pub job:      Job,
+    /// Unix timestamp of the next run, or null when disabled.
+    pub next_run: Option<i64>,
+}
+
+/// Changes accepted by `Scheduler::update`; missing fields are left as they are.
+#[derive(Deserialize, ToSchema)]
+pub struct JobPatch {
+    pub cron:    Option<String>,
+    pub command: Option<CmdRequest>,
+    pub enabled: Option<bool>,
+}
+
+fn save_jobs(path: &Path, jobs: &[Job]) -> anyhow::Result<()> {
+    let data = serde_json::to_string_pretty(jobs)?;
+    crate::persist::write_atomic(path, data.as_bytes())?;
+    Ok(())
+}
+
+/// Scheduled bot commands, persisted in `schedules.json`. Owned by `BotManager`.
+pub struct Scheduler {
+    path: PathBuf,
+    jobs: Vec<Job>,
+}
+
+impl Scheduler {
+    /// Errors when `schedules.json` can't be read, or does not parse and can't be moved aside.
+    pub fn new(data_dir: &Path) -> anyhow::Result<Self> {
+        let path = data_dir.join("schedules.json");
+        let jobs = crate::persist::load_json(&path, "Scheduler")?.unwrap_or_default();
+        Ok(Self { path, jobs })
+    }
+
+    fn save(&self) {
+        if let Err(e) = save_jobs(&self.path, &self.jobs) {
+            println!("[Scheduler] Failed to save schedules.json: {e}");
+        }
+    }
+
+    pub fn list(&self) -> Vec<JobInfo> {
+        let now = Local::now();
+        self.jobs.iter()
+            .map(|job| JobInfo {
+                next_run: job.enabled
+                    .then(|| Cron::parse(&job.cron).ok()?.next_after(now))
+                    .flatten()
+                    .map(|t| t.timestamp()),
+                job: job.clone(),
+            })
+            .collect()
+    }
+
+    pub fn add(&mut self, bot_id: u32, cron: String, command: CmdRequest, enabled: bool) -> Result<Job, String> {
+        Cron::parse(&cron)?;
+        let job = Job {
+            id: uuid::Uuid::new_v4().to_string(),
+            bot_id,
+            cron,
+            command,
+            enabled,
+            last_run: None,
+            last_error: None,
+        };
+        self.jobs.push(job.clone());
+        self.save();
+        Ok(job)
+    }
+
+    /// Returns `Ok(None)` when no job has this id.
+    pub fn update(&mut self, id: &str, patch: JobPatch) -> Result<Option<Job>, String> {
+        if let Some(cron) = &patch.cron {
+            Cron::parse(cron)?;
+        }
+        let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) else {
+            return Ok(None);
+        };
+        if let Some(cron) = patch.cron {
+            job.cron = cron;
+        }
+        if let Some(command) = patch.command {
+            job.command = command;
+        }
+        if let Some(enabled) = patch.enabled {
+            job.enabled = enabled;
+        }
+        let job = job.clone();
+        self.save();
+        Ok(Some(job))
+    }
+
+    pub fn remove(&mut self, id: &str) -> bool {
+        let before = self.jobs.len();
+        self.jobs.retain(|j| j.id != id);
+        let removed = self.jobs.len() != before;
+        if removed {
+            self.save();
+        }
+        removed
+    }
+
+    /// Drop every job of a bot that was removed.
+    pub fn remove_for_bot(&mut self, bot_id: u32) {
+        let before = self.jobs.len();
+        self.jobs.retain(|j| j.bot_id != bot_id);
+        if self.jobs.len() != before {
+            self.save();
+        }
+    }
+
+    /// IDs of the jobs that run the stored script `name`.
+    pub fn jobs_running_script(&self, name: &str) -> Vec<String> {
+        self.jobs.iter()
+            .filter(|j| matches!(&j.command,
+                CmdRequest::RunStoredScript { name: n, .. } if n == name))
+            .map(|j| j.id.clone())
+            .collect()
+    }
+
+    /// Enabled jobs whose expression matches `now`'s minute and that have not fired in it yet.
+    pub fn due(&self, now: DateTime<Local>) -> Vec<Job> {
+        let minute_start = now.timestamp() - now.second() as i64;
+        let naive = now.naive_local();
+        self.jobs.iter()
+            .filter(|j| j.enabled)
+            .filter(|j| j.last_run.is_none_or(|t| (t as i64) < minute_start))
+            .filter(|j| Cron::parse(&j.cron).is_ok_and(|c| c.matches(&naive)))
+            .cloned()
+            .collect()
+    }
+
+    /// Record the result of running job `id` at unix time `at`.
+    pub fn record(&mut self, id: &str, at: u64, error: Option<String>) {
+        if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
+            job.last_run = Some(at);
+            job.last_error = error;
+        }
+        self.save();
+    }
+}
+
+// ── Ticker ────────────────────────────────────────────────────────────────────
+
+/// Wake at the start of every minute and run the jobs that are due.
+pub fn spawn(manager: SharedManager) {
+    tokio::spawn(async move {
+        loop {
+            let now = Local::now();
+            let into_minute = Duration::new(now.second() as u64, now.nanosecond().min(999_999_999));
+            tokio::time::sleep(Duration::from_secs(60).saturating_sub(into_minute)).await;
+            manager.lock().unwrap().run_due_jobs(Local::now());
+        }
+    });
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+    use chrono::NaiveDate;
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, mo, d).unwrap().and_hms_opt(h, mi, 0).unwrap()
+    }
+
+    #[test]
+    fn parses_fields_and_steps() {
+        let every_six = Cron::parse("0 */6 * * *").unwrap();
+        assert!(every_six.matches(&at(2026, 3, 1, 12, 0)));
+        assert!(!every_six.matches(&at(2026, 3, 1, 13, 0)));
+        assert!(!every_six.matches(&at(2026, 3, 1, 12, 1)));
+
+        let half_past = Cron::parse("30 * * * *").unwrap();
+        assert!(half_past.matches(&at(2026, 3, 1, 7, 30)));
+
+        assert_eq!(Cron::parse("@daily").unwrap(), Cron::parse("0 0 * * *").unwrap());
+        assert_eq!(Cron::parse("0 0 * * 7").unwrap(), Cron::parse("0 0 * * 0").unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        assert!(Cron::parse("* * * *").is_err());
+        assert!(Cron::parse("60 * * * *").is_err());
+        assert!(Cron::parse("*/0 * * * *").is_err());
+        assert!(Cron::parse("5-1 * * * *").is_err());
+        assert!(Cron::parse("0 0 0 * *").is_err());
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // 2026-03-02 is a Monday, 2026-03-15 a Sunday.
+        let cron = Cron::parse("0 2 15 * 1").unwrap();
+        assert!(cron.matches(&at(2026, 3, 2, 2, 0)));
+        assert!(cron.matches(&at(2026, 3, 15, 2, 0)));
+        assert!(!cron.matches(&at(2026, 3, 3, 2, 0)));
+    }
+
+    #[test]
+    fn day_fields_covering_every_value_are_unrestricted() {
+        // Only Mondays, however the day-of-month wildcard is written.
+        for expr in ["0 2 * * 1", "0 2 */1 * 1", "0 2 1-31 * 1"] {
+            let cron = Cron::parse(expr).unwrap();
+            assert!(cron.matches(&at(2026, 3, 2, 2, 0)), "{expr}");
+            assert!(!cron.matches(&at(2026, 3, 3, 2, 0)), "{expr}");
+        }
+        // Only the 15th, with day-of-week spelled as every day.
+        for expr in ["0 2 15 * */1", "0 2 15 * 0-7"] {
+            let cron = Cron::parse(expr).unwrap();
+            assert!(cron.matches(&at(2026, 3, 15, 2, 0)), "{expr}");
+            assert!(!cron.matches(&at(2026, 3, 16, 2, 0)), "{expr}");
+        }
+    }
+
+    #[test]
+    fn next_after_skips_to_matching_minute() {
+        let cron = Cron::parse("0 2 * * *").unwrap();
+        let from = Local.from_local_datetime(&at(2026, 3, 1, 12, 0)).earliest().unwrap();
+        let next = cron.next_after(from).unwrap().naive_local();
+        assert_eq!(next, at(2026, 3, 2, 2, 0));
+
+        assert!(Cron::parse("0 0 31 2 *").unwrap().next_after(from).is_none());
+    }
+
+    #[test]
+    fn finds_jobs_running_a_script() {
+        let dir = TestDir::new("scheduler");
+        let mut scheduler = Scheduler::new(&dir).unwrap();
+        let stored = scheduler.add(1, "0 2 * * *".into(),
+            CmdRequest::RunStoredScript { name: "farm.lua".into(), version: Some(2) }, true).unwrap();
+        let latest = scheduler.add(2, "0 3 * * *".into(),
+            CmdRequest::RunStoredScript { name: "farm.lua".into(), version: None }, false).unwrap();
+        scheduler.add(1, "0 4 * * *".into(),
+            CmdRequest::RunStoredScript { name: "other.lua".into(), version: None }, true).unwrap();
+        scheduler.add(1, "0 5 * * *".into(), CmdRequest::StopScript, true).unwrap();
+
+        assert_eq!(scheduler.jobs_running_script("farm.lua"), [stored.id, latest.id]);
+        assert!(scheduler.jobs_running_script("missing").is_empty());
+    }
+
+    #[test]
+    fn corrupt_schedules_are_moved_aside() {
+        let dir = TestDir::new("scheduler");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("schedules.json"), "[{\"id\": \"a").unwrap();
+        let mut scheduler = Scheduler::new(&dir).unwrap();
+        assert!(scheduler.list().is_empty());
+
+        scheduler.add(1, "0 2 * * *".into(), CmdRequest::StopScript, true).unwrap();
+        let backups: Vec<String> = std::fs::read_dir(&*dir).unwrap()
+            .filter_map(|e| e.unwrap().file_name().into_string().ok())
+            .filter(|name| name.starts_with("schedules.json.corrupt-"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(std::fs::read_to_string(dir.join(&backups[0])).unwrap(), "[{\"id\": \"a");
+        assert_eq!(Scheduler::new(&dir).unwrap().list().len(), 1);
+    }
+}
@@ -24,6 +24,16 @@ pub struct LocalSnapshot {
     pub mac:      String,
 }
 
+/// How a script run ended.
+pub enum ScriptOutcome {
+    /// The chunk ran to the end.
+    Finished,
+    /// Interrupted by `StopScript`, a newer `RunScript` or the bot stopping.
+    Stopped,
+    /// Setup or runtime error; carries the Lua error message.
+    Failed(String),
+}
+
 /// Commands the script thread sends to the bot thread.
 pub enum ScriptRequest {
     // Network / connection
@@ -0,0 +1,405 @@
+use anyhow::{Context, bail};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use utoipa::ToSchema;
+
+use crate::groups::normalize_tags;
+use crate::history::unix_now;
+
+// ── Script library ────────────────────────────────────────────────────────────
+//
+// Stored Lua scripts with their version history, kept under
+// `<data_dir>/scripts/library/<name>/`: `script.json` holds the metadata and every
+// saved version is an immutable `<version>.lua` next to it. Loose `.lua` files left
+// directly in `scripts/` by older versions are moved into the library on startup.
+
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+pub struct ScriptVersion {
+    /// Starts at 1 and goes up by one with every saved change.
+    pub version:    u32,
+    /// Unix timestamp.
+    pub created_at: u64,
+    /// Who saved it, e.g. `user:alice`.
+    pub saved_by:   Option<String>,
+    /// Size of the content in bytes.
+    pub size:       usize,
+}
+
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+pub struct Script {
+    pub name:        String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub author:      String,
+    #[serde(default)]
+    pub tags:        Vec<String>,
+    /// Unix timestamps.
+    pub created_at:  u64,
+    pub updated_at:  u64,
+    /// Oldest first; the last one is what runs when no version is asked for.
+    pub versions:    Vec<ScriptVersion>,
+}
+
+impl Script {
+    pub fn latest(&self) -> Option<u32> {
+        self.versions.last().map(|v| v.version)
+    }
+}
+
+/// A new script for `ScriptLibrary::create`.
+pub struct NewScript {
+    pub name:        String,
+    pub content:     String,
+    pub description: String,
+    pub author:      String,
+    pub tags:        Vec<String>,
+}
+
+/// Changes accepted by `ScriptLibrary::update`; missing fields are left as they are.
+#[derive(Deserialize, ToSchema)]
+pub struct ScriptPatch {
+    /// Saved as a new version unless it matches the latest one.
+    pub content:     Option<String>,
+    pub description: Option<String>,
+    pub author:      Option<String>,
+    pub tags:        Option<Vec<String>>,
+}
+
+/// Script names: 1–64 ASCII letters, digits, `-`, `_` or `.`, not starting with `.`.
+pub fn check_name(name: &str) -> anyhow::Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        bail!("invalid script name '{name}': use 1-64 letters, digits, '-', '_' or '.'");
+    }
+    Ok(())
+}
+
+fn check_content(content: &str) -> anyhow::Result<()> {
+    if content.trim().is_empty() {
+        bail!("script content must not be empty");
+    }
+    Ok(())
+}
+
+// ── Store ─────────────────────────────────────────────────────────────────────
+
+/// Reads straight from disk, so `run_stored_script` can resolve a script without
+/// the manager lock. Writes go through `BotManager::scripts`, which serialises them.
+#[derive(Clone)]
+pub struct ScriptLibrary {
+    dir: PathBuf,
+}
+
+impl ScriptLibrary {
+    pub fn new(scripts_dir: &Path) -> Self {
+        Self { dir: scripts_dir.join("library") }
+    }
+
+    fn meta_path(&self, name: &str) -> PathBuf {
+        self.dir.join(name).join("script.json")
+    }
+
+    fn version_path(&self, name: &str, version: u32) -> PathBuf {
+        self.dir.join(name).join(format!("{version}.lua"))
+    }
+
+    /// Written atomically so readers never see half a `script.json`.
+    fn save(&self, script: &Script) -> anyhow::Result<()> {
+        let data = serde_json::to_string_pretty(script)?;
+        crate::persist::write_atomic(&self.meta_path(&script.name), data.as_bytes())
+            .with_context(|| format!("cannot save script '{}'", script.name))
+    }
+
+    /// Store `content` as the next version of `script` and record it.
+    fn add_version(&self, script: &mut Script, content: &str, saved_by: Option<String>) -> anyhow::Result<()> {
+        let version = script.latest().unwrap_or(0) + 1;
+        let now = unix_now();
+        std::fs::write(self.version_path(&script.name, version), content)
+            .with_context(|| format!("cannot save script '{}'", script.name))?;
+        script.versions.push(ScriptVersion { version, created_at: now, saved_by, size: content.len() });
+        script.updated_at = now;
+        Ok(())
+    }
+
+    /// Every stored script, sorted by name.
+    pub fn list(&self) -> Vec<Script> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut scripts: Vec<Script> = entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| self.get(entry.file_name().to_str()?))
+            .collect();
+        scripts.sort_by(|a, b| a.name.cmp(&b.name));
+        scripts
+    }
+
+    pub fn get(&self, name: &str) -> Option<Script> {
+        check_name(name).ok()?;
+        let data = std::fs::read_to_string(self.meta_path(name)).ok()?;
+        match serde_json::from_str(&data) {
+            Ok(script) => Some(script),
+            Err(e) => {
+                println!("[Scripts] Failed to parse script.json of '{name}': {e}");
+                None
+            }
+        }
+    }
+
+    /// Returns `Ok(None)` when a script with this name already exists.
+    pub fn create(&self, new: NewScript, saved_by: Option<String>) -> anyhow::Result<Option<Script>> {
+        check_name(&new.name)?;
+        check_content(&new.content)?;
+        let tags = normalize_tags(new.tags).map_err(anyhow::Error::msg)?;
+        if self.meta_path(&new.name).exists() {
+            return Ok(None);
+        }
+        std::fs::create_dir_all(self.dir.join(&new.name))?;
+        let now = unix_now();
+        let mut script = Script {
+            name:        new.name,
+            description: new.description,
+            author:      new.author,
+            tags,
+            created_at:  now,
+            updated_at:  now,
+            versions:    Vec::new(),
+        };
+        let saved = self.add_version(&mut script, &new.content, saved_by).and_then(|()| self.save(&script));
+        if let Err(e) = saved {
+            // Without its `script.json` the directory is not a script; don't leave it behind.
+            let _ = std::fs::remove_dir_all(self.dir.join(&script.name));
+            return Err(e);
+        }
+        println!("[Scripts] Created '{}'", script.name);
+        Ok(Some(script))
+    }
+
+    /// Returns `Ok(None)` when no script has this name.
+    pub fn update(&self, name: &str, patch: ScriptPatch, saved_by: Option<String>) -> anyhow::Result<Option<Script>> {
+        if let Some(content) = &patch.content {
+            check_content(content)?;
+        }
+        let tags = patch.tags.map(normalize_tags).transpose().map_err(anyhow::Error::msg)?;
+        let Some(mut script) = self.get(name) else {
+            return Ok(None);
+        };
+        let mut added = None;
+        if let Some(content) = patch.content {
+            let unchanged = script.latest()
+                .and_then(|v| self.read(name, Some(v)).ok())
+                .is_some_and(|(_, latest)| latest == content);
+            if !unchanged {
+                self.add_version(&mut script, &content, saved_by)?;
+                added = script.latest();
+            }
+        }
+        if let Some(description) = patch.description {
+            script.description = description;
+        }
+        if let Some(author) = patch.author {
+            script.author = author;
+        }
+        if let Some(tags) = tags {
+            script.tags = tags;
+        }
+        script.updated_at = unix_now();
+        if let Err(e) = self.save(&script) {
+            // The new version is not listed anywhere, so drop its file.
+            if let Some(version) = added {
+                let _ = std::fs::remove_file(self.version_path(name, version));
+            }
+            return Err(e);
+        }
+        if let Some(version) = added {
+            println!("[Scripts] Saved '{name}' version {version}");
+        }
+        Ok(Some(script))
+    }
+
+    /// Delete a script with all of its versions.
+    pub fn remove(&self, name: &str) -> bool {
+        if self.get(name).is_none() {
+            return false;
+        }
+        match std::fs::remove_dir_all(self.dir.join(name)) {
+            Ok(()) => {
+                println!("[Scripts] Removed '{name}'");
+                true
+            }
+            Err(e) => {
+                println!("[Scripts] Failed to remove '{name}': {e}");
+                false
+            }
+        }
+    }
+
+    /// Move loose `.lua` files in `scripts/` into the library as version 1 of a script
+    /// with the same name, so they can be run with `run_stored_script`. A file whose name
+    /// is invalid or already taken is left where it is.
+    pub fn import_loose(&self) {
+        let Some(Ok(entries)) = self.dir.parent().map(std::fs::read_dir) else {
+            return;
+        };
+        for path in entries.filter_map(Result::ok).map(|e| e.path()) {
+            if !path.is_file() || path.extension().is_none_or(|ext| ext != "lua") {
+                continue;
+            }
+            let Some(name) = path.file_name().and_then(|n| n.to_str()).map(str::to_string) else {
+                continue;
+            };
+            let new = std::fs::read_to_string(&path).map_err(anyhow::Error::from).and_then(|content| {
+                self.create(NewScript {
+                    name:        name.clone(),
+                    content,
+                    description: format!("Imported from scripts/{name}"),
+                    author:      String::new(),
+                    tags:        Vec::new(),
+                }, None)
+            });
+            match new {
+                Ok(Some(_)) => {
+                    if let Err(e) = std::fs::remove_file(&path) {
+                        println!("[Scripts] Imported '{name}' but could not remove the loose file: {e}");
+                    }
+                }
+                Ok(None) => println!("[Scripts] Not importing scripts/{name}: a stored script has that name"),
+                Err(e) => println!("[Scripts] Not importing scripts/{name}: {e}"),
+            }
+        }
+    }
+
+    /// The content of `version` of a script, or of its latest version, with the version number.
+    pub fn read(&self, name: &str, version: Option<u32>) -> anyhow::Result<(u32, String)> {
+        check_name(name)?;
+        let Some(script) = self.get(name) else {
+            bail!("no stored script '{name}'");
+        };
+        let version = match version {
+            Some(v) if script.versions.iter().any(|sv| sv.version == v) => v,
+            Some(v) => bail!("script '{name}' has no version {v}"),
+            None => script.latest().with_context(|| format!("script '{name}' has no versions"))?,
+        };
+        let content = std::fs::read_to_string(self.version_path(name, version))
+            .with_context(|| format!("cannot read script '{name}' version {version}"))?;
+        Ok((version, content))
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    fn patch(content: Option<&str>, description: Option<&str>) -> ScriptPatch {
+        ScriptPatch {
+            content:     content.map(str::to_string),
+            description: description.map(str::to_string),
+            author:      None,
+            tags:        None,
+        }
+    }
+
+    fn new(name: &str, content: &str) -> NewScript {
+        NewScript {
+            name:        name.into(),
+            content:     content.into(),
+            description: "farms seeds".into(),
+            author:      "alice".into(),
+            tags:        vec!["farm".into(), "farm".into()],
+        }
+    }
+
+    /// A library holding `farm.lua` with versions `print(1)` and `print(2)`.
+    fn two_versions(dir: &TestDir) -> ScriptLibrary {
+        let library = ScriptLibrary::new(dir);
+        library.create(new("farm.lua", "print(1)"), Some("user:alice".into())).unwrap().unwrap();
+        library.update("farm.lua", patch(Some("print(2)"), None), Some("key:ci".into())).unwrap().unwrap();
+        library
+    }
+
+    #[test]
+    fn create_checks_name_and_content() {
+        let dir = TestDir::new("scripts");
+        let library = ScriptLibrary::new(&dir);
+        let script = library.create(new("farm.lua", "print(1)"), Some("user:alice".into())).unwrap().unwrap();
+        assert_eq!((script.latest(), script.tags.clone()), (Some(1), vec!["farm".to_string()]));
+        assert!(library.create(new("farm.lua", "print(2)"), None).unwrap().is_none());
+        assert!(library.create(new("../escape", "print(1)"), None).is_err());
+        assert!(library.create(new(".hidden", "print(1)"), None).is_err());
+        assert!(library.create(new("empty", "  "), None).is_err());
+    }
+
+    #[test]
+    fn update_adds_a_version_only_for_new_content() {
+        let dir = TestDir::new("scripts");
+        let library = two_versions(&dir);
+        let script = library.get("farm.lua").unwrap();
+        assert_eq!(script.latest(), Some(2));
+        assert_eq!(script.versions[1].saved_by.as_deref(), Some("key:ci"));
+        // Same content again only changes the metadata.
+        let script = library.update("farm.lua", patch(Some("print(2)"), Some("v2")), None).unwrap().unwrap();
+        assert_eq!((script.latest(), script.description.as_str()), (Some(2), "v2"));
+        assert!(library.update("missing", patch(None, None), None).unwrap().is_none());
+    }
+
+    #[test]
+    fn read_returns_the_latest_or_a_pinned_version() {
+        let dir = TestDir::new("scripts");
+        let library = two_versions(&dir);
+        assert_eq!(library.read("farm.lua", None).unwrap(), (2, "print(2)".to_string()));
+        assert_eq!(library.read("farm.lua", Some(1)).unwrap(), (1, "print(1)".to_string()));
+        assert!(library.read("farm.lua", Some(3)).is_err());
+        assert!(library.read("missing", None).is_err());
+    }
+
+    #[test]
+    fn list_and_remove() {
+        let dir = TestDir::new("scripts");
+        let library = two_versions(&dir);
+        library.create(new("a", "print(0)"), None).unwrap().unwrap();
+        let names: Vec<String> = library.list().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, ["a", "farm.lua"]);
+        assert!(library.remove("farm.lua"));
+        assert!(!library.remove("farm.lua"));
+        assert!(library.read("farm.lua", Some(1)).is_err());
+    }
+
+    #[test]
+    fn failed_saves_leave_no_version_files() {
+        let dir = TestDir::new("scripts");
+        let library = two_versions(&dir);
+        // A directory where `script.json.tmp` goes makes every save fail.
+        std::fs::create_dir_all(dir.join("library/farm.lua/script.json.tmp/x")).unwrap();
+        assert!(library.update("farm.lua", patch(Some("print(3)"), None), None).is_err());
+        assert!(!dir.join("library/farm.lua/3.lua").exists());
+        assert_eq!(library.read("farm.lua", None).unwrap().0, 2);
+
+        std::fs::create_dir_all(dir.join("library/new/script.json.tmp/x")).unwrap();
+        assert!(library.create(new("new", "print(1)"), None).is_err());
+        assert!(!dir.join("library/new").exists());
+    }
+
+    #[test]
+    fn loose_files_are_imported_once() {
+        let dir = TestDir::new("scripts");
+        let library = two_versions(&dir);
+        std::fs::write(dir.join("loose.lua"), "print('loose')").unwrap();
+        std::fs::write(dir.join("farm.lua"), "print('old')").unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a script").unwrap();
+        library.import_loose();
+
+        assert_eq!(library.read("loose.lua", None).unwrap(), (1, "print('loose')".to_string()));
+        assert!(!dir.join("loose.lua").exists());
+        // A name that is already taken keeps the stored script and the loose file.
+        assert_eq!(library.read("farm.lua", None).unwrap().0, 2);
+        assert!(dir.join("farm.lua").exists());
+        assert!(library.get("notes.txt").is_none());
+    }
+}
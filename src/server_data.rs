@@ -81,6 +81,21 @@ pub fn get_server_data(alternate: bool, login_info: &LoginInfo) -> Result<Server
     get_server_data_proxied(alternate, login_info, None)
 }
 
+/// Whether a `get_server_data_proxied` error came from reaching the server at all
+/// (proxy refused, connection dropped or timed out) rather than from its answer.
+pub fn is_connection_error(e: &(dyn std::error::Error + Send + Sync + 'static)) -> bool {
+    matches!(
+        e.downcast_ref::<ureq::Error>(),
+        Some(
+            ureq::Error::ConnectProxyFailed(_)
+                | ureq::Error::ConnectionFailed
+                | ureq::Error::HostNotFound
+                | ureq::Error::Io(_)
+                | ureq::Error::Timeout(_)
+        )
+    )
+}
+
 pub fn get_server_data_proxied(
     alternate: bool,
     login_info: &LoginInfo,
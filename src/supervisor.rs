@@ -0,0 +1,91 @@
+use std::any::Any;
+use std::time::Duration;
+
+use crate::bot::BotError;
+use crate::web::SharedManager;
+
+/// First restart delay; doubled after every consecutive crash.
+const BACKOFF_BASE_SECS: u64 = 5;
+/// Upper bound on the restart delay.
+const BACKOFF_MAX_SECS: u64 = 300;
+/// A bot that ran this long before crashing starts its backoff from scratch.
+pub const HEALTHY_RUN: Duration = Duration::from_secs(600);
+
+/// How a bot thread ended.
+pub enum ExitOutcome {
+    /// `Bot::run` returned (stop flag or an internal stop such as UPDATE REQUIRED).
+    Stopped,
+    /// Login or connection setup failed for good (wrong credentials, bad token, dead proxy).
+    Failed(BotError),
+    /// The thread panicked; carries the panic message.
+    Crashed(String),
+}
+
+/// Sent by every bot thread as it exits.
+pub struct BotExit {
+    pub bot_id:  u32,
+    /// Identifies the launch, so exits of a replaced thread are ignored.
+    pub run_id:  u64,
+    pub outcome: ExitOutcome,
+}
+
+pub type ExitTx = tokio::sync::mpsc::UnboundedSender<BotExit>;
+pub type ExitRx = tokio::sync::mpsc::UnboundedReceiver<BotExit>;
+
+/// Best-effort text of a `catch_unwind` payload.
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Delay before restart number `attempt` (1-based).
+pub fn backoff(attempt: u32) -> Duration {
+    let exp = attempt.saturating_sub(1).min(16);
+    Duration::from_secs((BACKOFF_BASE_SECS << exp).min(BACKOFF_MAX_SECS))
+}
+
+/// Watch bot thread exits and hand them to the manager, scheduling restarts it asks for.
+pub fn spawn(manager: SharedManager, mut exit_rx: ExitRx) {
+    tokio::spawn(async move {
+        while let Some(exit) = exit_rx.recv().await {
+            let (bot_id, run_id) = (exit.bot_id, exit.run_id);
+            let restart = manager.lock().unwrap().handle_exit(exit);
+            if let Some(delay) = restart {
+                let manager = manager.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    manager.lock().unwrap().relaunch(bot_id, run_id);
+                });
+            }
+        }
+    });
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_and_caps() {
+        assert_eq!(backoff(1), Duration::from_secs(5));
+        assert_eq!(backoff(2), Duration::from_secs(10));
+        assert_eq!(backoff(4), Duration::from_secs(40));
+        assert_eq!(backoff(7), Duration::from_secs(300));
+        assert_eq!(backoff(100), Duration::from_secs(300));
+    }
+
+    #[test]
+    fn panic_message_reads_str_and_string() {
+        let err = std::panic::catch_unwind(|| panic!("boom")).unwrap_err();
+        assert_eq!(panic_message(err.as_ref()), "boom");
+        let err = std::panic::catch_unwind(|| panic!("code {}", 7)).unwrap_err();
+        assert_eq!(panic_message(err.as_ref()), "code 7");
+    }
+}
@@ -0,0 +1,36 @@
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+// ── Test directories ──────────────────────────────────────────────────────────
+//
+// A fresh directory under the system temp dir for tests that write to a data
+// directory. It is deleted when the guard is dropped, also when the test panics.
+
+pub struct TestDir(PathBuf);
+
+impl TestDir {
+    /// `<temp>/mori-<name>-<uuid>`; created by whatever writes to it first.
+    pub fn new(name: &str) -> Self {
+        Self(std::env::temp_dir().join(format!("mori-{name}-{}", uuid::Uuid::new_v4())))
+    }
+}
+
+impl Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TestDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,140 @@
+use std::io::Write;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+use axum_server::tls_rustls::RustlsConfig;
+
+use crate::config::Config;
+
+// ── Certificate selection ─────────────────────────────────────────────────────
+//
+// `tls.cert_path` + `tls.key_path` when configured; otherwise a self-signed
+// certificate in `<data_dir>/tls/`, generated on first start and reused after.
+
+/// Names the self-signed certificate is valid for.
+fn self_signed_names(config: &Config) -> Vec<String> {
+    let listen_specific = config.listen_host.parse::<IpAddr>().map_or(true, |ip| !ip.is_unspecified());
+    let mut names: Vec<String> = Vec::new();
+    for name in ["localhost", "127.0.0.1", "::1"].into_iter()
+        .chain(listen_specific.then_some(config.listen_host.as_str()))
+        .chain(config.tls.hostnames.iter().map(String::as_str))
+    {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+fn generate_self_signed(cert_path: &Path, key_path: &Path, names: Vec<String>) -> anyhow::Result<()> {
+    let rcgen::CertifiedKey { cert, key_pair } = rcgen::generate_simple_self_signed(names)?;
+    if let Some(parent) = cert_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // The key is created owner-only rather than narrowed after writing, so it is
+    // never readable by others; a leftover key without its certificate is replaced.
+    match std::fs::remove_file(key_path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(key_path)?.write_all(key_pair.serialize_pem().as_bytes())?;
+    std::fs::write(cert_path, cert.pem())?;
+    Ok(())
+}
+
+/// Certificate and key files to serve, generating the self-signed pair if needed.
+fn resolve(config: &Config) -> anyhow::Result<(PathBuf, PathBuf)> {
+    match (&config.tls.cert_path, &config.tls.key_path) {
+        (Some(cert), Some(key)) => Ok((cert.clone(), key.clone())),
+        (None, None) => {
+            let dir = config.data_dir.join("tls");
+            let (cert, key) = (dir.join("cert.pem"), dir.join("key.pem"));
+            if !cert.exists() || !key.exists() {
+                let names = self_signed_names(config);
+                generate_self_signed(&cert, &key, names.clone())?;
+                println!("[TLS] Generated self-signed certificate for {}", names.join(", "));
+            }
+            Ok((cert, key))
+        }
+        _ => anyhow::bail!("tls.cert_path and tls.key_path must be set together"),
+    }
+}
+
+/// rustls server config for `web::serve`, or None when TLS is disabled.
+pub async fn load(config: &Config) -> anyhow::Result<Option<RustlsConfig>> {
+    if !config.tls.enabled {
+        return Ok(None);
+    }
+    // ureq pulls in rustls too; pick the provider explicitly so the builder never has to guess.
+    let _ = rustls::crypto::ring::default_provider().install_default();
+    let (cert, key) = resolve(config)?;
+    let rustls = RustlsConfig::from_pem_file(&cert, &key)
+        .await
+        .map_err(|e| anyhow::anyhow!("cannot load {} / {}: {e}", cert.display(), key.display()))?;
+    println!("[TLS] Serving HTTPS with {}", cert.display());
+    Ok(Some(rustls))
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    fn tls_config(dir: &TestDir) -> Config {
+        let mut config = Config { data_dir: dir.to_path_buf(), ..Config::default() };
+        config.tls.enabled = true;
+        config.tls.hostnames = vec!["mori.lan".into()];
+        config
+    }
+
+    #[test]
+    fn self_signed_names_skip_the_wildcard_address() {
+        let dir = TestDir::new("tls");
+        let names = self_signed_names(&tls_config(&dir));
+        assert!(!names.contains(&"0.0.0.0".to_string()));
+        assert!(names.contains(&"mori.lan".to_string()));
+    }
+
+    #[test]
+    fn self_signed_is_generated_once() {
+        let dir = TestDir::new("tls");
+        let config = tls_config(&dir);
+        let (cert, key) = resolve(&config).unwrap();
+        let pem = std::fs::read_to_string(&cert).unwrap();
+        assert!(pem.starts_with("-----BEGIN CERTIFICATE-----"));
+        assert!(std::fs::read_to_string(&key).unwrap().contains("PRIVATE KEY"));
+        resolve(&config).unwrap();
+        assert_eq!(std::fs::read_to_string(&cert).unwrap(), pem);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn self_signed_key_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = TestDir::new("tls");
+        let config = tls_config(&dir);
+        std::fs::create_dir_all(dir.join("tls")).unwrap();
+        std::fs::write(dir.join("tls/key.pem"), "stale").unwrap();
+
+        let (_, key) = resolve(&config).unwrap();
+        assert_eq!(std::fs::metadata(&key).unwrap().permissions().mode() & 0o777, 0o600);
+        assert!(std::fs::read_to_string(&key).unwrap().contains("PRIVATE KEY"));
+    }
+
+    #[test]
+    fn cert_path_without_key_path_is_rejected() {
+        let dir = TestDir::new("tls");
+        let mut config = tls_config(&dir);
+        config.tls.cert_path = Some(dir.join("cert.pem"));
+        assert!(resolve(&config).is_err());
+    }
+}
@@ -0,0 +1,381 @@
+use argon2::Argon2;
+use chacha20poly1305::{
+    ChaCha20Poly1305, Key, Nonce,
+    aead::{Aead, AeadCore, KeyInit},
+};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+// ── On-disk format ────────────────────────────────────────────────────────────
+//
+// Secrets are encrypted with a random data key. The data key itself is stored
+// wrapped by a key derived from the master password (Argon2id + `kdf_salt`),
+// so the password can change without re-encrypting every secret.
+
+/// A value sealed with a key; also used for secrets stored outside `vault.json`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Sealed {
+    /// Hex-encoded 96-bit nonce.
+    nonce:      String,
+    /// Hex-encoded ciphertext including the Poly1305 tag.
+    ciphertext: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct VaultFile {
+    /// Hex-encoded Argon2 salt for the key-encryption key.
+    kdf_salt:    String,
+    /// Data key, sealed with the password-derived key.
+    wrapped_key: Sealed,
+    /// The admin whose password that is. Vaults written by older versions have none
+    /// until they are next unlocked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    owner:       Option<String>,
+    /// Bot secrets (password or ltoken string), sealed with the data key.
+    #[serde(default)]
+    secrets:     BTreeMap<u32, Sealed>,
+}
+
+/// `Ok(None)` only when there is no `vault.json` yet.
+fn load_file(path: &Path) -> anyhow::Result<Option<VaultFile>> {
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => anyhow::bail!("cannot read vault.json: {e}"),
+    };
+    serde_json::from_str(&data)
+        .map(Some)
+        .map_err(|e| anyhow::anyhow!("vault.json does not parse: {e}"))
+}
+
+fn save_file(path: &Path, file: &VaultFile) -> anyhow::Result<()> {
+    let data = serde_json::to_string_pretty(file)?;
+    crate::persist::write_atomic(path, data.as_bytes())?;
+    Ok(())
+}
+
+// ── Crypto helpers ────────────────────────────────────────────────────────────
+
+fn derive_key(password: &str, salt: &[u8]) -> anyhow::Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+fn seal(key: &[u8; 32], plaintext: &[u8]) -> anyhow::Result<Sealed> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| anyhow::anyhow!("encryption failed"))?;
+    Ok(Sealed {
+        nonce:      hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+    })
+}
+
+fn open(key: &[u8; 32], sealed: &Sealed) -> anyhow::Result<Vec<u8>> {
+    decrypt(key, sealed)?.ok_or_else(|| anyhow::anyhow!("decryption failed (wrong password or corrupted vault)"))
+}
+
+/// `Ok(None)` when `key` does not open `sealed`; `Err` when `sealed` is malformed.
+fn decrypt(key: &[u8; 32], sealed: &Sealed) -> anyhow::Result<Option<Vec<u8>>> {
+    let nonce = hex::decode(&sealed.nonce)?;
+    if nonce.len() != 12 {
+        anyhow::bail!("invalid nonce length");
+    }
+    let ciphertext = hex::decode(&sealed.ciphertext)?;
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    Ok(cipher.decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice()).ok())
+}
+
+/// Returned by `Vault::unlock` when the password does not open the vault, as opposed
+/// to the vault file being unreadable or unwritable.
+#[derive(Debug)]
+pub struct WrongPassword;
+
+impl std::fmt::Display for WrongPassword {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("wrong master password")
+    }
+}
+
+impl std::error::Error for WrongPassword {}
+
+// ── Vault (shared across the manager and web layer) ───────────────────────────
+
+/// Encrypted store for bot passwords and ltoken strings, keyed by bot ID.
+#[derive(Clone)]
+pub struct Vault {
+    inner: Arc<RwLock<VaultInner>>,
+    /// `vault.json` inside the data directory.
+    path:  PathBuf,
+}
+
+struct VaultInner {
+    /// None until the vault is first created.
+    file:  Option<VaultFile>,
+    /// Data key; only held in memory once unlocked.
+    key:   Option<[u8; 32]>,
+    /// Why an existing `vault.json` could not be loaded. The vault then refuses to
+    /// unlock, so it never replaces the file with an empty one.
+    error: Option<String>,
+}
+
+impl Vault {
+    pub fn new(data_dir: &Path) -> Self {
+        let path = data_dir.join("vault.json");
+        let (file, error) = match load_file(&path) {
+            Ok(file) => (file, None),
+            Err(e) => {
+                println!("[Vault] {e} — the vault stays locked until the file is fixed or restored");
+                (None, Some(e.to_string()))
+            }
+        };
+        Self {
+            inner: Arc::new(RwLock::new(VaultInner { file, key: None, error })),
+            path,
+        }
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.inner.read().unwrap().key.is_some()
+    }
+
+    /// Username of the admin whose password opens the vault, once known.
+    pub fn owner(&self) -> Option<String> {
+        self.inner.read().unwrap().file.as_ref().and_then(|f| f.owner.clone())
+    }
+
+    /// Derive the key from `username`'s password and unlock the vault, creating an
+    /// empty one owned by them on first use. Argon2 runs without holding the lock.
+    pub fn unlock(&self, username: &str, password: &str) -> anyhow::Result<()> {
+        let existing = {
+            let inner = self.inner.read().unwrap();
+            if inner.key.is_some() {
+                return Ok(());
+            }
+            if let Some(error) = &inner.error {
+                anyhow::bail!("{error}");
+            }
+            inner.file.clone()
+        };
+        let salt_before = existing.as_ref().map(|f| f.kdf_salt.clone());
+
+        let (file, data_key, changed) = match existing {
+            Some(mut file) => {
+                if let Some(owner) = file.owner.as_deref().filter(|o| *o != username) {
+                    anyhow::bail!("only {owner}'s password opens the vault");
+                }
+                let salt = hex::decode(&file.kdf_salt)?;
+                let kek = derive_key(password, &salt)?;
+                let data_key: [u8; 32] = decrypt(&kek, &file.wrapped_key)?
+                    .ok_or(WrongPassword)?
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("invalid data key length"))?;
+                let claimed = file.owner.is_none();
+                file.owner = Some(username.to_string());
+                (file, data_key, claimed)
+            }
+            None => {
+                let mut salt = [0u8; 16];
+                OsRng.fill_bytes(&mut salt);
+                let mut data_key = [0u8; 32];
+                OsRng.fill_bytes(&mut data_key);
+
+                let kek = derive_key(password, &salt)?;
+                let file = VaultFile {
+                    kdf_salt:    hex::encode(salt),
+                    wrapped_key: seal(&kek, &data_key)?,
+                    owner:       Some(username.to_string()),
+                    secrets:     BTreeMap::new(),
+                };
+                (file, data_key, true)
+            }
+        };
+
+        let mut inner = self.inner.write().unwrap();
+        if inner.key.is_some() {
+            return Ok(());
+        }
+        if changed {
+            // A password change in the meantime must not be undone by this save.
+            if inner.file.as_ref().map(|f| &f.kdf_salt) != salt_before.as_ref() {
+                anyhow::bail!("the vault changed while unlocking it; try again");
+            }
+            save_file(&self.path, &file)?;
+        }
+        inner.file = Some(file);
+        inner.key = Some(data_key);
+        Ok(())
+    }
+
+    /// Re-wrap the data key for a new master password. Returns false, leaving the
+    /// vault untouched, when there is no vault or `old_password` does not open it.
+    ///
+    /// Both keys are derived without holding the lock, so `put` and `get` carry on
+    /// meanwhile; the file is only swapped if the wrapped key is still the one read.
+    pub fn change_password(&self, old_password: &str, new_password: &str) -> anyhow::Result<bool> {
+        let (salt, wrapped_key) = {
+            let inner = self.inner.read().unwrap();
+            let Some(file) = inner.file.as_ref() else {
+                return Ok(false);
+            };
+            (hex::decode(&file.kdf_salt)?, file.wrapped_key.clone())
+        };
+        let Ok(data_key) = open(&derive_key(old_password, &salt)?, &wrapped_key) else {
+            return Ok(false);
+        };
+
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        let kek = derive_key(new_password, &salt)?;
+        let rewrapped = seal(&kek, &data_key)?;
+
+        let mut inner = self.inner.write().unwrap();
+        let Some(mut file) = inner.file.clone() else {
+            return Ok(false);
+        };
+        if file.wrapped_key != wrapped_key {
+            anyhow::bail!("the vault password was changed concurrently");
+        }
+        file.kdf_salt = hex::encode(salt);
+        file.wrapped_key = rewrapped;
+        save_file(&self.path, &file)?;
+        inner.file = Some(file);
+        Ok(true)
+    }
+
+    /// Encrypt and store the secret for `bot_id`.
+    pub fn put(&self, bot_id: u32, secret: &str) -> anyhow::Result<()> {
+        let mut inner = self.inner.write().unwrap();
+        let key = inner.key.ok_or_else(|| anyhow::anyhow!("vault is locked"))?;
+        let sealed = seal(&key, secret.as_bytes())?;
+        let mut file = inner.file.clone().ok_or_else(|| anyhow::anyhow!("vault is locked"))?;
+        file.secrets.insert(bot_id, sealed);
+        save_file(&self.path, &file)?;
+        inner.file = Some(file);
+        Ok(())
+    }
+
+    /// Seal a secret that is stored elsewhere (pooled proxy passwords) with the data key.
+    pub fn seal_value(&self, plain: &str) -> anyhow::Result<Sealed> {
+        let key = self.inner.read().unwrap().key.ok_or_else(|| anyhow::anyhow!("vault is locked"))?;
+        seal(&key, plain.as_bytes())
+    }
+
+    /// Open a value sealed by `seal_value`.
+    pub fn open_value(&self, sealed: &Sealed) -> anyhow::Result<String> {
+        let key = self.inner.read().unwrap().key.ok_or_else(|| anyhow::anyhow!("vault is locked"))?;
+        Ok(String::from_utf8(open(&key, sealed)?)?)
+    }
+
+    /// Decrypt the secret stored for `bot_id`.
+    pub fn get(&self, bot_id: u32) -> anyhow::Result<Option<String>> {
+        let inner = self.inner.read().unwrap();
+        let key = inner.key.ok_or_else(|| anyhow::anyhow!("vault is locked"))?;
+        let Some(sealed) = inner.file.as_ref().and_then(|f| f.secrets.get(&bot_id)) else {
+            return Ok(None);
+        };
+        let plain = open(&key, sealed)?;
+        Ok(Some(String::from_utf8(plain)?))
+    }
+
+    pub fn remove(&self, bot_id: u32) -> anyhow::Result<()> {
+        let mut inner = self.inner.write().unwrap();
+        let Some(file) = inner.file.as_mut() else {
+            return Ok(());
+        };
+        if file.secrets.remove(&bot_id).is_some() {
+            save_file(&self.path, file)?;
+        }
+        Ok(())
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn seal_open_roundtrip() {
+        let key = derive_key("hunter2", b"0123456789abcdef").unwrap();
+        let sealed = seal(&key, b"secret|rid|mac|wk").unwrap();
+        assert_eq!(open(&key, &sealed).unwrap(), b"secret|rid|mac|wk");
+    }
+
+    #[test]
+    fn open_rejects_wrong_password() {
+        let key = derive_key("hunter2", b"0123456789abcdef").unwrap();
+        let other = derive_key("hunter3", b"0123456789abcdef").unwrap();
+        let sealed = seal(&key, b"secret").unwrap();
+        assert!(open(&other, &sealed).is_err());
+    }
+
+    #[test]
+    fn change_password_keeps_secrets() {
+        let dir = TestDir::new("vault");
+        let vault = Vault::new(&dir);
+        vault.unlock("admin", "hunter2").unwrap();
+        vault.put(1, "secret").unwrap();
+        assert!(!vault.change_password("wrong", "hunter3").unwrap());
+        assert!(vault.change_password("hunter2", "hunter3").unwrap());
+
+        let reopened = Vault::new(&dir);
+        assert!(reopened.unlock("admin", "hunter2").unwrap_err().is::<WrongPassword>());
+        reopened.unlock("admin", "hunter3").unwrap();
+        assert_eq!(reopened.get(1).unwrap().as_deref(), Some("secret"));
+    }
+
+    #[test]
+    fn only_the_owner_unlocks() {
+        let dir = TestDir::new("vault");
+        let vault = Vault::new(&dir);
+        vault.unlock("admin", "hunter2").unwrap();
+        assert_eq!(vault.owner().as_deref(), Some("admin"));
+
+        let reopened = Vault::new(&dir);
+        assert!(reopened.unlock("root", "hunter2").is_err());
+        assert!(!reopened.is_unlocked());
+        reopened.unlock("admin", "hunter2").unwrap();
+    }
+
+    #[test]
+    fn older_vault_is_claimed_by_its_first_unlock() {
+        let dir = TestDir::new("vault");
+        Vault::new(&dir).unlock("admin", "hunter2").unwrap();
+        let path = dir.join("vault.json");
+        let mut file: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        file.as_object_mut().unwrap().remove("owner");
+        std::fs::write(&path, file.to_string()).unwrap();
+
+        let vault = Vault::new(&dir);
+        assert_eq!(vault.owner(), None);
+        vault.unlock("root", "hunter2").unwrap();
+        assert_eq!(Vault::new(&dir).owner().as_deref(), Some("root"));
+    }
+
+    #[test]
+    fn unreadable_vault_is_never_replaced() {
+        let dir = TestDir::new("vault");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("vault.json"), "{\"kdf_salt\": \"00").unwrap();
+
+        let vault = Vault::new(&dir);
+        assert!(!vault.unlock("admin", "hunter2").unwrap_err().is::<WrongPassword>());
+        assert!(!vault.is_unlocked());
+        assert!(vault.put(1, "secret").is_err());
+        vault.remove(1).unwrap();
+        assert_eq!(std::fs::read_to_string(dir.join("vault.json")).unwrap(), "{\"kdf_salt\": \"00");
+    }
+}
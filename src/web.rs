@@ -1,35 +1,70 @@
 use axum::{
     Router,
     body::Body,
-    extract::{Path, Query, Request, State, WebSocketUpgrade},
-    extract::ws::{Message, WebSocket},
-    http::{StatusCode, Method},
+    extract::{ConnectInfo, Extension, Path, Query, Request, State, WebSocketUpgrade},
+    extract::ws::{CloseFrame, Message, WebSocket, close_code},
+    http::{header, StatusCode, Method},
     middleware::{self, Next},
     response::{IntoResponse, Response},
-    routing::{delete, get, post},
+    routing::get,
     Json,
 };
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::services::{ServeDir, ServeFile};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::sync::{Arc, Mutex};
-use std::net::{ToSocketAddrs, SocketAddr};
+use std::net::{IpAddr, ToSocketAddrs, SocketAddr};
+use std::time::Duration;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use utoipa_axum::{router::OpenApiRouter, routes};
 
-use crate::auth::AuthState;
+use crate::api_keys::{ApiKey, ApiKeyStore, Scope};
+use crate::audit::{AuditEntry, AuditLog, AuditNote, AuditPage};
+use crate::auth::{self, AuthState, DEFAULT_ADMIN, Role, Session, SessionInfo, UserInfo};
 use crate::bot::Socks5Config;
-use crate::bot_manager::{BotInfo, BotManager};
-use crate::bot_state::{BotCommand, BotDelays, BotState};
-use crate::events::WsTx;
+use crate::bot_manager::{BotInfo, BotManager, Delivery};
+use crate::bot_state::BotState;
+use crate::command_tracker::{CmdStatus, CommandRecord};
+use crate::commands::CmdRequest;
+use crate::config::Config;
+use crate::console_log::{ConsoleCategory, ConsolePage, ConsoleQuery};
+use crate::groups::{GroupInfo, GroupPatch};
+use crate::events::{SnapshotReason, Subscription, WsClientMessage, WsEvent, WsRequest, WsTx};
+use crate::history;
+use crate::import::{self, ImportError};
 use crate::items::ItemInfo;
+use crate::login_guard::{Attempt, LoginGuard};
+use crate::metrics;
+use crate::proxy_pool::PoolInfo;
 use crate::proxy_test::{ProxyTestResult, run_proxy_test};
+use crate::roster::{BotConfig, BotLogin};
+use crate::scheduler::{JobInfo, JobPatch};
+use crate::scripts::{NewScript, Script, ScriptPatch, ScriptVersion};
+use crate::vault::WrongPassword;
 
 pub type SharedManager = Arc<Mutex<BotManager>>;
 
 #[derive(Clone)]
 pub struct AppState {
-    pub manager: SharedManager,
-    pub ws_tx:   WsTx,
-    pub auth:    AuthState,
+    pub manager:     SharedManager,
+    pub ws_tx:       WsTx,
+    pub auth:        AuthState,
+    pub config:      Arc<Config>,
+    pub audit:       AuditLog,
+    pub api_keys:    ApiKeyStore,
+    pub login_guard: LoginGuard,
+}
+
+/// Body of error responses.
+#[derive(Serialize, ToSchema)]
+pub struct ErrorBody {
+    pub error: String,
+}
+
+/// `status` with an `ErrorBody`.
+fn error_response(status: StatusCode, error: impl std::fmt::Display) -> Response {
+    (status, Json(ErrorBody { error: error.to_string() })).into_response()
 }
 
 // ── Auth middleware ────────────────────────────────────────────────────────────
@@ -40,14 +75,67 @@ fn extract_bearer(req: &Request) -> Option<String> {
     hdr.strip_prefix("Bearer ").map(str::to_owned)
 }
 
+/// Compare two secrets without an early exit, so the time taken doesn't reveal
+/// how much of `given` matched. Hashing first evens out the lengths.
+fn tokens_match(given: &str, expected: &str) -> bool {
+    let given = Sha256::digest(given.as_bytes());
+    let expected = Sha256::digest(expected.as_bytes());
+    given.iter().zip(expected.iter()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Who a request or WebSocket belongs to: a dashboard session or an API key.
+#[derive(Clone)]
+enum Caller {
+    User(Session),
+    Key(ApiKey),
+}
+
+impl Caller {
+    /// Look a session token or API key up; `None` once it has expired or been revoked.
+    fn resolve(s: &AppState, token: &str) -> Option<Caller> {
+        if let Some(session) = s.auth.session(token) {
+            return Some(Caller::User(session));
+        }
+        s.api_keys.lookup(token).map(Caller::Key)
+    }
+
+    /// `user:<name>` or `key:<name>`, as recorded in the audit log.
+    fn identity(&self) -> String {
+        match self {
+            Caller::User(session) => format!("user:{}", session.username),
+            Caller::Key(key)      => format!("key:{}", key.name),
+        }
+    }
+
+    /// The username, or the key's name.
+    fn name(&self) -> &str {
+        match self {
+            Caller::User(session) => &session.username,
+            Caller::Key(key)      => &key.name,
+        }
+    }
+
+    /// Whether the caller's role or key scope allows `method` on `path`.
+    fn permits(&self, method: &Method, path: &str) -> bool {
+        match self {
+            Caller::User(session) => session.role.permits(method, path),
+            Caller::Key(key)      => key.scope.permits(method, path),
+        }
+    }
+}
+
+/// Resolve the caller, check their role or key scope against the route and attach
+/// the `Caller` (and the `Session` of a user) as request extensions.
 async fn auth_middleware(
     State(s): State<AppState>,
-    req: Request,
+    mut req: Request,
     next: Next,
 ) -> Response {
     let path = req.uri().path();
-    if path.starts_with("/auth/")
+    let public_auth = matches!(path, "/auth/status" | "/auth/setup" | "/auth/login" | "/auth/logout");
+    if public_auth
         || path == "/"
+        || path == "/openapi.json"
         || path.starts_with("/assets/")
         || path.starts_with("/growtopia-cdn/")
     {
@@ -62,84 +150,723 @@ async fn auth_middleware(
                     .find(|(k, _)| k == "token")
                     .map(|(_, v)| v.into_owned())
             });
-        if let Some(t) = token {
-            if s.auth.validate_token(&t) {
-                return next.run(req).await;
-            }
+        // Every role and key scope may watch the event stream.
+        if token.is_some_and(|t| Caller::resolve(&s, &t).is_some()) {
+            return next.run(req).await;
         }
         return StatusCode::UNAUTHORIZED.into_response();
     }
 
     // All other routes need a valid Bearer token.
-    if let Some(token) = extract_bearer(&req) {
-        if s.auth.validate_token(&token) {
-            return next.run(req).await;
+    let Some(token) = extract_bearer(&req) else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    // Prometheus scrapes with the static token from the config.
+    let metrics_token = s.config.metrics_token.as_deref().filter(|t| !t.is_empty());
+    if path == "/metrics" && metrics_token.is_some_and(|expected| tokens_match(&token, expected)) {
+        return next.run(req).await;
+    }
+    // Automation clients authenticate with an API key instead of a session.
+    let Some(caller) = Caller::resolve(&s, &token) else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    if !caller.permits(req.method(), path) {
+        let error = match caller {
+            Caller::User(_) => "insufficient role",
+            Caller::Key(_)  => "insufficient scope",
+        };
+        return error_response(StatusCode::FORBIDDEN, error);
+    }
+    if let Caller::User(session) = &caller {
+        req.extensions_mut().insert(session.clone());
+    }
+    req.extensions_mut().insert(caller);
+    next.run(req).await
+}
+
+// ── Audit middleware ──────────────────────────────────────────────────────────
+
+/// Largest request body buffered to record a `CmdRequest` (same as axum's `Json` limit).
+const AUDIT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Append every non-GET request to the audit log once it has been answered.
+async fn audit_middleware(
+    State(s): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return next.run(req).await;
+    }
+
+    let method = req.method().to_string();
+    let path = req.uri().path().to_string();
+    let caller = extract_bearer(&req).and_then(|t| Caller::resolve(&s, &t));
+    let identity = caller.as_ref().map(Caller::identity);
+
+    // Only command bodies are recorded; others may carry passwords or ltokens. They are
+    // buffered only for callers allowed to send them, since `auth_middleware` runs
+    // after this and anonymous clients must not make the server hold large bodies.
+    let may_send = caller.is_some_and(|c| c.permits(req.method(), &path));
+    let (req, command) = if may_send && crate::commands::is_cmd_path(&path) {
+        let (parts, body) = req.into_parts();
+        let Ok(bytes) = axum::body::to_bytes(body, AUDIT_BODY_LIMIT).await else {
+            return StatusCode::PAYLOAD_TOO_LARGE.into_response();
+        };
+        let command = serde_json::from_slice(&bytes).ok();
+        (Request::from_parts(parts, Body::from(bytes)), command)
+    } else {
+        (req, None)
+    };
+
+    let response = next.run(req).await;
+    let entry = AuditEntry {
+        ts: crate::history::unix_now(),
+        identity,
+        ip: addr.ip().to_string(),
+        method,
+        path,
+        command,
+        status: response.status().as_u16(),
+        note: response.extensions().get::<AuditNote>().map(|n| n.0.clone()),
+    };
+    let audit = s.audit.clone();
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = audit.append(&entry) {
+            println!("[Audit] Failed to write audit.jsonl: {e}");
         }
+    });
+    response
+}
+
+// ── Login throttling ──────────────────────────────────────────────────────────
+
+/// Body of `429` responses from the password endpoints.
+#[derive(Serialize, ToSchema)]
+struct LockedOutBody {
+    error:            String,
+    retry_after_secs: u64,
+}
+
+/// `429` with `Retry-After` while the caller is locked out.
+fn locked_out(retry: Duration) -> Response {
+    let secs = (retry.as_secs_f64().ceil() as u64).max(1);
+    let body = LockedOutBody { error: "too many failed attempts".into(), retry_after_secs: secs };
+    (StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, secs.to_string())], Json(body)).into_response()
+}
+
+/// Count a failed password check; a lockout it triggers is noted in the audit log.
+fn password_failed(attempt: Attempt, message: &str) -> Response {
+    let mut response = error_response(StatusCode::UNAUTHORIZED, message);
+    if let Some(lockout) = attempt.failed() {
+        response.extensions_mut().insert(AuditNote(lockout.to_string()));
     }
-    StatusCode::UNAUTHORIZED.into_response()
+    response
+}
+
+/// Check `username`'s password on a blocking thread; Argon2 is slow on purpose.
+async fn verify_password(s: &AppState, username: &str, password: &str) -> Option<Role> {
+    let (auth, username, password) = (s.auth.clone(), username.to_string(), password.to_string());
+    tokio::task::spawn_blocking(move || auth.verify(&username, &password)).await.ok().flatten()
 }
 
 // ── Auth handlers ─────────────────────────────────────────────────────────────
 
-/// GET /auth/status  →  { registered: bool }
-async fn auth_status(State(s): State<AppState>) -> Json<serde_json::Value> {
-    Json(serde_json::json!({ "registered": s.auth.is_registered() }))
+#[derive(Serialize, ToSchema)]
+struct AuthStatus {
+    /// An admin account exists; `/auth/setup` is closed.
+    registered:     bool,
+    vault_unlocked: bool,
 }
 
-#[derive(Deserialize)]
+/// GET /auth/status  →  { registered: bool, vault_unlocked: bool }
+#[utoipa::path(
+    get, path = "/auth/status", tag = "auth",
+    security(()),
+    responses(
+        (status = 200, description = "`registered` and `vault_unlocked` flags", body = AuthStatus),
+    ),
+)]
+async fn auth_status(State(s): State<AppState>) -> Json<AuthStatus> {
+    let vault_unlocked = s.manager.lock().unwrap().vault.is_unlocked();
+    Json(AuthStatus { registered: s.auth.is_registered(), vault_unlocked })
+}
+
+/// Unlock the credential vault with `username`'s password and replay the roster.
+/// Only the vault's owner holds its key, so for any other admin this does nothing.
+/// Argon2 runs on a blocking thread, without the manager lock.
+async fn unlock_vault(s: &AppState, username: &str, password: &str) -> anyhow::Result<()> {
+    let vault = s.manager.lock().unwrap().vault.clone();
+    if vault.is_unlocked() || vault.owner().is_some_and(|owner| owner != username) {
+        return Ok(());
+    }
+    let (username, password) = (username.to_string(), password.to_string());
+    tokio::task::spawn_blocking(move || vault.unlock(&username, &password)).await??;
+    s.manager.lock().unwrap().restore();
+    Ok(())
+}
+
+/// The vault owner's password is the only one that opens the vault, so they can't be
+/// removed or demoted.
+fn vault_owner_conflict(s: &AppState, username: &str) -> Option<Response> {
+    let owner = s.manager.lock().unwrap().vault.owner();
+    (owner.as_deref() == Some(username)).then(|| error_response(
+        StatusCode::CONFLICT,
+        format!("'{username}' holds the vault key and can't be removed or demoted"),
+    ))
+}
+
+fn default_admin() -> String {
+    DEFAULT_ADMIN.to_string()
+}
+
+#[derive(Deserialize, ToSchema)]
 struct SetupRequest {
+    #[serde(default = "default_admin")]
+    username: String,
     password: String,
 }
 
-/// POST /auth/setup  →  registers the single user (only works once)
+/// POST /auth/setup  →  registers the first admin (only works once)
+#[utoipa::path(
+    post, path = "/auth/setup", tag = "auth",
+    request_body = SetupRequest,
+    security(()),
+    responses(
+        (status = 204, description = "First admin registered"),
+        (status = 400, description = "Invalid username or empty password", body = ErrorBody),
+        (status = 409, description = "An admin is already registered", body = ErrorBody),
+        (status = 500, description = "Could not save the user", body = ErrorBody),
+    ),
+)]
 async fn auth_setup(
     State(s): State<AppState>,
     Json(req): Json<SetupRequest>,
 ) -> Response {
-    if s.auth.is_registered() {
-        return (StatusCode::CONFLICT, Json(serde_json::json!({ "error": "already registered" }))).into_response();
-    }
-    match s.auth.register(&req.password) {
-        Ok(_) => StatusCode::NO_CONTENT.into_response(),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({ "error": e.to_string() })),
-        ).into_response(),
+    if let Err(e) = auth::check_username(&req.username) {
+        return error_response(StatusCode::BAD_REQUEST, e);
+    }
+    if req.password.is_empty() {
+        return error_response(StatusCode::BAD_REQUEST, "password is empty");
+    }
+    let (auth, username, password) = (s.auth.clone(), req.username.clone(), req.password.clone());
+    let registered = tokio::task::spawn_blocking(move || auth.register(&username, &password))
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|registered| registered);
+    match registered {
+        Ok(true) => {
+            if let Err(e) = unlock_vault(&s, &req.username, &req.password).await {
+                println!("[Vault] Failed to unlock: {e}");
+            }
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(false) => error_response(StatusCode::CONFLICT, "already registered"),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct LoginRequest {
+    #[serde(default = "default_admin")]
+    username: String,
     password: String,
 }
 
-/// POST /auth/login  →  { token: "…" }
+#[derive(Serialize, ToSchema)]
+struct LoginResponse {
+    /// Send as `Authorization: Bearer …`.
+    token:    String,
+    username: String,
+    role:     Role,
+}
+
+/// POST /auth/login  →  { token: "…", username: "…", role: "admin" | "operator" | "viewer" }
+#[utoipa::path(
+    post, path = "/auth/login", tag = "auth",
+    request_body = LoginRequest,
+    security(()),
+    responses(
+        (status = 200, description = "`token`, `username` and `role`", body = LoginResponse),
+        (status = 401, description = "Invalid username or password", body = ErrorBody),
+        (status = 429, description = "Too many failed attempts; retry after `Retry-After` seconds", body = LockedOutBody),
+    ),
+)]
 async fn auth_login(
     State(s): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Json(req): Json<LoginRequest>,
 ) -> Response {
-    match s.auth.login(&req.password) {
-        Some(token) => Json(serde_json::json!({ "token": token })).into_response(),
-        None => (
-            StatusCode::UNAUTHORIZED,
-            Json(serde_json::json!({ "error": "invalid password" })),
-        ).into_response(),
+    let attempt = match s.login_guard.check(addr.ip()).await {
+        Ok(attempt) => attempt,
+        Err(retry) => return locked_out(retry),
+    };
+    let (auth, username, password) = (s.auth.clone(), req.username.clone(), req.password.clone());
+    let login = tokio::task::spawn_blocking(move || auth.login(&username, &password)).await.ok().flatten();
+    match login {
+        Some((token, role)) => {
+            attempt.succeeded();
+            // Only the vault owner's password can unwrap the vault key.
+            if role == Role::Admin
+                && let Err(e) = unlock_vault(&s, &req.username, &req.password).await
+            {
+                println!("[Vault] Failed to unlock: {e}");
+            }
+            Json(LoginResponse { token, username: req.username, role }).into_response()
+        }
+        None => password_failed(attempt, "invalid username or password"),
     }
 }
 
-/// POST /auth/logout
-async fn auth_logout(State(s): State<AppState>) -> StatusCode {
-    s.auth.logout();
+/// POST /auth/logout  →  ends the caller's session only
+#[utoipa::path(
+    post, path = "/auth/logout", tag = "auth",
+    security(()),
+    responses(
+        (status = 204, description = "Session ended"),
+    ),
+)]
+async fn auth_logout(State(s): State<AppState>, req: Request) -> StatusCode {
+    if let Some(token) = extract_bearer(&req) {
+        s.auth.logout(&token);
+    }
     StatusCode::NO_CONTENT
 }
 
+#[derive(Serialize, ToSchema)]
+struct CallerInfo {
+    username: String,
+    role:     Role,
+}
+
+/// GET /auth/me  →  { username, role } of the caller
+#[utoipa::path(
+    get, path = "/auth/me", tag = "auth",
+    responses(
+        (status = 200, description = "`username` and `role` of the caller", body = CallerInfo),
+    ),
+)]
+async fn auth_me(Extension(session): Extension<Session>) -> Json<CallerInfo> {
+    Json(CallerInfo { username: session.username, role: session.role })
+}
+
+#[derive(Serialize, ToSchema)]
+struct SessionListEntry {
+    #[serde(flatten)]
+    info:    SessionInfo,
+    /// True for the session that made this request.
+    current: bool,
+}
+
+/// GET /auth/sessions  →  the caller's sessions, or everyone's for an admin
+#[utoipa::path(
+    get, path = "/auth/sessions", tag = "auth",
+    responses(
+        (status = 200, description = "Sessions, each with a `current` flag", body = [SessionListEntry]),
+    ),
+)]
+async fn list_sessions(Extension(session): Extension<Session>, State(s): State<AppState>) -> Json<Vec<SessionListEntry>> {
+    let owner = (session.role != Role::Admin).then_some(session.username.as_str());
+    let list = s.auth.list_sessions(owner).into_iter()
+        .map(|info| SessionListEntry { current: info.id == session.id, info })
+        .collect();
+    Json(list)
+}
+
+/// DELETE /auth/sessions/{id}  →  ends one of the caller's sessions (any session for an admin)
+#[utoipa::path(
+    delete, path = "/auth/sessions/{id}", tag = "auth",
+    params(("id" = String, Path, description = "Session ID")),
+    responses(
+        (status = 204, description = "Session ended"),
+        (status = 404, description = "No such session"),
+    ),
+)]
+async fn revoke_session(
+    Extension(session): Extension<Session>,
+    State(s): State<AppState>,
+    Path(id): Path<String>,
+) -> StatusCode {
+    let owner = (session.role != Role::Admin).then_some(session.username.as_str());
+    if s.auth.revoke_session(&id, owner) {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+struct ChangePasswordRequest {
+    current_password: String,
+    new_password:     String,
+}
+
+/// POST /auth/password  →  sets a new password and ends the caller's other sessions
+#[utoipa::path(
+    post, path = "/auth/password", tag = "auth",
+    request_body = ChangePasswordRequest,
+    responses(
+        (status = 204, description = "Password changed"),
+        (status = 400, description = "New password is empty", body = ErrorBody),
+        (status = 401, description = "Invalid current password", body = ErrorBody),
+        (status = 429, description = "Too many failed attempts; retry after `Retry-After` seconds", body = LockedOutBody),
+        (status = 500, description = "Could not re-wrap the vault key or save the password; it is unchanged", body = ErrorBody),
+    ),
+)]
+async fn change_password(
+    Extension(session): Extension<Session>,
+    State(s): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(req): Json<ChangePasswordRequest>,
+) -> Response {
+    let attempt = match s.login_guard.check(addr.ip()).await {
+        Ok(attempt) => attempt,
+        Err(retry) => return locked_out(retry),
+    };
+    if verify_password(&s, &session.username, &req.current_password).await.is_none() {
+        return password_failed(attempt, "invalid password");
+    }
+    attempt.succeeded();
+    if req.new_password.is_empty() {
+        return error_response(StatusCode::BAD_REQUEST, "new password is empty");
+    }
+    // The master password also wraps the vault key. Re-wrap it first, so that if that
+    // fails the old password still opens both.
+    let rewrapped = match rewrap_vault(&s, &session.username, &req.current_password, &req.new_password).await {
+        Ok(rewrapped) => rewrapped,
+        Err(e) => {
+            let error = format!("cannot re-wrap the vault key: {e}");
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, error);
+        }
+    };
+    let (auth, username, new_password, keep) =
+        (s.auth.clone(), session.username.clone(), req.new_password.clone(), session.id.clone());
+    let changed = tokio::task::spawn_blocking(move || auth.change_password(&username, &new_password, &keep))
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|changed| changed);
+    if let Err(e) = changed {
+        if rewrapped
+            && let Err(undo) = rewrap_vault(&s, &session.username, &req.new_password, &req.current_password).await
+        {
+            println!("[Vault] Failed to re-wrap key for the old password again: {undo}");
+        }
+        return error_response(StatusCode::INTERNAL_SERVER_ERROR, e);
+    }
+    if rewrapped {
+        println!("[Vault] Re-wrapped key for the new master password");
+    }
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// Re-wrap the vault key for `new` if `username` owns the vault. `Ok(false)` when
+/// their password does not open it.
+async fn rewrap_vault(s: &AppState, username: &str, old: &str, new: &str) -> anyhow::Result<bool> {
+    let vault = s.manager.lock().unwrap().vault.clone();
+    if vault.owner().is_some_and(|owner| owner != username) {
+        return Ok(false);
+    }
+    let (old, new) = (old.to_string(), new.to_string());
+    tokio::task::spawn_blocking(move || vault.change_password(&old, &new)).await?
+}
+
+// ── Users (admin only) ────────────────────────────────────────────────────────
+
+/// GET /users  →  [{ username, role }]
+#[utoipa::path(
+    get, path = "/users", tag = "users",
+    responses(
+        (status = 200, description = "Every user", body = [UserInfo]),
+    ),
+)]
+async fn list_users(State(s): State<AppState>) -> Response {
+    Json(s.auth.list_users()).into_response()
+}
+
+#[derive(Deserialize, ToSchema)]
+struct AddUserRequest {
+    username: String,
+    password: String,
+    role:     Role,
+}
+
+/// POST /users  →  201, or 400 for a taken/invalid username or an empty password
+#[utoipa::path(
+    post, path = "/users", tag = "users",
+    request_body = AddUserRequest,
+    responses(
+        (status = 201, description = "User created"),
+        (status = 400, description = "Username taken or invalid, or empty password", body = ErrorBody),
+    ),
+)]
+async fn add_user(
+    State(s): State<AppState>,
+    Json(req): Json<AddUserRequest>,
+) -> Response {
+    if req.password.is_empty() {
+        return error_response(StatusCode::BAD_REQUEST, "password is empty");
+    }
+    let auth = s.auth.clone();
+    match tokio::task::spawn_blocking(move || auth.add_user(&req.username, &req.password, req.role)).await {
+        Ok(Ok(())) => StatusCode::CREATED.into_response(),
+        Ok(Err(e)) => error_response(StatusCode::BAD_REQUEST, e),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+struct UpdateUserRequest {
+    role: Role,
+}
+
+/// POST /users/{username}  →  change the user's role
+#[utoipa::path(
+    post, path = "/users/{username}", tag = "users",
+    params(("username" = String, Path)),
+    request_body = UpdateUserRequest,
+    responses(
+        (status = 204, description = "Role changed"),
+        (status = 400, description = "Unknown user or last admin", body = ErrorBody),
+        (status = 409, description = "Demoting the admin who holds the vault key", body = ErrorBody),
+    ),
+)]
+async fn update_user(
+    State(s): State<AppState>,
+    Path(username): Path<String>,
+    Json(req): Json<UpdateUserRequest>,
+) -> Response {
+    if req.role != Role::Admin
+        && let Some(conflict) = vault_owner_conflict(&s, &username)
+    {
+        return conflict;
+    }
+    match s.auth.set_role(&username, req.role) {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+/// DELETE /users/{username}  →  removes the user and ends their sessions
+#[utoipa::path(
+    delete, path = "/users/{username}", tag = "users",
+    params(("username" = String, Path)),
+    responses(
+        (status = 204, description = "User removed"),
+        (status = 400, description = "Unknown user or last admin", body = ErrorBody),
+        (status = 409, description = "The user holds the vault key", body = ErrorBody),
+    ),
+)]
+async fn remove_user(
+    State(s): State<AppState>,
+    Path(username): Path<String>,
+) -> Response {
+    if let Some(conflict) = vault_owner_conflict(&s, &username) {
+        return conflict;
+    }
+    match s.auth.remove_user(&username) {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+// ── API keys (admin only) ──────────────────────────────────────────────────────
+
+/// GET /api-keys  →  [{ id, name, prefix, scope, created_at, expires_at }]
+#[utoipa::path(
+    get, path = "/api-keys", tag = "api-keys",
+    responses(
+        (status = 200, description = "Every key, without secrets", body = [ApiKey]),
+    ),
+)]
+async fn list_api_keys(State(s): State<AppState>) -> Json<Vec<ApiKey>> {
+    Json(s.api_keys.list())
+}
+
+#[derive(Deserialize, ToSchema)]
+struct CreateApiKeyRequest {
+    name:       String,
+    scope:      Scope,
+    /// Unix timestamp; omitted or null for a key that never expires.
+    #[serde(default)]
+    expires_at: Option<u64>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct CreatedApiKey {
+    #[serde(flatten)]
+    info: ApiKey,
+    /// The full secret, `mori_…`; it is not stored and can't be shown again.
+    key:  String,
+}
+
+/// POST /api-keys  →  201 { key: "mori_…", ...ApiKey } — the key is only shown here
+#[utoipa::path(
+    post, path = "/api-keys", tag = "api-keys",
+    request_body = CreateApiKeyRequest,
+    responses(
+        (status = 201, description = "The new key plus the secret `key`, shown only here", body = CreatedApiKey),
+        (status = 400, description = "Invalid name", body = ErrorBody),
+    ),
+)]
+async fn create_api_key(
+    State(s): State<AppState>,
+    Json(req): Json<CreateApiKeyRequest>,
+) -> Response {
+    match s.api_keys.create(&req.name, req.scope, req.expires_at) {
+        Ok((info, key)) => (StatusCode::CREATED, Json(CreatedApiKey { info, key })).into_response(),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+/// DELETE /api-keys/{id}  →  revokes the key immediately
+#[utoipa::path(
+    delete, path = "/api-keys/{id}", tag = "api-keys",
+    params(("id" = String, Path, description = "Key ID")),
+    responses(
+        (status = 204, description = "Key revoked"),
+        (status = 404, description = "No such key"),
+        (status = 500, description = "Could not save the key store", body = ErrorBody),
+    ),
+)]
+async fn revoke_api_key(
+    State(s): State<AppState>,
+    Path(id): Path<String>,
+) -> Response {
+    match s.api_keys.revoke(&id) {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+// ── Vault ─────────────────────────────────────────────────────────────────────
+
+#[derive(Deserialize, ToSchema)]
+struct VaultUnlockRequest {
+    password: String,
+}
+
+/// POST /vault/unlock  →  opens the vault with the master password, e.g. when a
+/// session restored after a restart has not logged in again
+#[utoipa::path(
+    post, path = "/vault/unlock", tag = "vault",
+    request_body = VaultUnlockRequest,
+    responses(
+        (status = 204, description = "Vault unlocked"),
+        (status = 401, description = "Wrong master password", body = ErrorBody),
+        (status = 403, description = "The vault is owned by another admin", body = ErrorBody),
+        (status = 429, description = "Too many failed attempts; retry after `Retry-After` seconds", body = LockedOutBody),
+        (status = 500, description = "The vault file could not be read or written", body = ErrorBody),
+    ),
+)]
+async fn vault_unlock(
+    State(s): State<AppState>,
+    Extension(session): Extension<Session>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(req): Json<VaultUnlockRequest>,
+) -> Response {
+    let attempt = match s.login_guard.check(addr.ip()).await {
+        Ok(attempt) => attempt,
+        Err(retry) => return locked_out(retry),
+    };
+    let vault = s.manager.lock().unwrap().vault.clone();
+    if vault.is_unlocked() {
+        return StatusCode::NO_CONTENT.into_response();
+    }
+    if let Some(owner) = vault.owner().filter(|owner| *owner != session.username) {
+        return error_response(StatusCode::FORBIDDEN, format!("only {owner}'s password opens the vault"));
+    }
+    match unlock_vault(&s, &session.username, &req.password).await {
+        Ok(()) => attempt.succeeded(),
+        Err(e) if e.is::<WrongPassword>() => return password_failed(attempt, &e.to_string()),
+        // Not the caller's fault, so it doesn't count as a failed attempt.
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+    StatusCode::NO_CONTENT.into_response()
+}
+
+#[derive(Deserialize, ToSchema)]
+struct VaultExportRequest {
+    password: String,
+}
+
+#[derive(Serialize, ToSchema)]
+struct ExportedLogin {
+    id:       u32,
+    method:   &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ltoken:   Option<String>,
+}
+
+/// POST /vault/export  →  decrypted credentials of every bot (password re-check required)
+#[utoipa::path(
+    post, path = "/vault/export", tag = "vault",
+    request_body = VaultExportRequest,
+    responses(
+        (status = 200, description = "Decrypted credentials of every bot", body = [ExportedLogin]),
+        (status = 401, description = "Invalid password", body = ErrorBody),
+        (status = 500, description = "A secret could not be decrypted", body = ErrorBody),
+        (status = 429, description = "Too many failed attempts; retry after `Retry-After` seconds", body = LockedOutBody),
+    ),
+)]
+async fn vault_export(
+    State(s): State<AppState>,
+    Extension(session): Extension<Session>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(req): Json<VaultExportRequest>,
+) -> Response {
+    let attempt = match s.login_guard.check(addr.ip()).await {
+        Ok(attempt) => attempt,
+        Err(retry) => return locked_out(retry),
+    };
+    if verify_password(&s, &session.username, &req.password).await.is_none() {
+        return password_failed(attempt, "invalid password");
+    }
+    attempt.succeeded();
+    let logins = match s.manager.lock().unwrap().export_logins() {
+        Ok(logins) => logins,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+    let exported: Vec<ExportedLogin> = logins.into_iter().map(|(id, login)| match login {
+        BotLogin::Legacy { username, password } => ExportedLogin {
+            id, method: "legacy", username: Some(username), password: Some(password), ltoken: None,
+        },
+        BotLogin::Ltoken { ltoken } => ExportedLogin {
+            id, method: "ltoken", username: None, password: None, ltoken: Some(ltoken),
+        },
+    }).collect();
+    Json(exported).into_response()
+}
+
 // ── Bot handlers ───────────────────────────────────────────────────────────────
 
+#[utoipa::path(
+    get, path = "/bots", tag = "bots",
+    responses(
+        (status = 200, description = "Every running bot", body = [BotInfo]),
+    ),
+)]
 async fn list_bots(State(s): State<AppState>) -> Json<Vec<BotInfo>> {
     Json(s.manager.lock().unwrap().list())
 }
 
-#[derive(Deserialize)]
+/// Until the roster is restored, a new bot could take the ID of a saved one.
+fn roster_not_restored() -> Response {
+    error_response(StatusCode::LOCKED, "the vault is locked: log in or unlock it before adding bots")
+}
+
+/// ID of a bot or pooled proxy that was just added.
+#[derive(Serialize, ToSchema)]
+struct CreatedId {
+    id: u32,
+}
+
+#[derive(Deserialize, ToSchema)]
 struct SpawnRequest {
     username:       String,
     password:       String,
@@ -147,64 +874,190 @@ struct SpawnRequest {
     proxy_port:     Option<u16>,
     proxy_username: Option<String>,
     proxy_password: Option<String>,
+    /// Respawn this bot when the server restarts (default from `bot_defaults`).
+    autostart:      Option<bool>,
+    /// Take a proxy from the pool when no explicit proxy is given (default from `bot_defaults`).
+    use_proxy_pool: Option<bool>,
+    /// Respawn with backoff if the bot thread panics (default from `bot_defaults`).
+    restart_on_crash: Option<bool>,
 }
 
+/// Resolve an explicit proxy address, or a 400 response when it doesn't resolve.
+/// A DNS lookup blocks, so it runs off the async workers.
+async fn resolve_proxy(host: &str, port: u16) -> Result<SocketAddr, Response> {
+    let host = format!("{host}:{port}");
+    let lookup = host.clone();
+    let resolved = tokio::task::spawn_blocking(move || lookup.to_socket_addrs().ok()?.next()).await;
+    match resolved {
+        Ok(Some(addr)) => Ok(addr),
+        _ => Err(error_response(StatusCode::BAD_REQUEST, format!("cannot resolve proxy address '{host}'"))),
+    }
+}
+
+#[utoipa::path(
+    post, path = "/bots", tag = "bots",
+    request_body = SpawnRequest,
+    responses(
+        (status = 200, description = "`id` of the new bot", body = CreatedId),
+        (status = 400, description = "Unresolvable proxy address", body = ErrorBody),
+        (status = 423, description = "Vault locked; the saved roster is not restored yet", body = ErrorBody),
+        (status = 500, description = "The secret could not be stored in the vault; nothing was spawned", body = ErrorBody),
+    ),
+)]
 async fn spawn_bot(
     State(s): State<AppState>,
     Json(req): Json<SpawnRequest>,
-) -> Json<serde_json::Value> {
+) -> Response {
     let proxy = match (req.proxy_host, req.proxy_port) {
-        (Some(host), Some(port)) => {
-            let addr = format!("{}:{}", host, port)
-            .parse()
-            .or_else(|_| {
-                // Try to resolve the host if it's not a valid socket address
-                let mut addrs = format!("{}:{}", host, port)
-                    .to_socket_addrs()
-                    .map_err(|_| StatusCode::BAD_REQUEST)?;
-                addrs.next().ok_or(StatusCode::BAD_REQUEST)
-            })
-            .ok();
-            addr.map(|proxy_addr| Socks5Config {
+        (Some(host), Some(port)) => match resolve_proxy(&host, port).await {
+            Ok(proxy_addr) => Some(Socks5Config {
                 proxy_addr,
                 username: req.proxy_username,
                 password: req.proxy_password,
-            })
-        }
+            }),
+            Err(response) => return response,
+        },
         _ => None,
     };
-    let id = s.manager.lock().unwrap().spawn(req.username, req.password, proxy);
-    Json(serde_json::json!({ "id": id }))
+    let mut config = BotConfig::new(
+        BotLogin::Legacy { username: req.username, password: req.password },
+        proxy,
+        &s.config.bot_defaults,
+    );
+    config.autostart = req.autostart.unwrap_or(config.autostart);
+    config.use_proxy_pool = req.use_proxy_pool.unwrap_or(config.use_proxy_pool);
+    config.restart_on_crash = req.restart_on_crash.unwrap_or(config.restart_on_crash);
+    match s.manager.lock().unwrap().spawn(config) {
+        Ok(Some(id)) => Json(CreatedId { id }).into_response(),
+        Ok(None) => roster_not_restored(),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct SpawnLtokenRequest {
     ltoken:         String,
     proxy_host:     Option<String>,
     proxy_port:     Option<u16>,
     proxy_username: Option<String>,
     proxy_password: Option<String>,
+    /// Respawn this bot when the server restarts (default from `bot_defaults`).
+    autostart:      Option<bool>,
+    /// Take a proxy from the pool when no explicit proxy is given (default from `bot_defaults`).
+    use_proxy_pool: Option<bool>,
+    /// Respawn with backoff if the bot thread panics (default from `bot_defaults`).
+    restart_on_crash: Option<bool>,
 }
 
+#[utoipa::path(
+    post, path = "/bots/ltoken", tag = "bots",
+    request_body = SpawnLtokenRequest,
+    responses(
+        (status = 200, description = "`id` of the new bot", body = CreatedId),
+        (status = 400, description = "Unresolvable proxy address", body = ErrorBody),
+        (status = 423, description = "Vault locked; the saved roster is not restored yet", body = ErrorBody),
+        (status = 500, description = "The secret could not be stored in the vault; nothing was spawned", body = ErrorBody),
+    ),
+)]
 async fn spawn_ltoken_bot(
     State(s): State<AppState>,
     Json(req): Json<SpawnLtokenRequest>,
-) -> Json<serde_json::Value> {
+) -> Response {
     let proxy = match (req.proxy_host, req.proxy_port) {
-        (Some(host), Some(port)) => {
-            let addr = format!("{}:{}", host, port).parse().ok();
-            addr.map(|proxy_addr| Socks5Config {
+        (Some(host), Some(port)) => match resolve_proxy(&host, port).await {
+            Ok(proxy_addr) => Some(Socks5Config {
                 proxy_addr,
                 username: req.proxy_username,
                 password: req.proxy_password,
-            })
-        }
+            }),
+            Err(response) => return response,
+        },
         _ => None,
     };
-    let id = s.manager.lock().unwrap().spawn_ltoken(req.ltoken, proxy);
-    Json(serde_json::json!({ "id": id }))
+    let mut config = BotConfig::new(
+        BotLogin::Ltoken { ltoken: req.ltoken },
+        proxy,
+        &s.config.bot_defaults,
+    );
+    config.autostart = req.autostart.unwrap_or(config.autostart);
+    config.use_proxy_pool = req.use_proxy_pool.unwrap_or(config.use_proxy_pool);
+    config.restart_on_crash = req.restart_on_crash.unwrap_or(config.restart_on_crash);
+    match s.manager.lock().unwrap().spawn(config) {
+        Ok(Some(id)) => Json(CreatedId { id }).into_response(),
+        Ok(None) => roster_not_restored(),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+struct ImportRequest {
+    /// One account per line: `user:pass[:proxy_host:proxy_port[:proxy_user:proxy_pass]]` or `token|rid|mac|wk`.
+    accounts:    String,
+    /// Bots spawned per wave (default 5).
+    concurrency: Option<usize>,
+    /// Pause between waves in milliseconds (default 10000).
+    delay_ms:    Option<u64>,
+    /// Respawn the imported bots when the server restarts (default from `bot_defaults`).
+    autostart:   Option<bool>,
+    /// Take a pooled proxy for lines without one (default from `bot_defaults`).
+    use_proxy_pool: Option<bool>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct ImportResponse {
+    /// Valid lines, spawned in the background.
+    queued: usize,
+    errors: Vec<ImportError>,
+}
+
+/// POST /bots/import  →  validates every line, then spawns the valid ones in the background
+#[utoipa::path(
+    post, path = "/bots/import", tag = "bots",
+    request_body = ImportRequest,
+    responses(
+        (status = 202, description = "`queued` count and per-line `errors`", body = ImportResponse),
+        (status = 423, description = "Vault locked; the saved roster is not restored yet", body = ErrorBody),
+    ),
+)]
+async fn import_bots(
+    State(s): State<AppState>,
+    Json(req): Json<ImportRequest>,
+) -> Response {
+    if !s.manager.lock().unwrap().is_restored() {
+        return roster_not_restored();
+    }
+    // Proxy hosts are resolved with blocking DNS lookups.
+    let defaults = s.config.bot_defaults.clone();
+    let accounts = req.accounts;
+    let Ok((mut configs, errors)) =
+        tokio::task::spawn_blocking(move || import::parse_accounts(&accounts, &defaults)).await
+    else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    for config in &mut configs {
+        config.autostart = req.autostart.unwrap_or(config.autostart);
+        config.use_proxy_pool = req.use_proxy_pool.unwrap_or(config.use_proxy_pool);
+    }
+    let queued = configs.len();
+    if queued > 0 {
+        let concurrency = req.concurrency.unwrap_or(5).max(1);
+        let delay = std::time::Duration::from_millis(req.delay_ms.unwrap_or(10_000));
+        tokio::spawn(import::spawn_in_waves(s.manager.clone(), configs, concurrency, delay));
+    }
+    (
+        StatusCode::ACCEPTED,
+        Json(ImportResponse { queued, errors }),
+    ).into_response()
 }
 
+#[utoipa::path(
+    delete, path = "/bots/{id}", tag = "bots",
+    params(("id" = u32, Path, description = "Bot ID")),
+    responses(
+        (status = 204, description = "Bot stopped and removed"),
+        (status = 404, description = "No bot with this ID"),
+    ),
+)]
 async fn stop_bot(
     State(s): State<AppState>,
     Path(id): Path<u32>,
@@ -216,6 +1069,72 @@ async fn stop_bot(
     }
 }
 
+#[derive(Deserialize, ToSchema)]
+struct AutostartRequest {
+    enabled: bool,
+}
+
+#[utoipa::path(
+    post, path = "/bots/{id}/autostart", tag = "bots",
+    params(("id" = u32, Path, description = "Bot ID")),
+    request_body = AutostartRequest,
+    responses(
+        (status = 204, description = "Autostart changed"),
+        (status = 404, description = "No bot with this ID"),
+    ),
+)]
+async fn set_autostart(
+    State(s): State<AppState>,
+    Path(id): Path<u32>,
+    Json(req): Json<AutostartRequest>,
+) -> StatusCode {
+    if s.manager.lock().unwrap().set_autostart(id, req.enabled) {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+struct TagsRequest {
+    tags: Vec<String>,
+}
+
+/// POST /bots/{id}/tags  →  204; replaces the bot's tags
+#[utoipa::path(
+    post, path = "/bots/{id}/tags", tag = "bots",
+    params(("id" = u32, Path, description = "Bot ID")),
+    request_body = TagsRequest,
+    responses(
+        (status = 204, description = "Tags replaced"),
+        (status = 400, description = "Invalid tag", body = ErrorBody),
+        (status = 404, description = "No bot or roster entry with this ID"),
+    ),
+)]
+async fn set_tags(
+    State(s): State<AppState>,
+    Path(id): Path<u32>,
+    Json(req): Json<TagsRequest>,
+) -> axum::response::Response {
+    let tags = match crate::groups::normalize_tags(req.tags) {
+        Ok(tags) => tags,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, e),
+    };
+    if s.manager.lock().unwrap().set_tags(id, tags) {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        StatusCode::NOT_FOUND.into_response()
+    }
+}
+
+#[utoipa::path(
+    get, path = "/bots/{id}/state", tag = "bots",
+    params(("id" = u32, Path, description = "Bot ID")),
+    responses(
+        (status = 200, description = "Full bot state", body = BotState),
+        (status = 404, description = "No bot with this ID"),
+    ),
+)]
 async fn bot_state(
     State(s): State<AppState>,
     Path(id): Path<u32>,
@@ -226,32 +1145,8 @@ async fn bot_state(
         .ok_or(StatusCode::NOT_FOUND)
 }
 
-#[derive(Deserialize)]
-#[serde(tag = "type", rename_all = "snake_case")]
-enum CmdRequest {
-    Move { x: i32, y: i32 },
-    WalkTo { x: u32, y: u32 },
-    RunScript { content: String },
-    StopScript,
-    Wear { item_id: u32 },
-    Unwear { item_id: u32 },
-    Drop { item_id: u32, count: u32 },
-    Trash { item_id: u32, count: u32 },
-    SetDelays(BotDelays),
-    SetAutoCollect { enabled: bool },
-    SetCollectConfig {
-        radius_tiles: u8,
-        #[serde(default)]
-        blacklist: Vec<u16>,
-    },
-    SetAutoReconnect { enabled: bool },
-    Disconnect,
-    Reconnect,
-    AcceptAccess,
-    Warp { name: String, id: String },
-}
-
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 struct ItemsQuery {
     page:      Option<usize>,
     q:         Option<String>,
@@ -259,7 +1154,7 @@ struct ItemsQuery {
     get_items: Option<String>,
 }
 
-#[derive(serde::Serialize)]
+#[derive(Serialize, ToSchema)]
 struct ItemsResponse {
     items:     Vec<ItemInfo>,
     total:     usize,
@@ -269,6 +1164,13 @@ struct ItemsResponse {
 
 const ITEMS_PAGE_SIZE: usize = 50;
 
+#[utoipa::path(
+    get, path = "/items", tag = "items",
+    params(ItemsQuery),
+    responses(
+        (status = 200, description = "A page of items, or `[ItemInfo]` when `get-items` is set", body = ItemsResponse),
+    ),
+)]
 async fn list_items(
     State(s): State<AppState>,
     Query(params): Query<ItemsQuery>,
@@ -303,6 +1205,12 @@ async fn list_items(
     Json(ItemsResponse { items, total, page, page_size: ITEMS_PAGE_SIZE }).into_response()
 }
 
+#[utoipa::path(
+    get, path = "/items/names", tag = "items",
+    responses(
+        (status = 200, description = "Item names by ID", body = std::collections::HashMap<u32, String>),
+    ),
+)]
 async fn item_names(State(s): State<AppState>) -> Json<std::collections::HashMap<u32, String>> {
     let mgr = s.manager.lock().unwrap();
     let map = mgr.items_dat.items.iter()
@@ -311,6 +1219,12 @@ async fn item_names(State(s): State<AppState>) -> Json<std::collections::HashMap
     Json(map)
 }
 
+#[utoipa::path(
+    get, path = "/items/colors", tag = "items",
+    responses(
+        (status = 200, description = "Minimap RGB colors by item ID", body = std::collections::HashMap<u32, u32>),
+    ),
+)]
 async fn item_colors(State(s): State<AppState>) -> Json<std::collections::HashMap<u32, u32>> {
     let mgr = s.manager.lock().unwrap();
     let map = mgr.items_dat.items.iter()
@@ -330,45 +1244,84 @@ async fn item_colors(State(s): State<AppState>) -> Json<std::collections::HashMa
     Json(map)
 }
 
-async fn bot_cmd(
+/// Hand a command to bot `id`; shared by `POST /bots/{id}/cmd` and WebSocket `cmd` messages.
+/// Returns the ID the command is tracked under.
+fn dispatch_cmd(s: &AppState, id: u32, req: CmdRequest) -> Result<String, (StatusCode, String)> {
+    let request = serde_json::to_value(&req).unwrap_or_default();
+    let cmd = req.into_command(&s.config.scripts_dir())
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    s.manager.lock().unwrap()
+        .submit_cmd(id, request, cmd)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("bot {id} not found")))
+}
+
+#[utoipa::path(
+    get, path = "/bots/{id}/commands", tag = "commands",
+    params(("id" = u32, Path, description = "Bot ID")),
+    responses(
+        (status = 200, description = "Recent commands, newest first", body = [CommandRecord]),
+    ),
+)]
+async fn list_commands(
     State(s): State<AppState>,
     Path(id): Path<u32>,
-    Json(req): Json<CmdRequest>,
-) -> StatusCode {
-    let cmd = match req {
-        CmdRequest::Move { x, y }   => BotCommand::Move { x, y },
-        CmdRequest::WalkTo { x, y } => BotCommand::WalkTo { x, y },
-        CmdRequest::RunScript { content }   => BotCommand::RunScript { content },
-        CmdRequest::StopScript              => BotCommand::StopScript,
-        CmdRequest::Wear { item_id }        => BotCommand::Wear { item_id },
-        CmdRequest::Unwear { item_id }      => BotCommand::Unwear { item_id },
-        CmdRequest::Drop { item_id, count } => BotCommand::Drop { item_id, count },
-        CmdRequest::Trash { item_id, count } => BotCommand::Trash { item_id, count },
-        CmdRequest::SetDelays(d) => BotCommand::SetDelays(d),
-        CmdRequest::SetAutoCollect { enabled } => BotCommand::SetAutoCollect { enabled },
-        CmdRequest::SetCollectConfig {
-            radius_tiles,
-            blacklist,
-        } => BotCommand::SetCollectConfig {
-            radius_tiles,
-            blacklist,
-        },
-        CmdRequest::SetAutoReconnect { enabled } => BotCommand::SetAutoReconnect { enabled },
-        CmdRequest::Disconnect => BotCommand::Disconnect,
-        CmdRequest::Reconnect => BotCommand::Reconnect,
-        CmdRequest::AcceptAccess => BotCommand::AcceptAccess,
-        CmdRequest::Warp { name, id } => BotCommand::Warp { name, id },
-    };
-    if s.manager.lock().unwrap().send_cmd(id, cmd) {
-        StatusCode::NO_CONTENT
-    } else {
-        StatusCode::NOT_FOUND
+) -> Json<Vec<CommandRecord>> {
+    Json(s.manager.lock().unwrap().commands.list(id))
+}
+
+#[utoipa::path(
+    get, path = "/bots/{id}/commands/{cmd_id}", tag = "commands",
+    params(("id" = u32, Path, description = "Bot ID"), ("cmd_id" = String, Path, description = "Command ID")),
+    responses(
+        (status = 200, description = "The command", body = CommandRecord),
+        (status = 404, description = "No such command"),
+    ),
+)]
+async fn get_command(
+    State(s): State<AppState>,
+    Path((id, cmd_id)): Path<(u32, String)>,
+) -> Result<Json<CommandRecord>, StatusCode> {
+    s.manager.lock().unwrap()
+        .commands.get(id, &cmd_id)
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+#[derive(Serialize, ToSchema)]
+struct CmdQueued {
+    /// Look the command up with `/bots/{id}/commands/{cmd_id}`.
+    id:     String,
+    status: CmdStatus,
+}
+
+#[utoipa::path(
+    post, path = "/bots/{id}/cmd", tag = "commands",
+    params(("id" = u32, Path, description = "Bot ID")),
+    request_body = CmdRequest,
+    responses(
+        (status = 202, description = "`id` and `status` of the queued command", body = CmdQueued),
+        (status = 400, description = "Invalid command", body = ErrorBody),
+        (status = 404, description = "No bot with this ID"),
+    ),
+)]
+async fn bot_cmd(
+    State(s): State<AppState>,
+    Path(id): Path<u32>,
+    Json(req): Json<CmdRequest>,
+) -> axum::response::Response {
+    match dispatch_cmd(&s, id, req) {
+        Ok(cmd_id) => (
+            StatusCode::ACCEPTED,
+            Json(CmdQueued { id: cmd_id, status: CmdStatus::Queued }),
+        ).into_response(),
+        Err((StatusCode::NOT_FOUND, _)) => StatusCode::NOT_FOUND.into_response(),
+        Err((status, e)) => error_response(status, e),
     }
 }
 
 // ── Proxy test ────────────────────────────────────────────────────────────────
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct ProxyTestRequest {
     proxy_host:     String,
     proxy_port:     u16,
@@ -376,6 +1329,14 @@ struct ProxyTestRequest {
     proxy_password: Option<String>,
 }
 
+#[utoipa::path(
+    post, path = "/proxy/test", tag = "proxies",
+    request_body = ProxyTestRequest,
+    responses(
+        (status = 200, description = "Result of each check", body = ProxyTestResult),
+        (status = 400, description = "Unresolvable proxy address"),
+    ),
+)]
 async fn proxy_check(
     Json(req): Json<ProxyTestRequest>,
 ) -> Result<Json<ProxyTestResult>, StatusCode> {
@@ -402,36 +1363,907 @@ async fn proxy_check(
     Ok(Json(result))
 }
 
+// ── Proxy pool ────────────────────────────────────────────────────────────────
+
+#[utoipa::path(
+    get, path = "/proxies", tag = "proxies",
+    responses(
+        (status = 200, description = "Pool settings and proxies", body = PoolInfo),
+    ),
+)]
+async fn list_proxies(State(s): State<AppState>) -> Json<PoolInfo> {
+    let pool = s.manager.lock().unwrap().proxy_pool.clone();
+    Json(pool.info())
+}
+
+#[utoipa::path(
+    post, path = "/proxies", tag = "proxies",
+    request_body = ProxyTestRequest,
+    responses(
+        (status = 200, description = "`id` of the pooled proxy", body = CreatedId),
+        (status = 400, description = "Unresolvable proxy address", body = ErrorBody),
+        (status = 423, description = "The proxy has a password and the vault is locked", body = ErrorBody),
+        (status = 500, description = "The password could not be sealed", body = ErrorBody),
+    ),
+)]
+async fn add_proxy(
+    State(s): State<AppState>,
+    Json(req): Json<ProxyTestRequest>,
+) -> Response {
+    let proxy_addr = match resolve_proxy(&req.proxy_host, req.proxy_port).await {
+        Ok(proxy_addr) => proxy_addr,
+        Err(response) => return response,
+    };
+    let cfg = Socks5Config {
+        proxy_addr,
+        username: req.proxy_username,
+        password: req.proxy_password,
+    };
+    let mgr = s.manager.lock().unwrap();
+    match mgr.proxy_pool.add(cfg) {
+        Ok(id) => Json(CreatedId { id }).into_response(),
+        Err(e) if !mgr.vault.is_unlocked() => error_response(StatusCode::LOCKED, e),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+#[utoipa::path(
+    delete, path = "/proxies/{id}", tag = "proxies",
+    params(("id" = u32, Path, description = "Proxy ID")),
+    responses(
+        (status = 204, description = "Proxy removed"),
+        (status = 404, description = "No such proxy"),
+    ),
+)]
+async fn remove_proxy(
+    State(s): State<AppState>,
+    Path(id): Path<u32>,
+) -> StatusCode {
+    if s.manager.lock().unwrap().proxy_pool.remove(id) {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+/// POST /proxies/check  →  re-check every pooled proxy now and return the pool
+#[utoipa::path(
+    post, path = "/proxies/check", tag = "proxies",
+    responses(
+        (status = 200, description = "The pool after the check", body = PoolInfo),
+    ),
+)]
+async fn check_proxies(State(s): State<AppState>) -> Result<Json<PoolInfo>, StatusCode> {
+    let pool = s.manager.lock().unwrap().proxy_pool.clone();
+    let checked = pool.clone();
+    tokio::task::spawn_blocking(move || checked.check_all())
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(pool.info()))
+}
+
+#[derive(Deserialize, ToSchema)]
+struct PoolSettingsRequest {
+    max_bots_per_proxy:  Option<usize>,
+    check_interval_secs: Option<u64>,
+}
+
+#[utoipa::path(
+    post, path = "/proxies/settings", tag = "proxies",
+    request_body = PoolSettingsRequest,
+    responses(
+        (status = 204, description = "Settings changed"),
+    ),
+)]
+async fn proxy_pool_settings(
+    State(s): State<AppState>,
+    Json(req): Json<PoolSettingsRequest>,
+) -> StatusCode {
+    s.manager.lock().unwrap().proxy_pool.set_settings(req.max_bots_per_proxy, req.check_interval_secs);
+    StatusCode::NO_CONTENT
+}
+
+// ── Audit ─────────────────────────────────────────────────────────────────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct AuditQuery {
+    page:      Option<usize>,
+    page_size: Option<usize>,
+}
+
+#[utoipa::path(
+    get, path = "/audit", tag = "audit",
+    params(AuditQuery),
+    responses(
+        (status = 200, description = "A page of the audit log, newest first", body = AuditPage),
+    ),
+)]
+async fn list_audit(
+    State(s): State<AppState>,
+    Query(q): Query<AuditQuery>,
+) -> Result<Json<AuditPage>, StatusCode> {
+    let page = q.page.unwrap_or(1);
+    let page_size = q.page_size.unwrap_or(50).clamp(1, 500);
+    let audit = s.audit.clone();
+    tokio::task::spawn_blocking(move || audit.page(page, page_size))
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+// ── History ───────────────────────────────────────────────────────────────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct HistoryQuery {
+    /// `gems`, `level`, `awesomeness`, `world` or `item:<id>`; full samples when absent.
+    metric:      Option<String>,
+    /// Unix timestamps, inclusive.
+    from:        Option<u64>,
+    to:          Option<u64>,
+    /// Keep only the last point per window of this many seconds (needs `metric`).
+    bucket_secs: Option<u64>,
+}
+
+#[utoipa::path(
+    get, path = "/bots/{id}/history", tag = "bots",
+    params(("id" = u32, Path, description = "Bot ID"), HistoryQuery),
+    responses(
+        (status = 200, description = "Samples, or `Point`s when `metric` is set", body = [history::Sample]),
+        (status = 400, description = "Unknown metric", body = ErrorBody),
+        (status = 404, description = "No bot or kept history with this ID"),
+    ),
+)]
+async fn bot_history(
+    State(s): State<AppState>,
+    Path(id): Path<u32>,
+    Query(q): Query<HistoryQuery>,
+) -> axum::response::Response {
+    if let Some(metric) = q.metric.as_deref().filter(|m| !history::is_metric(m)) {
+        return error_response(StatusCode::BAD_REQUEST, format!("unknown metric '{metric}'"));
+    }
+    let store = {
+        let mgr = s.manager.lock().unwrap();
+        if !mgr.bots.contains_key(&id) && !mgr.history.exists(id) {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+        mgr.history.clone()
+    };
+    let (from, to) = (q.from.unwrap_or(0), q.to.unwrap_or(u64::MAX));
+    let Ok(samples) = tokio::task::spawn_blocking(move || store.read(id, from, to)).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+
+    let Some(metric) = q.metric else {
+        return Json(samples).into_response();
+    };
+    let mut points: Vec<history::Point> = samples.iter()
+        .filter_map(|sample| Some(history::Point {
+            ts:      sample.ts,
+            session: sample.session,
+            value:   sample.metric(&metric)?,
+        }))
+        .collect();
+    if let Some(bucket) = q.bucket_secs.filter(|b| *b > 0) {
+        points = history::downsample(points, bucket);
+    }
+    Json(points).into_response()
+}
+
+#[utoipa::path(
+    delete, path = "/bots/{id}/history", tag = "bots",
+    params(("id" = u32, Path, description = "Bot ID")),
+    responses(
+        (status = 204, description = "History deleted"),
+        (status = 404, description = "No history kept for this ID"),
+        (status = 409, description = "The bot is still running", body = ErrorBody),
+    ),
+)]
+async fn purge_history(
+    State(s): State<AppState>,
+    Path(id): Path<u32>,
+) -> axum::response::Response {
+    let mgr = s.manager.lock().unwrap();
+    if mgr.bots.contains_key(&id) {
+        return error_response(StatusCode::CONFLICT, "remove the bot before purging its history");
+    }
+    if mgr.history.remove(id) {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        StatusCode::NOT_FOUND.into_response()
+    }
+}
+
+// ── Console log ───────────────────────────────────────────────────────────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ConsoleParams {
+    /// Unix milliseconds; only later lines, oldest first. Pass the last `ts` to follow the log.
+    since:    Option<u64>,
+    /// Unix milliseconds; only earlier lines. Pass the first `ts` to page back.
+    before:   Option<u64>,
+    /// Case-insensitive text the line must contain.
+    q:        Option<String>,
+    /// Comma-separated list of `network`, `world`, `script` and `chat`; all when absent.
+    category: Option<String>,
+    /// Lines per page (default 200, at most 1000).
+    limit:    Option<usize>,
+}
+
+#[utoipa::path(
+    get, path = "/bots/{id}/console", tag = "bots",
+    params(("id" = u32, Path, description = "Bot ID"), ConsoleParams),
+    responses(
+        (status = 200, description = "Matching lines from the bot's console log", body = ConsolePage),
+        (status = 400, description = "Unknown category", body = ErrorBody),
+        (status = 404, description = "No bot or kept console log with this ID"),
+    ),
+)]
+async fn bot_console(
+    State(s): State<AppState>,
+    Path(id): Path<u32>,
+    Query(p): Query<ConsoleParams>,
+) -> axum::response::Response {
+    let mut categories = Vec::new();
+    for name in p.category.iter().flat_map(|c| c.split(',')).map(str::trim).filter(|c| !c.is_empty()) {
+        let Some(category) = ConsoleCategory::parse(name) else {
+            return error_response(StatusCode::BAD_REQUEST, format!("unknown category '{name}'"));
+        };
+        categories.push(category);
+    }
+    let log = {
+        let mgr = s.manager.lock().unwrap();
+        if !mgr.bots.contains_key(&id) && !mgr.console_log.exists(id) {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+        mgr.console_log.clone()
+    };
+    let query = ConsoleQuery {
+        since: p.since,
+        before: p.before,
+        q: p.q.filter(|q| !q.is_empty()),
+        categories,
+        limit: p.limit.unwrap_or(200).clamp(1, 1000),
+    };
+    match tokio::task::spawn_blocking(move || log.read(id, &query)).await {
+        Ok(page) => Json(page).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+#[utoipa::path(
+    delete, path = "/bots/{id}/console", tag = "bots",
+    params(("id" = u32, Path, description = "Bot ID")),
+    responses(
+        (status = 204, description = "Console log deleted"),
+        (status = 404, description = "No console log kept for this ID"),
+        (status = 409, description = "The bot is still running", body = ErrorBody),
+    ),
+)]
+async fn purge_console(
+    State(s): State<AppState>,
+    Path(id): Path<u32>,
+) -> axum::response::Response {
+    let mgr = s.manager.lock().unwrap();
+    if mgr.bots.contains_key(&id) {
+        return error_response(StatusCode::CONFLICT, "remove the bot before purging its console log");
+    }
+    if mgr.console_log.remove(id) {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        StatusCode::NOT_FOUND.into_response()
+    }
+}
+
+// ── Metrics ───────────────────────────────────────────────────────────────────
+
+#[utoipa::path(
+    get, path = "/metrics", tag = "metrics",
+    responses(
+        (status = 200, description = "Prometheus text format", body = String, content_type = "text/plain"),
+    ),
+)]
+async fn metrics_handler(State(s): State<AppState>) -> impl IntoResponse {
+    let body = metrics::render(&s.manager.lock().unwrap());
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+// ── Schedules ─────────────────────────────────────────────────────────────────
+
+#[utoipa::path(
+    get, path = "/schedules", tag = "schedules",
+    responses(
+        (status = 200, description = "Every job", body = [JobInfo]),
+    ),
+)]
+async fn list_schedules(State(s): State<AppState>) -> Json<Vec<JobInfo>> {
+    Json(s.manager.lock().unwrap().scheduler.list())
+}
+
+#[derive(Deserialize, ToSchema)]
+struct ScheduleRequest {
+    bot_id:  u32,
+    cron:    String,
+    command: CmdRequest,
+    enabled: Option<bool>,
+}
+
+#[utoipa::path(
+    post, path = "/schedules", tag = "schedules",
+    request_body = ScheduleRequest,
+    responses(
+        (status = 201, description = "The new job", body = crate::scheduler::Job),
+        (status = 400, description = "Invalid cron expression", body = ErrorBody),
+        (status = 404, description = "No bot with this ID", body = ErrorBody),
+    ),
+)]
+async fn add_schedule(
+    State(s): State<AppState>,
+    Json(req): Json<ScheduleRequest>,
+) -> axum::response::Response {
+    let mut mgr = s.manager.lock().unwrap();
+    if !mgr.bots.contains_key(&req.bot_id) {
+        return error_response(StatusCode::NOT_FOUND, format!("no bot with id {}", req.bot_id));
+    }
+    match mgr.scheduler.add(req.bot_id, req.cron, req.command, req.enabled.unwrap_or(true)) {
+        Ok(job) => (StatusCode::CREATED, Json(job)).into_response(),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, format!("invalid cron expression: {e}")),
+    }
+}
+
+#[utoipa::path(
+    post, path = "/schedules/{id}", tag = "schedules",
+    params(("id" = String, Path, description = "Job ID")),
+    request_body = JobPatch,
+    responses(
+        (status = 200, description = "The updated job", body = crate::scheduler::Job),
+        (status = 400, description = "Invalid cron expression", body = ErrorBody),
+        (status = 404, description = "No such job"),
+    ),
+)]
+async fn update_schedule(
+    State(s): State<AppState>,
+    Path(id): Path<String>,
+    Json(patch): Json<JobPatch>,
+) -> axum::response::Response {
+    match s.manager.lock().unwrap().scheduler.update(&id, patch) {
+        Ok(Some(job)) => Json(job).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, format!("invalid cron expression: {e}")),
+    }
+}
+
+#[utoipa::path(
+    delete, path = "/schedules/{id}", tag = "schedules",
+    params(("id" = String, Path, description = "Job ID")),
+    responses(
+        (status = 204, description = "Job removed"),
+        (status = 404, description = "No such job"),
+    ),
+)]
+async fn remove_schedule(
+    State(s): State<AppState>,
+    Path(id): Path<String>,
+) -> StatusCode {
+    if s.manager.lock().unwrap().scheduler.remove(&id) {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+// ── Groups ────────────────────────────────────────────────────────────────────
+
+#[utoipa::path(
+    get, path = "/groups", tag = "groups",
+    responses(
+        (status = 200, description = "Every group with its current members", body = [GroupInfo]),
+    ),
+)]
+async fn list_groups(State(s): State<AppState>) -> Json<Vec<GroupInfo>> {
+    let mgr = s.manager.lock().unwrap();
+    Json(mgr.groups.list().iter()
+        .map(|group| GroupInfo { members: mgr.group_members(group), group: group.clone() })
+        .collect())
+}
+
+#[derive(Deserialize, ToSchema)]
+struct GroupRequest {
+    name:    String,
+    #[serde(default)]
+    bot_ids: Vec<u32>,
+    #[serde(default)]
+    tags:    Vec<String>,
+}
+
+#[utoipa::path(
+    post, path = "/groups", tag = "groups",
+    request_body = GroupRequest,
+    responses(
+        (status = 201, description = "The new group", body = crate::groups::Group),
+        (status = 400, description = "Invalid name or tag", body = ErrorBody),
+        (status = 409, description = "Name taken", body = ErrorBody),
+    ),
+)]
+async fn add_group(
+    State(s): State<AppState>,
+    Json(req): Json<GroupRequest>,
+) -> axum::response::Response {
+    match s.manager.lock().unwrap().groups.create(req.name, req.bot_ids, req.tags) {
+        Ok(Some(group)) => (StatusCode::CREATED, Json(group)).into_response(),
+        Ok(None) => error_response(StatusCode::CONFLICT, "a group with this name already exists"),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[utoipa::path(
+    post, path = "/groups/{name}", tag = "groups",
+    params(("name" = String, Path)),
+    request_body = GroupPatch,
+    responses(
+        (status = 200, description = "The updated group", body = crate::groups::Group),
+        (status = 400, description = "Invalid tag", body = ErrorBody),
+        (status = 404, description = "No such group"),
+    ),
+)]
+async fn update_group(
+    State(s): State<AppState>,
+    Path(name): Path<String>,
+    Json(patch): Json<GroupPatch>,
+) -> axum::response::Response {
+    match s.manager.lock().unwrap().groups.update(&name, patch) {
+        Ok(Some(group)) => Json(group).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[utoipa::path(
+    delete, path = "/groups/{name}", tag = "groups",
+    params(("name" = String, Path)),
+    responses(
+        (status = 204, description = "Group removed"),
+        (status = 404, description = "No such group"),
+    ),
+)]
+async fn remove_group(
+    State(s): State<AppState>,
+    Path(name): Path<String>,
+) -> StatusCode {
+    if s.manager.lock().unwrap().groups.remove(&name) {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+struct BroadcastResult {
+    results: Vec<Delivery>,
+}
+
+/// Convert `req` once and send it to every bot in `ids`.
+fn broadcast(s: &AppState, ids: &[u32], req: CmdRequest) -> axum::response::Response {
+    let request = serde_json::to_value(&req).unwrap_or_default();
+    let cmd = match req.into_command(&s.config.scripts_dir()) {
+        Ok(cmd) => cmd,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, e),
+    };
+    let results = s.manager.lock().unwrap().broadcast_cmd(ids, request, cmd);
+    Json(BroadcastResult { results }).into_response()
+}
+
+/// POST /groups/{name}/cmd  →  { results: [{ bot_id, ok, error }] }
+#[utoipa::path(
+    post, path = "/groups/{name}/cmd", tag = "commands",
+    params(("name" = String, Path)),
+    request_body = CmdRequest,
+    responses(
+        (status = 200, description = "One delivery per member", body = BroadcastResult),
+        (status = 400, description = "Invalid command", body = ErrorBody),
+        (status = 404, description = "No such group"),
+    ),
+)]
+async fn group_cmd(
+    State(s): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<CmdRequest>,
+) -> axum::response::Response {
+    let members = {
+        let mgr = s.manager.lock().unwrap();
+        mgr.groups.get(&name).map(|group| mgr.group_members(group))
+    };
+    match members {
+        Some(ids) => broadcast(&s, &ids, req),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+struct BroadcastRequest {
+    #[serde(default)]
+    bot_ids: Vec<u32>,
+    /// Also send to every running bot with one of these tags.
+    #[serde(default)]
+    tags:    Vec<String>,
+    cmd:     CmdRequest,
+}
+
+/// POST /bots/cmd  →  { results: [{ bot_id, ok, error }] }
+#[utoipa::path(
+    post, path = "/bots/cmd", tag = "commands",
+    request_body = BroadcastRequest,
+    responses(
+        (status = 200, description = "One delivery per bot", body = BroadcastResult),
+        (status = 400, description = "Invalid command", body = ErrorBody),
+    ),
+)]
+async fn bots_cmd(
+    State(s): State<AppState>,
+    Json(req): Json<BroadcastRequest>,
+) -> axum::response::Response {
+    let mut ids = req.bot_ids;
+    ids.extend(s.manager.lock().unwrap().tagged(&req.tags));
+    ids.sort_unstable();
+    ids.dedup();
+    broadcast(&s, &ids, req.cmd)
+}
+
+// ── Script library ────────────────────────────────────────────────────────────
+
+#[derive(Serialize, ToSchema)]
+struct ScriptDetail {
+    #[serde(flatten)]
+    script:  Script,
+    /// Content of the latest version.
+    content: String,
+}
+
+#[derive(Serialize, ToSchema)]
+struct ScriptVersionDetail {
+    #[serde(flatten)]
+    version: ScriptVersion,
+    content: String,
+}
+
+#[utoipa::path(
+    get, path = "/scripts", tag = "scripts",
+    responses(
+        (status = 200, description = "Every stored script with its version history", body = [Script]),
+    ),
+)]
+async fn list_scripts(State(s): State<AppState>) -> Json<Vec<Script>> {
+    Json(s.manager.lock().unwrap().scripts.list())
+}
+
+#[derive(Deserialize, ToSchema)]
+struct ScriptRequest {
+    name:        String,
+    content:     String,
+    #[serde(default)]
+    description: String,
+    /// Defaults to the caller's username or API key name.
+    author:      Option<String>,
+    #[serde(default)]
+    tags:        Vec<String>,
+}
+
+#[utoipa::path(
+    post, path = "/scripts", tag = "scripts",
+    request_body = ScriptRequest,
+    responses(
+        (status = 201, description = "The new script at version 1", body = Script),
+        (status = 400, description = "Invalid name, tag or empty content", body = ErrorBody),
+        (status = 409, description = "Name taken", body = ErrorBody),
+    ),
+)]
+async fn add_script(
+    State(s): State<AppState>,
+    Extension(caller): Extension<Caller>,
+    Json(req): Json<ScriptRequest>,
+) -> axum::response::Response {
+    let author = req.author.unwrap_or_else(|| caller.name().to_string());
+    let new = NewScript { name: req.name, content: req.content, description: req.description, author, tags: req.tags };
+    match s.manager.lock().unwrap().scripts.create(new, Some(caller.identity())) {
+        Ok(Some(script)) => (StatusCode::CREATED, Json(script)).into_response(),
+        Ok(None) => error_response(StatusCode::CONFLICT, "a script with this name already exists"),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[utoipa::path(
+    get, path = "/scripts/{name}", tag = "scripts",
+    params(("name" = String, Path)),
+    responses(
+        (status = 200, description = "The script with the content of its latest version", body = ScriptDetail),
+        (status = 404, description = "No such script"),
+    ),
+)]
+async fn get_script(
+    State(s): State<AppState>,
+    Path(name): Path<String>,
+) -> axum::response::Response {
+    let mgr = s.manager.lock().unwrap();
+    let Some(script) = mgr.scripts.get(&name) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match mgr.scripts.read(&name, None) {
+        Ok((_, content)) => Json(ScriptDetail { script, content }).into_response(),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+#[utoipa::path(
+    post, path = "/scripts/{name}", tag = "scripts",
+    params(("name" = String, Path)),
+    request_body = ScriptPatch,
+    responses(
+        (status = 200, description = "The updated script", body = Script),
+        (status = 400, description = "Invalid tag or empty content", body = ErrorBody),
+        (status = 404, description = "No such script"),
+    ),
+)]
+async fn update_script(
+    State(s): State<AppState>,
+    Extension(caller): Extension<Caller>,
+    Path(name): Path<String>,
+    Json(patch): Json<ScriptPatch>,
+) -> axum::response::Response {
+    match s.manager.lock().unwrap().scripts.update(&name, patch, Some(caller.identity())) {
+        Ok(Some(script)) => Json(script).into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct RemoveScriptQuery {
+    /// Remove the script even though scheduled jobs still run it.
+    #[serde(default)]
+    force: bool,
+}
+
+/// Body of the `409` returned when scheduled jobs still run a script.
+#[derive(Serialize, ToSchema)]
+struct ScriptInUseBody {
+    error: String,
+    /// IDs of the jobs that run the script.
+    jobs:  Vec<String>,
+}
+
+#[utoipa::path(
+    delete, path = "/scripts/{name}", tag = "scripts",
+    params(("name" = String, Path), RemoveScriptQuery),
+    responses(
+        (status = 204, description = "Script and all its versions removed"),
+        (status = 404, description = "No such script"),
+        (status = 409, description = "Scheduled jobs still run the script", body = ScriptInUseBody),
+    ),
+)]
+async fn remove_script(
+    State(s): State<AppState>,
+    Path(name): Path<String>,
+    Query(q): Query<RemoveScriptQuery>,
+) -> axum::response::Response {
+    let mgr = s.manager.lock().unwrap();
+    if mgr.scripts.get(&name).is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let jobs = mgr.scheduler.jobs_running_script(&name);
+    if !jobs.is_empty() && !q.force {
+        let error = format!("{} scheduled job(s) still run this script", jobs.len());
+        return (StatusCode::CONFLICT, Json(ScriptInUseBody { error, jobs })).into_response();
+    }
+    if !mgr.scripts.remove(&name) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    if !jobs.is_empty() {
+        println!("[Scripts] Removed '{name}' while jobs {} still run it", jobs.join(", "));
+    }
+    StatusCode::NO_CONTENT.into_response()
+}
+
+#[utoipa::path(
+    get, path = "/scripts/{name}/versions/{version}", tag = "scripts",
+    params(("name" = String, Path), ("version" = u32, Path)),
+    responses(
+        (status = 200, description = "One version with its content", body = ScriptVersionDetail),
+        (status = 404, description = "No such script or version"),
+    ),
+)]
+async fn get_script_version(
+    State(s): State<AppState>,
+    Path((name, version)): Path<(String, u32)>,
+) -> axum::response::Response {
+    let mgr = s.manager.lock().unwrap();
+    let Some(meta) = mgr.scripts.get(&name)
+        .and_then(|script| script.versions.into_iter().find(|v| v.version == version))
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match mgr.scripts.read(&name, Some(version)) {
+        Ok((_, content)) => Json(ScriptVersionDetail { version: meta, content }).into_response(),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
 // ── WebSocket handler ─────────────────────────────────────────────────────────
 
+/// How often an open socket checks that its session or API key is still valid.
+const WS_REVALIDATE_EVERY: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+struct WsQuery {
+    token: String,
+}
+
 async fn ws_handler(
     ws: WebSocketUpgrade,
     State(s): State<AppState>,
-) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, s.ws_tx.subscribe()))
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Query(query): Query<WsQuery>,
+) -> Response {
+    if Caller::resolve(&s, &query.token).is_none() {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    let rx = s.ws_tx.subscribe();
+    ws.on_upgrade(move |socket| handle_socket(socket, rx, s, query.token, addr.ip()))
+}
+
+/// Close the socket because its session or key is no longer valid.
+async fn close_revoked(mut socket: WebSocket) {
+    let frame = CloseFrame { code: close_code::POLICY, reason: "session or API key is no longer valid".into() };
+    let _ = socket.send(Message::Close(Some(frame))).await;
+}
+
+async fn send_event(socket: &mut WebSocket, event: &WsEvent) -> bool {
+    let Ok(msg) = serde_json::to_string(event) else {
+        return true;
+    };
+    socket.send(Message::Text(msg.into())).await.is_ok()
+}
+
+/// Send a `Snapshot` of the subscribed bots to this socket only.
+async fn send_snapshot(
+    socket: &mut WebSocket,
+    manager: &SharedManager,
+    sub: &Subscription,
+    reason: SnapshotReason,
+) -> bool {
+    // Only the handles are taken under the manager lock; serializing happens after.
+    let states = manager.lock().unwrap().bot_states();
+    let bots = states.into_iter()
+        .filter(|(id, _)| sub.wants_bot(*id))
+        .filter_map(|(id, state)| {
+            let mut value = serde_json::to_value(&*state.read().unwrap()).ok()?;
+            value["bot_id"] = id.into();
+            Some(value)
+        })
+        .collect();
+    send_event(socket, &WsEvent::Snapshot { reason, bots }).await
 }
 
 async fn handle_socket(
     mut socket: WebSocket,
-    mut rx: tokio::sync::broadcast::Receiver<crate::events::WsEvent>,
+    mut rx: tokio::sync::broadcast::Receiver<WsEvent>,
+    s: AppState,
+    token: String,
+    ip: IpAddr,
 ) {
+    let mut sub = Subscription::default();
+    // Subscribed before the snapshot is taken, so nothing falls between the two.
+    if !send_snapshot(&mut socket, &s.manager, &sub, SnapshotReason::Connect).await {
+        return;
+    }
+    let mut revalidate = tokio::time::interval(WS_REVALIDATE_EVERY);
+    revalidate.tick().await;
     loop {
-        match rx.recv().await {
-            Ok(event) => {
-                let msg = match serde_json::to_string(&event) {
-                    Ok(s)  => s,
-                    Err(_) => continue,
-                };
-                if socket.send(Message::Text(msg.into())).await.is_err() {
-                    break;
+        tokio::select! {
+            _ = revalidate.tick() => if Caller::resolve(&s, &token).is_none() {
+                return close_revoked(socket).await;
+            },
+            event = rx.recv() => match event {
+                Ok(event) => {
+                    if sub.wants(&event) && !send_event(&mut socket, &event).await {
+                        break;
+                    }
                 }
-            }
-            Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
-            Err(_) => break,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
+                    // Skip the stale backlog and resend everything instead.
+                    rx = rx.resubscribe();
+                    if !send_snapshot(&mut socket, &s.manager, &sub, SnapshotReason::Lagged).await {
+                        break;
+                    }
+                }
+                Err(_) => break,
+            },
+            msg = socket.recv() => match msg {
+                Some(Ok(Message::Text(text))) => {
+                    // Checked per message too, so a revoked caller can't send one more `cmd`.
+                    let Some(caller) = Caller::resolve(&s, &token) else {
+                        return close_revoked(socket).await;
+                    };
+                    let ack = handle_client_message(&s, &caller, ip, &mut sub, &text).await;
+                    if !send_event(&mut socket, &ack).await {
+                        break;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
         }
     }
 }
 
+/// Apply one client message and build its `Ack`.
+async fn handle_client_message(
+    s: &AppState,
+    caller: &Caller,
+    ip: IpAddr,
+    sub: &mut Subscription,
+    text: &str,
+) -> WsEvent {
+    let msg: WsClientMessage = match serde_json::from_str(text) {
+        Ok(msg) => msg,
+        Err(e) => {
+            // Still echo the id when the rest of the message is malformed.
+            let id = serde_json::from_str::<serde_json::Value>(text).ok()
+                .and_then(|v| v["id"].as_str().map(str::to_owned));
+            return WsEvent::Ack { id, ok: false, error: Some(format!("invalid message: {e}")), cmd_id: None };
+        }
+    };
+    let result = match msg.request {
+        WsRequest::Subscribe { bots, events }   => sub.subscribe(bots, events).map(|_| None),
+        WsRequest::Unsubscribe { bots, events } => sub.unsubscribe(bots, events).map(|_| None),
+        WsRequest::Reset => {
+            *sub = Subscription::default();
+            Ok(None)
+        }
+        WsRequest::Cmd { bot_id, cmd } => ws_cmd(s, caller, ip, bot_id, cmd).await.map(Some),
+    };
+    match result {
+        Ok(cmd_id) => WsEvent::Ack { id: msg.id, ok: true, error: None, cmd_id },
+        Err(e) => WsEvent::Ack { id: msg.id, ok: false, error: Some(e), cmd_id: None },
+    }
+}
+
+/// Run a `cmd` message and record it in the audit log like the HTTP route.
+async fn ws_cmd(
+    s: &AppState,
+    caller: &Caller,
+    ip: IpAddr,
+    bot_id: u32,
+    cmd: CmdRequest,
+) -> Result<String, String> {
+    let command = serde_json::to_value(&cmd).ok();
+    // Same check the auth middleware applies to `POST /bots/{id}/cmd`.
+    let result = if caller.permits(&Method::POST, &format!("/bots/{bot_id}/cmd")) {
+        dispatch_cmd(s, bot_id, cmd)
+    } else {
+        Err((StatusCode::FORBIDDEN, "not allowed to command bots".to_string()))
+    };
+    let entry = AuditEntry {
+        ts:       crate::history::unix_now(),
+        identity: Some(caller.identity()),
+        ip:       ip.to_string(),
+        method:   "WS".into(),
+        path:     format!("/bots/{bot_id}/cmd"),
+        command,
+        status:   result.as_ref().map_or_else(|(status, _)| status.as_u16(), |_| StatusCode::ACCEPTED.as_u16()),
+        note:     None,
+    };
+    let audit = s.audit.clone();
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = audit.append(&entry) {
+            println!("[Audit] Failed to write audit.jsonl: {e}");
+        }
+    });
+    result.map_err(|(_, e)| e)
+}
+
 async fn growtopia_cdn(Path(path): Path<String>) -> Response {
     let url = format!("https://growserver-cache.netlify.app/{}", path);
     match tokio::task::spawn_blocking(move || ureq::get(&url).call()).await {
@@ -454,12 +2286,13 @@ async fn growtopia_cdn(Path(path): Path<String>) -> Response {
     }
 }
 
-async fn index_html() -> impl IntoResponse {
-    let dist = std::env::current_dir()
-        .unwrap_or_else(|_| std::path::PathBuf::from("."))
-        .join("dist");
+/// GET /openapi.json  →  OpenAPI 3 description of this API
+async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(crate::openapi::spec().clone())
+}
 
-    let html = tokio::fs::read_to_string(dist.join("index.html"))
+async fn index_html(State(s): State<AppState>) -> impl IntoResponse {
+    let html = tokio::fs::read_to_string(s.config.dist_dir.join("index.html"))
         .await
         .unwrap_or_default();
 
@@ -471,49 +2304,127 @@ async fn index_html() -> impl IntoResponse {
     axum::response::Html(injected)
 }
 
-pub async fn serve(manager: SharedManager, ws_tx: WsTx) {
-    let auth = AuthState::new();
-    let state = AppState { manager, ws_tx, auth };
+/// Every JSON API route. The OpenAPI document is built from this same table, so a
+/// route can't be served without being described.
+pub fn api_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::with_openapi(crate::openapi::ApiDoc::openapi())
+        // Auth endpoints (public)
+        .routes(routes!(auth_status))
+        .routes(routes!(auth_setup))
+        .routes(routes!(auth_login))
+        .routes(routes!(auth_logout))
+        .routes(routes!(auth_me))
+        .routes(routes!(list_sessions))
+        .routes(routes!(revoke_session))
+        .routes(routes!(change_password))
+
+        // Protected API
+        .routes(routes!(list_bots, spawn_bot))
+        .routes(routes!(spawn_ltoken_bot))
+        .routes(routes!(import_bots))
+        .routes(routes!(bots_cmd))
+        .routes(routes!(stop_bot))
+        .routes(routes!(bot_state))
+        .routes(routes!(bot_history, purge_history))
+        .routes(routes!(bot_console, purge_console))
+        .routes(routes!(bot_cmd))
+        .routes(routes!(list_commands))
+        .routes(routes!(get_command))
+        .routes(routes!(set_autostart))
+        .routes(routes!(set_tags))
+        .routes(routes!(list_items))
+        .routes(routes!(item_names))
+        .routes(routes!(item_colors))
+        .routes(routes!(proxy_check))
+        .routes(routes!(list_proxies, add_proxy))
+        .routes(routes!(remove_proxy))
+        .routes(routes!(check_proxies))
+        .routes(routes!(proxy_pool_settings))
+        .routes(routes!(metrics_handler))
+        .routes(routes!(list_audit))
+        .routes(routes!(list_groups, add_group))
+        .routes(routes!(update_group, remove_group))
+        .routes(routes!(group_cmd))
+        .routes(routes!(list_schedules, add_schedule))
+        .routes(routes!(update_schedule, remove_schedule))
+        .routes(routes!(list_scripts, add_script))
+        .routes(routes!(get_script, update_script, remove_script))
+        .routes(routes!(get_script_version))
+        .routes(routes!(vault_unlock))
+        .routes(routes!(vault_export))
+        .routes(routes!(list_users, add_user))
+        .routes(routes!(update_user, remove_user))
+        .routes(routes!(list_api_keys, create_api_key))
+        .routes(routes!(revoke_api_key))
+}
+
+pub async fn serve(manager: SharedManager, ws_tx: WsTx, config: Arc<Config>) {
+    let auth = match AuthState::new(&config.data_dir, config.sessions.clone()) {
+        Ok(auth) => auth,
+        Err(e) => {
+            println!("[Auth] {e} — refusing to start; fix or restore the file");
+            std::process::exit(1);
+        }
+    };
+    let audit = AuditLog::new(&config.data_dir);
+    let api_keys = match ApiKeyStore::new(&config.data_dir) {
+        Ok(api_keys) => api_keys,
+        Err(e) => {
+            println!("[ApiKeys] {e} — refusing to start; fix or restore the file");
+            std::process::exit(1);
+        }
+    };
+    let state = AppState {
+        manager,
+        ws_tx,
+        auth,
+        config: config.clone(),
+        audit,
+        api_keys,
+        login_guard: LoginGuard::default(),
+    };
 
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods([Method::GET, Method::POST, Method::DELETE])
         .allow_headers(Any);
 
-    let dist = std::env::current_dir()
-        .unwrap_or_else(|_| std::path::PathBuf::from("."))
-        .join("dist");
+    let dist = &config.dist_dir;
 
     let app = Router::new()
         .route("/", get(index_html))
-
-        // Auth endpoints (public)
-        .route("/auth/status", get(auth_status))
-        .route("/auth/setup",  post(auth_setup))
-        .route("/auth/login",  post(auth_login))
-        .route("/auth/logout", post(auth_logout))
-
-        // Protected API
-        .route("/bots", get(list_bots).post(spawn_bot))
-        .route("/bots/ltoken", post(spawn_ltoken_bot))
-        .route("/bots/{id}", delete(stop_bot))
-        .route("/bots/{id}/state", get(bot_state))
-        .route("/bots/{id}/cmd", post(bot_cmd))
-        .route("/items", get(list_items))
-        .route("/items/names", get(item_names))
-        .route("/items/colors", get(item_colors))
-        .route("/proxy/test", post(proxy_check))
+        .route("/openapi.json", get(openapi_json))
         .route("/growtopia-cdn/{*path}", get(growtopia_cdn))
         .route("/ws", get(ws_handler))
+        .merge(api_routes().split_for_parts().0)
 
         .layer(middleware::from_fn_with_state(state.clone(), auth_middleware))
+        .layer(middleware::from_fn_with_state(state.clone(), audit_middleware))
         .layer(cors)
         .with_state(state)
-        .fallback_service(ServeDir::new(&dist).fallback(ServeFile::new(dist.join("index.html"))));
+        .fallback_service(ServeDir::new(dist).fallback(ServeFile::new(dist.join("index.html"))));
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
-    println!("Dashboard  http://localhost:3000");
-    println!("WebSocket  ws://localhost:3000/ws");
-    println!("API        http://localhost:3000/bots");
-    axum::serve(listener, app).await.unwrap();
+    let tls = match crate::tls::load(&config).await {
+        Ok(tls) => tls,
+        Err(e) => {
+            println!("[TLS] {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let addr = format!("{}:{}", config.listen_host, config.listen_port);
+    let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
+    let port = config.listen_port;
+    let (http, ws) = if tls.is_some() { ("https", "wss") } else { ("http", "ws") };
+    println!("Dashboard  {http}://localhost:{port}");
+    println!("WebSocket  {ws}://localhost:{port}/ws");
+    println!("API        {http}://localhost:{port}/bots");
+    let make_service = app.into_make_service_with_connect_info::<SocketAddr>();
+    match tls {
+        Some(tls) => axum_server::from_tcp_rustls(listener.into_std().unwrap(), tls)
+            .serve(make_service)
+            .await
+            .unwrap(),
+        None => axum::serve(listener, make_service).await.unwrap(),
+    }
 }
@@ -336,7 +336,7 @@ bitflags::bitflags! {
 
 // ── TileType ──────────────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
 #[serde(tag = "type")]
 pub enum TileType {
     Basic,
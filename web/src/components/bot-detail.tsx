@@ -45,6 +45,12 @@ const STATUS_DOT: Record<BotStatus, string> = {
   too_many_logins: "bg-purple-500",
   update_required: "bg-gray-500",
   maintenance: "bg-amber-500",
+  wrong_credentials: "bg-red-700",
+  login_exhausted: "bg-red-700",
+  token_invalid: "bg-red-700",
+  proxy_failed: "bg-red-700",
+  stopped: "bg-gray-500",
+  crashed: "bg-red-700",
 };
 
 export function BotDetail({ bot }: { bot: LiveBot }) {
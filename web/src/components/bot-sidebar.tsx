@@ -18,6 +18,12 @@ const STATUS_COLOR: Record<BotStatus, string> = {
   too_many_logins: 'bg-purple-500',
   update_required: 'bg-gray-500',
   maintenance: 'bg-amber-500',
+  wrong_credentials: 'bg-red-700',
+  login_exhausted: 'bg-red-700',
+  token_invalid: 'bg-red-700',
+  proxy_failed: 'bg-red-700',
+  stopped: 'bg-gray-500',
+  crashed: 'bg-red-700',
 }
 
 const STATUS_LABEL: Record<BotStatus, string> = {
@@ -29,6 +35,12 @@ const STATUS_LABEL: Record<BotStatus, string> = {
   too_many_logins: 'Too Many Logins',
   update_required: 'Update Required',
   maintenance: 'Maintenance',
+  wrong_credentials: 'Wrong Credentials',
+  login_exhausted: 'Login Exhausted',
+  token_invalid: 'Token Invalid',
+  proxy_failed: 'Proxy Failed',
+  stopped: 'Stopped',
+  crashed: 'Crashed',
 }
 
 export function BotSidebar({ onClose }: { onClose?: () => void }) {
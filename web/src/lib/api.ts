@@ -56,7 +56,13 @@ export type BotStatus =
   | "server_overloaded"
   | "too_many_logins"
   | "update_required"
-  | "maintenance";
+  | "maintenance"
+  | "wrong_credentials"
+  | "login_exhausted"
+  | "token_invalid"
+  | "proxy_failed"
+  | "stopped"
+  | "crashed";
 
 export interface BotSummary {
   id: number;
@@ -154,7 +160,21 @@ export type BotCmd =
   | { type: "move"; x: number; y: number }
   | { type: "walk_to"; x: number; y: number }
   | { type: "run_script"; content: string }
+  | { type: "run_stored_script"; name: string; version?: number }
   | { type: "stop_script" }
+  | { type: "say"; text: string }
+  | { type: "place"; x: number; y: number; item_id: number }
+  | { type: "hit"; x: number; y: number }
+  | { type: "wrench"; x: number; y: number }
+  | { type: "wrench_player"; net_id: number }
+  | { type: "active"; x: number; y: number }
+  | { type: "enter"; pass?: string }
+  | { type: "buy"; item: string }
+  | { type: "collect_object"; uid: number; range?: number }
+  | { type: "set_direction"; facing_left: boolean }
+  | { type: "leave_world" }
+  | { type: "respawn" }
+  | { type: "find_path"; x: number; y: number }
   | { type: "wear"; item_id: number }
   | { type: "unwear"; item_id: number }
   | { type: "drop"; item_id: number; count: number }
@@ -256,11 +276,11 @@ export const api = {
       headers: { "Content-Type": "application/json" },
       body: JSON.stringify({ password }),
     }),
-  authLogin: (password: string) =>
+  authLogin: (username: string, password: string) =>
     fetch(`${BASE}/auth/login`, {
       method: "POST",
       headers: { "Content-Type": "application/json" },
-      body: JSON.stringify({ password }),
+      body: JSON.stringify({ username, password }),
     }),
   authLogout: () => req<void>("POST", "/auth/logout"),
   getBots: () => req<BotSummary[]>("GET", "/bots"),
@@ -270,7 +290,7 @@ export const api = {
   deleteBot: (id: number) => req<void>("DELETE", `/bots/${id}`),
   getBotState: (id: number) => req<BotState>("GET", `/bots/${id}/state`),
   sendCmd: (id: number, cmd: BotCmd) =>
-    req<void>("POST", `/bots/${id}/cmd`, cmd),
+    req<{ id: string; status: "queued" }>("POST", `/bots/${id}/cmd`, cmd),
   getItemNames: () => req<Record<string, string>>("GET", "/items/names"),
   getItemColors: () => req<Record<string, number>>("GET", "/items/colors"),
   getItems: (page = 1, q = "") =>
@@ -1,6 +1,6 @@
 import { atom, useAtom, useAtomValue, useSetAtom } from 'jotai'
 import { useEffect } from 'react'
-import { moriWs, type TileData } from './ws'
+import { moriWs, type SnapshotBot, type TileData } from './ws'
 import type { BotStatus, BotSummary, InventoryItem, Player, WorldObject, ItemRecord } from './api'
 import { api } from './api'
 
@@ -55,6 +55,33 @@ export function makeBot(id: number, username: string): LiveBot {
   }
 }
 
+function botFromSnapshot(s: SnapshotBot): LiveBot {
+  return {
+    id: s.bot_id,
+    username: s.username,
+    status: s.status,
+    world_name: s.world_name,
+    pos_x: s.pos_x,
+    pos_y: s.pos_y,
+    gems: s.gems,
+    ping_ms: s.ping_ms,
+    world_width: s.world_width,
+    world_height: s.world_height,
+    tiles: s.tiles.map((t) => ({ fg: t.fg_item_id, bg: t.bg_item_id, flags: t.flags, tile_type: t.tile_type })),
+    players: new Map(s.players.map((p) => [p.net_id, p])),
+    objects: s.objects,
+    inventory: s.inventory,
+    inventory_slots: s.inventory_size,
+    console: s.console,
+    delays: s.delays,
+    track_info: s.track_info,
+    auto_collect: s.auto_collect,
+    collect_radius_tiles: s.collect_radius_tiles,
+    collect_blacklist: s.collect_blacklist,
+    auto_reconnect: s.auto_reconnect,
+  }
+}
+
 export const botsAtom = atom<Map<number, LiveBot>>(new Map())
 export const selectedBotIdAtom = atom<number | null>(null)
 export const itemNamesAtom = atom<Record<string, string>>({})
@@ -102,6 +129,9 @@ export function useMoriStore() {
     api.getItemColors().then(setItemColors).catch(() => {})
 
     const handlers: Array<[string, (d: never) => void]> = [
+      ['Snapshot', (d: { bots: SnapshotBot[] }) =>
+        setBots(new Map(d.bots.map((b) => [b.bot_id, botFromSnapshot(b)])))],
+
       ['BotAdded', (d: { bot_id: number; username: string }) =>
         setBots((m) => new Map(m).set(d.bot_id, makeBot(d.bot_id, d.username)))],
 
@@ -1,4 +1,4 @@
-import type { BotStatus, WorldObject, InventoryItem } from './api'
+import type { BotState, BotStatus, WorldObject, InventoryItem } from './api'
 import { authStore } from './auth'
 
 // ── Event payload types ────────────────────────────────────────────────────
@@ -10,11 +10,24 @@ export interface TileData {
   tile_type: { type: string } & Record<string, unknown>
 }
 
+/** One bot in a `Snapshot`: the `/bots/{id}/state` body plus its ID. */
+export type SnapshotBot = Omit<BotState, 'inventory_slots'> & {
+  bot_id: number
+  username: string
+  inventory_size: number
+}
+
 export type WsEventMap = {
+  Snapshot: { reason: 'connect' | 'lagged'; bots: SnapshotBot[] }
+  Ack: { id: string | null; ok: boolean; error: string | null; cmd_id?: string }
+  CommandFinished: { bot_id: number; cmd_id: string; ok: boolean; error: string | null }
   BotAdded: { bot_id: number; username: string }
   BotRemoved: { bot_id: number }
   BotStatus: { bot_id: number; status: BotStatus }
+  BotExited: { bot_id: number; status: BotStatus; error: string | null; restart_in_secs: number | null }
   BotWorld: { bot_id: number; world_name: string }
+  JobFired: { job_id: string; bot_id: number }
+  JobFailed: { job_id: string; bot_id: number; error: string }
   BotMove: { bot_id: number; x: number; y: number }
   BotGems: { bot_id: number; gems: number }
   BotPing: { bot_id: number; ping_ms: number }
@@ -35,7 +48,7 @@ export type WsEventMap = {
   TileUpdate: { bot_id: number; x: number; y: number; fg: number; bg: number }
   ObjectsUpdate: { bot_id: number; objects: WorldObject[] }
   InventoryUpdate: { bot_id: number; gems: number; items: InventoryItem[] }
-  Console: { bot_id: number; message: string }
+  Console: { bot_id: number; category: "network" | "world" | "script" | "chat"; message: string }
 }
 
 type Listener<K extends keyof WsEventMap> = (data: WsEventMap[K]) => void
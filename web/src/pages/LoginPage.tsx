@@ -1,5 +1,5 @@
 import { useState } from 'react'
-import { Lock, LogIn, Eye, EyeOff } from 'lucide-react'
+import { Lock, LogIn, Eye, EyeOff, User } from 'lucide-react'
 import { api } from '@/lib/api'
 import { authStore } from '@/lib/auth'
 
@@ -8,6 +8,7 @@ interface Props {
 }
 
 export function LoginPage({ onDone }: Props) {
+  const [username, setUsername] = useState('admin')
   const [password, setPassword] = useState('')
   const [showPw, setShowPw] = useState(false)
   const [error, setError] = useState<string | null>(null)
@@ -18,13 +19,16 @@ export function LoginPage({ onDone }: Props) {
     setError(null)
     setLoading(true)
     try {
-      const res = await api.authLogin(password)
+      const res = await api.authLogin(username, password)
       if (res.ok) {
         const { token } = await res.json() as { token: string }
         authStore.setToken(token)
         onDone()
+      } else if (res.status === 429) {
+        const retry = res.headers.get('Retry-After') ?? '?'
+        setError(`Too many failed attempts. Try again in ${retry}s.`)
       } else {
-        setError('Incorrect password. Please try again.')
+        setError('Incorrect username or password. Please try again.')
       }
     } catch (err) {
       setError((err as Error).message)
@@ -41,9 +45,26 @@ export function LoginPage({ onDone }: Props) {
         </div>
 
         <h1 className="auth-title">Mori<span className="text-primary">.</span></h1>
-        <p className="auth-subtitle">Enter your password to continue.</p>
+        <p className="auth-subtitle">Sign in to continue.</p>
 
         <form onSubmit={handleSubmit} className="auth-form">
+          <div className="auth-field">
+            <label htmlFor="login-username" className="auth-label">Username</label>
+            <div className="auth-input-wrap">
+              <User className="auth-input-icon" />
+              <input
+                id="login-username"
+                type="text"
+                className="auth-input"
+                placeholder="Your username"
+                value={username}
+                onChange={e => setUsername(e.target.value)}
+                autoComplete="username"
+                required
+              />
+            </div>
+          </div>
+
           <div className="auth-field">
             <label htmlFor="login-password" className="auth-label">Password</label>
             <div className="auth-input-wrap">
@@ -36,7 +36,7 @@ export function SetupPage({ onDone }: Props) {
         return
       }
       // Auto-login after setup
-      const loginRes = await api.authLogin(password)
+      const loginRes = await api.authLogin('admin', password)
       if (loginRes.ok) {
         const { token } = await loginRes.json() as { token: string }
         authStore.setToken(token)